@@ -46,6 +46,13 @@ struct NapkinCrosshair {
     selected_id: Option<String>,
 }
 
+/// One page of a Napkin list endpoint; follow `next_cursor` to fetch the rest.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+struct NapkinPage<T> {
+    items: Vec<T>,
+    next_cursor: Option<String>,
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 struct NapkinProject {
     id: String,
@@ -57,6 +64,8 @@ struct NapkinProject {
 struct NapkinNode {
     project: String,
     id: String,
+    #[serde(default)]
+    labels: Vec<String>,
 }
 
 impl fmt::Display for NapkinNode {
@@ -75,6 +84,14 @@ struct NapkinEdge {
     id: String,
     source: String,
     target: String,
+    weight: Option<f64>,
+}
+
+impl NapkinEdge {
+    // Edge weight clamped to a range that keeps rendering and physics stable
+    fn strength(&self) -> f32 {
+        self.weight.unwrap_or(1.0).clamp(0.25, 4.0) as f32
+    }
 }
 
 impl fmt::Display for NapkinEdge {
@@ -118,6 +135,12 @@ struct NapkinSettings {
     edge_metadata: Vec<NapkinEdgeMetadata>,
     projects: Vec<NapkinProject>,
     project_search_string: String,
+    // Pages received so far for list syncs that are still following `next_cursor`
+    paged_nodes: Vec<NapkinNode>,
+    paged_node_metadata: Vec<NapkinNodeMetadata>,
+    paged_edges: Vec<NapkinEdge>,
+    paged_edge_metadata: Vec<NapkinEdgeMetadata>,
+    paged_projects: Vec<NapkinProject>,
 }
 
 impl Default for NapkinSettings {
@@ -137,6 +160,11 @@ impl Default for NapkinSettings {
             edge_metadata: Vec::new(),
             projects: Vec::new(),
             project_search_string: String::new(),
+            paged_nodes: Vec::new(),
+            paged_node_metadata: Vec::new(),
+            paged_edges: Vec::new(),
+            paged_edge_metadata: Vec::new(),
+            paged_projects: Vec::new(),
         }
     }
 }
@@ -156,11 +184,11 @@ fn main() {
         .init_resource::<NapkinSettings>()
         .insert_resource(Msaa::Sample8) // TODO: Implement a --performance-mode flag or other setting to disable this and other performance tweaks
         .insert_resource(ClearColor(Color::rgb(0., 0., 0.)))
-        .register_request_type::<Vec<NapkinProject>>()
-        .register_request_type::<Vec<NapkinNode>>()
-        .register_request_type::<Vec<NapkinEdge>>()
-        .register_request_type::<Vec<NapkinNodeMetadata>>()
-        .register_request_type::<Vec<NapkinEdgeMetadata>>()
+        .register_request_type::<NapkinPage<NapkinProject>>()
+        .register_request_type::<NapkinPage<NapkinNode>>()
+        .register_request_type::<NapkinPage<NapkinEdge>>()
+        .register_request_type::<NapkinPage<NapkinNodeMetadata>>()
+        .register_request_type::<NapkinPage<NapkinEdgeMetadata>>()
         .add_plugins((
             DefaultPlugins,
             EguiPlugin,
@@ -454,6 +482,9 @@ fn setup_ui(
                                 .unwrap();
                             let project_display = format!("@{}/{}", project.scope, project.name);
                             ui.label(format!("Node ID: {}", node_title));
+                            if !node.labels.is_empty() {
+                                ui.label(format!("Labels: {}", node.labels.join(", ")));
+                            }
                             ui.label(format!("Project: {}", project_display));
                         });
                     }
@@ -6,7 +6,10 @@ use bevy_egui::{
     egui::{self, Color32, CursorIcon},
     EguiContexts,
 };
-use bevy_http_client::prelude::TypedResponse;
+use bevy_http_client::{
+    prelude::{TypedRequest, TypedResponse},
+    HttpClient,
+};
 use bevy_rapier3d::{
     dynamics::{
         GravityScale, ImpulseJoint, RapierRigidBodyHandle, RigidBody, RopeJointBuilder
@@ -17,9 +20,9 @@ use bevy_rapier3d::{
 };
 use std::fmt;
 
-use crate::{NapkinEdge, NapkinSettings};
+use crate::{NapkinEdge, NapkinPage, NapkinSettings};
 
-use super::node_controller::NodeController;
+use super::{napkin_controller::page_url, node_controller::NodeController};
 
 pub struct EdgeControllerPlugin;
 
@@ -44,6 +47,7 @@ pub struct EdgeController {
     pub id: String,
     pub source: String,
     pub target: String,
+    pub weight: Option<f64>,
 }
 
 impl Default for EdgeController {
@@ -53,6 +57,7 @@ impl Default for EdgeController {
             id: "1234".to_string(),
             source: "1234".to_string(),
             target: "1234".to_string(),
+            weight: None,
         }
     }
 }
@@ -118,6 +123,7 @@ pub fn run_edge_controller(
             id: edge_controller.id.clone(),
             source: edge_controller.source.clone(),
             target: edge_controller.target.clone(),
+            weight: edge_controller.weight,
         });
     }
     napkin.hovered_edges = Some(new_selected_edges);
@@ -272,6 +278,9 @@ pub fn edge_tooltip(
                                     .highlight();
                             }
                             ui.label(format!("Edge ID: {}", edge.id));
+                            if let Some(weight) = edge.weight {
+                                ui.label(format!("Weight: {}", weight));
+                            }
                             ui.label("Metadata");
                             // let metadata_color = Color32::from_rgb(180, 180, 220);
                             for metadata in napkin
@@ -306,12 +315,26 @@ pub fn edge_spawner(
     mut napkin: ResMut<NapkinSettings>,
     existing_nodes: Query<(Entity, &mut NodeController)>,
     existing_edges: Query<&mut EdgeController>,
-    mut ev_response: EventReader<TypedResponse<Vec<NapkinEdge>>>,
+    mut ev_response: EventReader<TypedResponse<NapkinPage<NapkinEdge>>>,
+    mut edge_request: EventWriter<TypedRequest<NapkinPage<NapkinEdge>>>,
 ) {
     for response in ev_response.read() {
         info!("Received edges from server");
         napkin.is_connected = true;
-        napkin.edges = response.to_vec();
+        napkin.paged_edges.extend(response.items.iter().cloned());
+        match &response.next_cursor {
+            Some(cursor) => {
+                edge_request.send(
+                    HttpClient::new()
+                        .get(page_url(&napkin.server_url, "/edge", Some(cursor)))
+                        .with_type::<NapkinPage<NapkinEdge>>(),
+                );
+            }
+            None => {
+                let edges = std::mem::take(&mut napkin.paged_edges);
+                napkin.edges = edges;
+            }
+        }
     }
     for edge in napkin.edges.iter() {
         if existing_edges
@@ -342,7 +365,8 @@ pub fn edge_spawner(
 
                     let line_mesh = meshes.add(
                         Mesh::from(Capsule3d {
-                            radius: 0.02,
+                            // Heavier edges are drawn thicker
+                            radius: 0.02 * edge.strength(),
                             half_length: line_length / 2.,
                         })
                         .transformed_by(line_transform),
@@ -370,7 +394,7 @@ pub fn edge_spawner(
                                 id: edge.id.clone(),
                                 source: edge.source.clone(),
                                 target: edge.target.clone(),
-                                ..Default::default()
+                                weight: edge.weight,
                             },
                             RigidBody::Dynamic,
                             GravityScale(0.0),
@@ -1,7 +1,11 @@
 use bevy::prelude::*;
-use bevy_http_client::prelude::TypedResponse;
+use bevy_http_client::{
+    prelude::{TypedRequest, TypedResponse},
+    HttpClient,
+};
 
-use crate::{NapkinEdgeMetadata, NapkinSettings};
+use super::napkin_controller::page_url;
+use crate::{NapkinEdgeMetadata, NapkinPage, NapkinSettings};
 
 pub struct EdgeMetadataControllerPlugin;
 
@@ -19,11 +23,25 @@ impl Plugin for EdgeMetadataControllerPlugin {
 
 pub fn run_edge_metadata_controller(
   mut napkin: ResMut<NapkinSettings>,
-  mut ev_response: EventReader<TypedResponse<Vec<NapkinEdgeMetadata>>>,
+  mut ev_response: EventReader<TypedResponse<NapkinPage<NapkinEdgeMetadata>>>,
+  mut edge_metadata_request: EventWriter<TypedRequest<NapkinPage<NapkinEdgeMetadata>>>,
 ) {
   for response in ev_response.read() {
       info!("Received edge metadata from server");
       napkin.is_connected = true;
-      napkin.edge_metadata = response.to_vec();
+      napkin.paged_edge_metadata.extend(response.items.iter().cloned());
+      match &response.next_cursor {
+          Some(cursor) => {
+              edge_metadata_request.send(
+                  HttpClient::new()
+                      .get(page_url(&napkin.server_url, "/edge/metadata", Some(cursor)))
+                      .with_type::<NapkinPage<NapkinEdgeMetadata>>(),
+              );
+          }
+          None => {
+              let edge_metadata = std::mem::take(&mut napkin.paged_edge_metadata);
+              napkin.edge_metadata = edge_metadata;
+          }
+      }
   }
-}
\ No newline at end of file
+}
@@ -2,7 +2,10 @@ use bevy::prelude::*;
 use bevy_http_client::HttpClient;
 use std::fmt;
 
-use crate::{AtlasDiagnostics, NapkinSettings};
+use crate::{AtlasDiagnostics, NapkinPage, NapkinSettings};
+
+/// Largest page the server accepts, so a full sync takes as few requests as possible.
+pub const PAGE_LIMIT: usize = 1000;
 
 pub struct NapkinPlugin;
 
@@ -23,47 +26,81 @@ impl fmt::Display for Napkin {
     }
 }
 
+/// Builds the URL of a list endpoint page, starting after `after` when continuing a sync.
+pub fn page_url(server_url: &str, path: &str, after: Option<&str>) -> String {
+    match after {
+        Some(after) => format!(
+            "{}{}?limit={}&after={}",
+            server_url,
+            path,
+            PAGE_LIMIT,
+            encode_query_value(after)
+        ),
+        None => format!("{}{}?limit={}", server_url, path, PAGE_LIMIT),
+    }
+}
+
+// Metadata cursors embed the metadata name, which may contain reserved characters
+fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b':' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
 fn run_napkin_controller(
     atlas_diagnostics: ResMut<AtlasDiagnostics>,
-    napkin: ResMut<NapkinSettings>,
+    mut napkin: ResMut<NapkinSettings>,
     mut project_request: EventWriter<
-        bevy_http_client::prelude::TypedRequest<Vec<crate::NapkinProject>>,
+        bevy_http_client::prelude::TypedRequest<NapkinPage<crate::NapkinProject>>,
     >,
-    mut node_request: EventWriter<bevy_http_client::prelude::TypedRequest<Vec<crate::NapkinNode>>>,
-    mut edge_request: EventWriter<bevy_http_client::prelude::TypedRequest<Vec<crate::NapkinEdge>>>,
+    mut node_request: EventWriter<bevy_http_client::prelude::TypedRequest<NapkinPage<crate::NapkinNode>>>,
+    mut edge_request: EventWriter<bevy_http_client::prelude::TypedRequest<NapkinPage<crate::NapkinEdge>>>,
     mut node_metadata_request: EventWriter<
-        bevy_http_client::prelude::TypedRequest<Vec<crate::NapkinNodeMetadata>>,
+        bevy_http_client::prelude::TypedRequest<NapkinPage<crate::NapkinNodeMetadata>>,
     >,
     mut edge_metadata_request: EventWriter<
-        bevy_http_client::prelude::TypedRequest<Vec<crate::NapkinEdgeMetadata>>,
+        bevy_http_client::prelude::TypedRequest<NapkinPage<crate::NapkinEdgeMetadata>>,
     >,
 ) {
     let time = atlas_diagnostics.uptime;
     if time % 5.0 < 0.016 || time % 5.0 > 4.984 { // Adjusted to trigger around every 5 seconds, independent of frame rate
+        // Each sync starts from the first page, so drop anything left over from the last one
+        napkin.paged_projects.clear();
+        napkin.paged_nodes.clear();
+        napkin.paged_edges.clear();
+        napkin.paged_node_metadata.clear();
+        napkin.paged_edge_metadata.clear();
+
         project_request.send(
             HttpClient::new()
-                .get(format!("{}/project", napkin.server_url))
-                .with_type::<Vec<crate::NapkinProject>>(),
+                .get(page_url(&napkin.server_url, "/project", None))
+                .with_type::<NapkinPage<crate::NapkinProject>>(),
         );
         node_request.send(
             HttpClient::new()
-                .get(format!("{}/node", napkin.server_url))
-                .with_type::<Vec<crate::NapkinNode>>(),
+                .get(page_url(&napkin.server_url, "/node", None))
+                .with_type::<NapkinPage<crate::NapkinNode>>(),
         );
         edge_request.send(
             HttpClient::new()
-                .get(format!("{}/edge", napkin.server_url))
-                .with_type::<Vec<crate::NapkinEdge>>(),
+                .get(page_url(&napkin.server_url, "/edge", None))
+                .with_type::<NapkinPage<crate::NapkinEdge>>(),
         );
         node_metadata_request.send(
             HttpClient::new()
-                .get(format!("{}/node/metadata", napkin.server_url))
-                .with_type::<Vec<crate::NapkinNodeMetadata>>(),
+                .get(page_url(&napkin.server_url, "/node/metadata", None))
+                .with_type::<NapkinPage<crate::NapkinNodeMetadata>>(),
         );
         edge_metadata_request.send(
             HttpClient::new()
-                .get(format!("{}/edge/metadata", napkin.server_url))
-                .with_type::<Vec<crate::NapkinEdgeMetadata>>(),
+                .get(page_url(&napkin.server_url, "/edge/metadata", None))
+                .with_type::<NapkinPage<crate::NapkinEdgeMetadata>>(),
         );
     }
 }
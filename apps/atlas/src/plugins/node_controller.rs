@@ -3,7 +3,10 @@ use bevy_egui::{
     egui::{self, Color32, CursorIcon},
     EguiContexts,
 };
-use bevy_http_client::prelude::TypedResponse;
+use bevy_http_client::{
+    prelude::{TypedRequest, TypedResponse},
+    HttpClient,
+};
 use bevy_rapier3d::{
     dynamics::{GravityScale, RigidBody},
     geometry::{Collider, CollisionGroups, Group, SolverGroups},
@@ -12,7 +15,8 @@ use bevy_rapier3d::{
 };
 use std::fmt;
 
-use crate::{NapkinNode, NapkinSettings};
+use super::napkin_controller::page_url;
+use crate::{NapkinNode, NapkinPage, NapkinSettings};
 
 pub struct NodeControllerPlugin;
 
@@ -40,6 +44,7 @@ pub struct HoveredNode;
 pub struct NodeController {
     pub project: String,
     pub id: String,
+    pub labels: Vec<String>,
     pub position: Vec3,
 }
 
@@ -48,6 +53,7 @@ impl Default for NodeController {
         Self {
             project: "Unknown".to_string(),
             id: "1234".to_string(),
+            labels: Vec::new(),
             position: Vec3::ZERO,
         }
     }
@@ -85,6 +91,7 @@ pub fn run_node_controller(
         new_selected_nodes.push(NapkinNode {
             project: node_controller.project.clone(),
             id: node_controller.id.clone(),
+            labels: node_controller.labels.clone(),
         });
     }
     napkin.hovered_nodes = Some(new_selected_nodes);
@@ -121,11 +128,15 @@ pub fn _handle_node_physics(
             if i != j {
                 let direction = node_position - nodes[j].0;
                 let distance = direction.length();
-                let connected = napkin.edges.iter().any(|edge| 
+                let connection = napkin.edges.iter().find(|edge|
                     (edge.source == nodes[i].1.id && edge.target == nodes[j].1.id) ||
                     (edge.target == nodes[i].1.id && edge.source == nodes[j].1.id)
                 );
-                let repulsion_factor = if connected { 0.3 } else { 0.6 };
+                // Heavier edges act as stiffer springs, pulling their nodes closer together
+                let repulsion_factor = match connection {
+                    Some(edge) => 0.3 / edge.strength(),
+                    None => 0.6,
+                };
                 if distance > 0.0 {
                     let force_magnitude = repulsion_factor / distance.max(0.6); // Repulsive force inversely proportional to distance, adjusted by connection
                     velocities[i] += direction.normalize() * force_magnitude * delta_time;
@@ -177,6 +188,7 @@ pub fn handle_node_click(
             selected_nodes.push(NapkinNode {
                 project: node.project.clone(),
                 id: node.id.clone(),
+                labels: node.labels.clone(),
             });
             napkin.napkin_crosshair = crate::NapkinCrosshair {
                 selected_id: Some(node.id.clone()),
@@ -278,6 +290,10 @@ pub fn node_tooltip(
                                     .highlight();
                             }
                             ui.label(format!("Node ID: {}", node.id));
+                            if !node.labels.is_empty() {
+                                ui.label(format!("Labels: {}", node.labels.join(", ")))
+                                    .highlight();
+                            }
                             ui.label("Metadata");
                             let metadata_color = Color32::from_rgb(180, 180, 220);
                             for metadata in napkin
@@ -310,12 +326,26 @@ pub fn node_spawner(
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut napkin: ResMut<NapkinSettings>,
     existing_nodes: Query<&mut NodeController>,
-    mut ev_response: EventReader<TypedResponse<Vec<NapkinNode>>>,
+    mut ev_response: EventReader<TypedResponse<NapkinPage<NapkinNode>>>,
+    mut node_request: EventWriter<TypedRequest<NapkinPage<NapkinNode>>>,
 ) {
     for response in ev_response.read() {
         info!("Received nodes from server");
         napkin.is_connected = true;
-        napkin.nodes = response.to_vec();
+        napkin.paged_nodes.extend(response.items.iter().cloned());
+        match &response.next_cursor {
+            Some(cursor) => {
+                node_request.send(
+                    HttpClient::new()
+                        .get(page_url(&napkin.server_url, "/node", Some(cursor)))
+                        .with_type::<NapkinPage<NapkinNode>>(),
+                );
+            }
+            None => {
+                let nodes = std::mem::take(&mut napkin.paged_nodes);
+                napkin.nodes = nodes;
+            }
+        }
     }
     let mut filtered_nodes: Vec<&NapkinNode> = napkin.nodes.iter().collect();
     if let Some(selected_project) = &napkin.selected_project {
@@ -357,8 +387,8 @@ pub fn node_spawner(
                     NodeController {
                         project: node.project.clone(),
                         id: node.id.clone(),
+                        labels: node.labels.clone(),
                         position: start_point,
-                        ..Default::default()
                     },
                     RigidBody::Dynamic,
                     GravityScale(0.0),
@@ -1,7 +1,11 @@
 use bevy::prelude::*;
-use bevy_http_client::prelude::TypedResponse;
+use bevy_http_client::{
+    prelude::{TypedRequest, TypedResponse},
+    HttpClient,
+};
 
-use crate::{NapkinNodeMetadata, NapkinSettings};
+use super::napkin_controller::page_url;
+use crate::{NapkinNodeMetadata, NapkinPage, NapkinSettings};
 
 pub struct NodeMetadataControllerPlugin;
 
@@ -19,11 +23,25 @@ impl Plugin for NodeMetadataControllerPlugin {
 
 pub fn run_node_metadata_controller(
   mut napkin: ResMut<NapkinSettings>,
-  mut ev_response: EventReader<TypedResponse<Vec<NapkinNodeMetadata>>>,
+  mut ev_response: EventReader<TypedResponse<NapkinPage<NapkinNodeMetadata>>>,
+  mut node_metadata_request: EventWriter<TypedRequest<NapkinPage<NapkinNodeMetadata>>>,
 ) {
   for response in ev_response.read() {
       info!("Received node metadata from server");
       napkin.is_connected = true;
-      napkin.node_metadata = response.to_vec();
+      napkin.paged_node_metadata.extend(response.items.iter().cloned());
+      match &response.next_cursor {
+          Some(cursor) => {
+              node_metadata_request.send(
+                  HttpClient::new()
+                      .get(page_url(&napkin.server_url, "/node/metadata", Some(cursor)))
+                      .with_type::<NapkinPage<NapkinNodeMetadata>>(),
+              );
+          }
+          None => {
+              let node_metadata = std::mem::take(&mut napkin.paged_node_metadata);
+              napkin.node_metadata = node_metadata;
+          }
+      }
   }
-}
\ No newline at end of file
+}
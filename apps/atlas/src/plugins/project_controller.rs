@@ -1,7 +1,11 @@
 use bevy::prelude::*;
-use bevy_http_client::prelude::TypedResponse;
+use bevy_http_client::{
+    prelude::{TypedRequest, TypedResponse},
+    HttpClient,
+};
 
-use crate::{NapkinProject, NapkinSettings};
+use super::napkin_controller::page_url;
+use crate::{NapkinProject, NapkinPage, NapkinSettings};
 
 pub struct ProjectControllerPlugin;
 
@@ -19,11 +23,25 @@ impl Plugin for ProjectControllerPlugin {
 
 pub fn run_project_controller(
   mut napkin: ResMut<NapkinSettings>,
-  mut ev_response: EventReader<TypedResponse<Vec<NapkinProject>>>,
+  mut ev_response: EventReader<TypedResponse<NapkinPage<NapkinProject>>>,
+  mut project_request: EventWriter<TypedRequest<NapkinPage<NapkinProject>>>,
 ) {
   for response in ev_response.read() {
       info!("Received projects list from server");
       napkin.is_connected = true;
-      napkin.projects = response.to_vec();
+      napkin.paged_projects.extend(response.items.iter().cloned());
+      match &response.next_cursor {
+          Some(cursor) => {
+              project_request.send(
+                  HttpClient::new()
+                      .get(page_url(&napkin.server_url, "/project", Some(cursor)))
+                      .with_type::<NapkinPage<NapkinProject>>(),
+              );
+          }
+          None => {
+              let projects = std::mem::take(&mut napkin.paged_projects);
+              napkin.projects = projects;
+          }
+      }
   }
-}
\ No newline at end of file
+}
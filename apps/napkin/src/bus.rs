@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use futures_util::{stream, StreamExt};
+use log::{error, warn};
+use tokio::sync::broadcast;
+use tokio_postgres::{AsyncMessage, NoTls};
+
+use crate::models::events::ChangeEvent;
+
+// Events a subscriber may fall behind by before it is told it lagged
+const EVENT_BUFFER: usize = 1024;
+// Notified by the triggers in `database/create_tables.sql`
+pub const CHANGE_CHANNEL: &str = "napkin_changes";
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+// Fans change events out to every `/events` subscriber of this process
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<ChangeEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        EventBus { sender }
+    }
+}
+
+impl EventBus {
+    pub fn subscribe(&self) -> broadcast::Receiver<ChangeEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish(&self, event: ChangeEvent) {
+        // Fails only while nobody is subscribed
+        let _ = self.sender.send(event);
+    }
+}
+
+// Relays change notifications from the database onto `bus`, so writes by any napkin
+// instance or script reach subscribers. Holds its own connection outside the pool and
+// reconnects when it drops; changes committed while disconnected are not replayed.
+pub async fn listen_for_changes(config: tokio_postgres::Config, bus: EventBus) {
+    loop {
+        if let Err(cause) = relay_changes(&config, &bus).await {
+            warn!("Change listener disconnected: {}", cause);
+        }
+        actix_web::rt::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn relay_changes(config: &tokio_postgres::Config, bus: &EventBus) -> Result<(), Box<dyn std::error::Error>> {
+    let (client, mut connection) = config.connect(NoTls).await?;
+
+    // Notifications only arrive while the connection is polled, which also carries `LISTEN`
+    let relay = bus.clone();
+    let messages = actix_web::rt::spawn(async move {
+        let mut messages = stream::poll_fn(move |cx| connection.poll_message(cx));
+        while let Some(message) = messages.next().await {
+            match message? {
+                AsyncMessage::Notification(notification) => match serde_json::from_str::<ChangeEvent>(notification.payload()) {
+                    Ok(event) => relay.publish(event),
+                    Err(cause) => error!("Unreadable change notification: {}", cause),
+                },
+                _ => continue,
+            }
+        }
+        Ok::<_, tokio_postgres::Error>(())
+    });
+    client.batch_execute(&format!("LISTEN {};", CHANGE_CHANNEL)).await?;
+
+    // `client` must outlive the connection task, or the connection closes
+    messages.await??;
+    drop(client);
+    Ok(())
+}
@@ -38,6 +38,7 @@ impl TryFrom<::config::ConfigBuilder<::config::builder::DefaultState>> for Napki
 }
 
 impl From<NapkinConfig> for ::config::ConfigBuilder<::config::builder::DefaultState> {
+    #[allow(clippy::unnecessary_fallible_conversions)]
     fn from(cfg: NapkinConfig) -> Self {
         ::config::ConfigBuilder::try_from(cfg).unwrap()
     }
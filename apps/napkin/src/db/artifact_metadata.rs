@@ -0,0 +1,115 @@
+use deadpool_postgres::Client;
+use tokio_pg_mapper::FromTokioPostgresRow;
+
+use crate::{
+    db::{query, query_one},
+    errors::{NapkinError, NapkinErrorRoot},
+    models::artifact_metadata::ArtifactMetadata,
+};
+
+fn artifact_metadata_not_found() -> NapkinError {
+    NapkinError {
+        code: "ARTIFACT_METADATA_NO_ID",
+        message: "Artifact Metadata with ID ({owner_id}, {name}) Not Found",
+        root: NapkinErrorRoot::NotFound,
+    }
+}
+
+// The owner is missing or in the trash
+fn owner_not_found() -> NapkinError {
+    NapkinError {
+        code: "ARTIFACT_NO_ID",
+        message: "Artifact with Node ID {owner_id} Not Found",
+        root: NapkinErrorRoot::NotFound,
+    }
+}
+
+// Returns up to `limit + 1` rows so callers can tell whether another page exists;
+// a `None` limit returns every row
+pub async fn get_artifact_metadata(
+    client: &Client,
+    after: &Option<(uuid::Uuid, String)>,
+    limit: Option<i64>,
+) -> Result<Vec<ArtifactMetadata>, NapkinError> {
+    let _stmt = "SELECT $artifact_metadata_fields FROM artifact_metadata
+        JOIN nodes ON nodes.id = artifact_metadata.owner_id
+        WHERE nodes.deleted_at IS NULL AND ($1::uuid IS NULL OR (artifact_metadata.owner_id, artifact_metadata.name) > ($1, $2))
+        ORDER BY artifact_metadata.owner_id, artifact_metadata.name LIMIT $3;";
+    let _stmt = _stmt.replace("$artifact_metadata_fields", &ArtifactMetadata::sql_table_fields());
+    let (after_owner_id, after_name) = match after {
+        Some((owner_id, name)) => (Some(*owner_id), Some(name.as_str())),
+        None => (None, None),
+    };
+
+    query::<ArtifactMetadata>(client, &_stmt, &[&after_owner_id, &after_name, &limit.map(|limit| limit + 1)]).await
+}
+
+pub async fn add_artifact_metadata(client: &Client, artifact_metadata_info: ArtifactMetadata) -> Result<ArtifactMetadata, NapkinError> {
+    let _stmt = "INSERT INTO artifact_metadata(owner_id, name, value) SELECT $1::uuid, $2::text, $3::jsonb WHERE EXISTS (SELECT 1 FROM artifacts JOIN nodes ON nodes.id = artifacts.node_id WHERE artifacts.node_id = $1 AND nodes.deleted_at IS NULL) RETURNING $artifact_metadata_fields;";
+    let _stmt = _stmt.replace("$artifact_metadata_fields", &ArtifactMetadata::sql_table_fields());
+
+    query_one::<ArtifactMetadata>(
+        client,
+        &_stmt,
+        &[
+            &artifact_metadata_info.owner_id,
+            &artifact_metadata_info.name,
+            &artifact_metadata_info.value,
+        ],
+        owner_not_found(),
+    )
+    .await
+}
+
+pub async fn get_artifact_metadata_singleton(client: &Client, owner_id: &uuid::Uuid) -> Result<Vec<ArtifactMetadata>, NapkinError> {
+    let _stmt = "SELECT $artifact_metadata_fields FROM artifact_metadata JOIN nodes ON nodes.id = artifact_metadata.owner_id WHERE artifact_metadata.owner_id = $1 AND nodes.deleted_at IS NULL;";
+    let _stmt = _stmt.replace("$artifact_metadata_fields", &ArtifactMetadata::sql_table_fields());
+
+    let results = query::<ArtifactMetadata>(client, &_stmt, &[owner_id]).await?;
+
+    if results.is_empty() {
+        Err(artifact_metadata_not_found())
+    } else {
+        Ok(results)
+    }
+}
+
+pub async fn get_artifact_metadata_singleton_key(client: &Client, owner_id: &uuid::Uuid, name: &str) -> Result<ArtifactMetadata, NapkinError> {
+    let _stmt = "SELECT $artifact_metadata_fields FROM artifact_metadata JOIN nodes ON nodes.id = artifact_metadata.owner_id WHERE (artifact_metadata.owner_id = $1 AND artifact_metadata.name = $2) AND nodes.deleted_at IS NULL;";
+    let _stmt = _stmt.replace("$artifact_metadata_fields", &ArtifactMetadata::sql_table_fields());
+
+    query_one::<ArtifactMetadata>(client, &_stmt, &[owner_id, &name], artifact_metadata_not_found()).await
+}
+
+pub async fn update_artifact_metadata(
+    client: &Client,
+    owner_id: &uuid::Uuid,
+    name: &str,
+    artifact_metadata_info: ArtifactMetadata,
+    if_match: &Option<Vec<i64>>,
+) -> Result<ArtifactMetadata, NapkinError> {
+    let _stmt = "UPDATE artifact_metadata SET owner_id = $3, name = $4, value = $5 WHERE (owner_id = $1 AND name = $2) AND EXISTS (SELECT 1 FROM nodes WHERE id = $1 AND deleted_at IS NULL) AND EXISTS (SELECT 1 FROM nodes WHERE id = $3 AND deleted_at IS NULL) AND ($6::bigint[] IS NULL OR version = ANY($6)) RETURNING $artifact_metadata_fields;";
+    let _stmt = _stmt.replace("$artifact_metadata_fields", &ArtifactMetadata::sql_table_fields());
+
+    query_one::<ArtifactMetadata>(
+        client,
+        &_stmt,
+        &[
+            owner_id,
+            &name,
+            &artifact_metadata_info.owner_id,
+            &artifact_metadata_info.name,
+            &artifact_metadata_info.value,
+            if_match,
+        ],
+        artifact_metadata_not_found(),
+    )
+    .await
+}
+
+pub async fn delete_artifact_metadata(client: &Client, owner_id: &uuid::Uuid, name: &str, if_match: &Option<Vec<i64>>) -> Result<ArtifactMetadata, NapkinError> {
+    let _stmt = "DELETE FROM artifact_metadata WHERE (owner_id = $1 AND name = $2) AND EXISTS (SELECT 1 FROM nodes WHERE id = $1 AND deleted_at IS NULL) AND ($3::bigint[] IS NULL OR version = ANY($3)) RETURNING $artifact_metadata_fields;";
+    let _stmt = _stmt.replace("$artifact_metadata_fields", &ArtifactMetadata::sql_table_fields());
+
+    query_one::<ArtifactMetadata>(client, &_stmt, &[owner_id, &name, if_match], artifact_metadata_not_found()).await
+}
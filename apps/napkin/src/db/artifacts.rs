@@ -0,0 +1,129 @@
+use deadpool_postgres::Client;
+use tokio_pg_mapper::FromTokioPostgresRow;
+
+use crate::{
+    db::{
+        filters::{as_sql_params, compile_metadata_filter, SqlParams},
+        query, query_dynamic, query_one,
+    },
+    errors::{NapkinError, NapkinErrorRoot},
+    models::{
+        artifacts::{Artifact, ArtifactSearchResult, DistanceMetric, VectorSupport},
+        filters::MetadataFilter,
+    },
+};
+
+fn artifact_not_found() -> NapkinError {
+    NapkinError {
+        code: "ARTIFACT_NO_ID",
+        message: "Artifact with Node ID {node_id} Not Found",
+        root: NapkinErrorRoot::NotFound,
+    }
+}
+
+// Returns up to `limit + 1` rows so callers can tell whether another page exists;
+// a `None` limit returns every row
+pub async fn get_artifacts(client: &Client, after: &Option<uuid::Uuid>, limit: Option<i64>) -> Result<Vec<Artifact>, NapkinError> {
+    let _stmt = "SELECT $artifact_fields FROM artifacts
+        JOIN nodes ON nodes.id = artifacts.node_id
+        WHERE nodes.deleted_at IS NULL AND ($1::uuid IS NULL OR artifacts.node_id > $1)
+        ORDER BY artifacts.node_id LIMIT $2;";
+    let _stmt = _stmt.replace("$artifact_fields", &Artifact::sql_table_fields());
+
+    query::<Artifact>(client, &_stmt, &[after, &limit.map(|limit| limit + 1)]).await
+}
+
+pub async fn add_artifact(client: &Client, artifact_info: Artifact) -> Result<Artifact, NapkinError> {
+    let _stmt = "INSERT INTO artifacts(node_id, embedding) VALUES ($1, $2) ON CONFLICT (node_id) DO NOTHING RETURNING $artifact_fields;";
+    let _stmt = _stmt.replace("$artifact_fields", &Artifact::sql_table_fields());
+
+    query_one::<Artifact>(
+        client,
+        &_stmt,
+        &[&artifact_info.node_id, &artifact_info.embedding],
+        NapkinError {
+            code: "ARTIFACT_EXISTS",
+            message: "Node already has an Artifact, use PUT to replace it",
+            root: NapkinErrorRoot::Conflict,
+        },
+    )
+    .await
+}
+
+pub async fn get_artifact(client: &Client, node_id: &uuid::Uuid) -> Result<Artifact, NapkinError> {
+    let _stmt = "SELECT $artifact_fields FROM artifacts JOIN nodes ON nodes.id = artifacts.node_id WHERE artifacts.node_id = $1 AND nodes.deleted_at IS NULL;";
+    let _stmt = _stmt.replace("$artifact_fields", &Artifact::sql_table_fields());
+
+    query_one::<Artifact>(client, &_stmt, &[node_id], artifact_not_found()).await
+}
+
+pub async fn update_artifact(
+    client: &Client,
+    node_id: &uuid::Uuid,
+    embedding: &pgvector::Vector,
+    if_match: &Option<Vec<i64>>,
+) -> Result<Artifact, NapkinError> {
+    let _stmt = "UPDATE artifacts SET embedding = $2 WHERE node_id = $1 AND EXISTS (SELECT 1 FROM nodes WHERE id = $1 AND deleted_at IS NULL) AND ($3::bigint[] IS NULL OR version = ANY($3)) RETURNING $artifact_fields;";
+    let _stmt = _stmt.replace("$artifact_fields", &Artifact::sql_table_fields());
+
+    query_one::<Artifact>(client, &_stmt, &[node_id, embedding, if_match], artifact_not_found()).await
+}
+
+pub async fn delete_artifact(client: &Client, node_id: &uuid::Uuid, if_match: &Option<Vec<i64>>) -> Result<Artifact, NapkinError> {
+    let _stmt = "DELETE FROM artifacts WHERE node_id = $1 AND EXISTS (SELECT 1 FROM nodes WHERE id = $1 AND deleted_at IS NULL) AND ($2::bigint[] IS NULL OR version = ANY($2)) RETURNING $artifact_fields;";
+    let _stmt = _stmt.replace("$artifact_fields", &Artifact::sql_table_fields());
+
+    query_one::<Artifact>(client, &_stmt, &[node_id, if_match], artifact_not_found()).await
+}
+
+// The HNSW indexes only look at `hnsw.ef_search` candidates (40 by default) and filter
+// them afterwards, so the search widens that to `k` and, on pgvector 0.8+, keeps
+// scanning until `k` rows pass the filters. Older versions scan exactly when filtered.
+pub async fn search_artifacts(
+    client: &mut Client,
+    embedding: pgvector::Vector,
+    k: i64,
+    project: Option<uuid::Uuid>,
+    metric: DistanceMetric,
+    filter: Option<&MetadataFilter>,
+) -> Result<Vec<ArtifactSearchResult>, NapkinError> {
+    let filtered = project.is_some() || filter.is_some();
+    let mut params: SqlParams = vec![Box::new(embedding), Box::new(project), Box::new(k)];
+    let filter = match filter {
+        Some(filter) => compile_metadata_filter(filter, "artifacts.node_id", &mut params)?,
+        None => "TRUE".to_string(),
+    };
+    let _stmt = "SELECT artifacts.node_id, nodes.project, (artifacts.embedding $op $1)::float8 AS distance,
+            (SELECT COALESCE(jsonb_object_agg(m.name, m.value), '{}'::jsonb) FROM node_metadata m WHERE m.owner_id = artifacts.node_id) AS metadata
+        FROM artifacts
+        JOIN nodes ON nodes.id = artifacts.node_id
+        WHERE nodes.deleted_at IS NULL AND ($2::uuid IS NULL OR nodes.project = $2) AND $filter
+        ORDER BY artifacts.embedding $op $1
+        LIMIT $3;";
+    let _stmt = _stmt.replace("$op", metric.operator());
+    let _stmt = _stmt.replace("$filter", &filter);
+
+    let transaction = client.build_transaction().read_only(true).start().await?;
+    let iterative_scan = query::<VectorSupport>(
+        &transaction,
+        "SELECT string_to_array(extversion, '.')::int[] >= ARRAY[0, 8] AS iterative_scan FROM pg_extension WHERE extname = 'vector';",
+        &[],
+    )
+    .await?
+    .pop()
+    .is_some_and(|support| support.iterative_scan);
+    transaction
+        .batch_execute(&format!("SET LOCAL hnsw.ef_search = {};", k.max(40)))
+        .await?;
+    if iterative_scan {
+        transaction.batch_execute("SET LOCAL hnsw.iterative_scan = strict_order;").await?;
+    } else if filtered {
+        transaction.batch_execute("SET LOCAL enable_indexscan = off;").await?;
+    }
+
+    let results = query_dynamic::<ArtifactSearchResult>(&transaction, &_stmt, &as_sql_params(&params)).await?;
+
+    transaction.commit().await?;
+
+    Ok(results)
+}
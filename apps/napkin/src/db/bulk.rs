@@ -0,0 +1,96 @@
+use deadpool_postgres::GenericClient;
+
+use crate::{
+    bus::CHANGE_CHANNEL,
+    db::{execute, query},
+    errors::NapkinError,
+    models::bulk::{EdgeColumns, MetadataColumns, NodeColumns, RowId},
+};
+
+// Reserves `count` IDs up front so rows can reference each other before insertion,
+// sorted so IDs follow document order like individually posted rows would
+pub async fn generate_ids(client: &impl GenericClient, count: i64) -> Result<Vec<uuid::Uuid>, NapkinError> {
+    let _stmt = "SELECT generate_ulid() AS id FROM generate_series(1, $1::bigint);";
+
+    let mut ids: Vec<uuid::Uuid> = query::<RowId>(client, _stmt, &[&count])
+        .await?
+        .into_iter()
+        .map(|row| row.id)
+        .collect();
+    ids.sort();
+
+    Ok(ids)
+}
+
+// The subset of `node_ids` that exist in `project` and are not in the trash,
+// locked so none of them can be trashed before the transaction ends
+pub async fn get_existing_node_ids(
+    client: &impl GenericClient,
+    project: &uuid::Uuid,
+    node_ids: &[uuid::Uuid],
+) -> Result<Vec<uuid::Uuid>, NapkinError> {
+    let _stmt = "SELECT id FROM nodes WHERE project = $1 AND id = ANY($2) AND deleted_at IS NULL FOR SHARE;";
+
+    Ok(query::<RowId>(client, _stmt, &[project, &node_ids])
+        .await?
+        .into_iter()
+        .map(|row| row.id)
+        .collect())
+}
+
+pub async fn add_nodes(client: &impl GenericClient, project: &uuid::Uuid, nodes: &NodeColumns) -> Result<u64, NapkinError> {
+    let _stmt = "INSERT INTO nodes(id, project, labels)
+        SELECT t.id, $1, ARRAY(SELECT jsonb_array_elements_text(t.labels))
+        FROM unnest($2::uuid[], $3::jsonb[]) AS t(id, labels);";
+
+    execute(client, _stmt, &[project, &nodes.ids, &nodes.labels]).await
+}
+
+pub async fn add_edges(client: &impl GenericClient, project: &uuid::Uuid, edges: &EdgeColumns) -> Result<u64, NapkinError> {
+    let _stmt = "INSERT INTO edges(id, project, source, target, label, directed, weight)
+        SELECT t.id, $1, t.source, t.target, t.label, t.directed, t.weight
+        FROM unnest($2::uuid[], $3::uuid[], $4::uuid[], $5::text[], $6::bool[], $7::float8[])
+            AS t(id, source, target, label, directed, weight);";
+
+    execute(
+        client,
+        _stmt,
+        &[project, &edges.ids, &edges.sources, &edges.targets, &edges.labels, &edges.directed, &edges.weights],
+    )
+    .await
+}
+
+pub async fn add_node_metadata(client: &impl GenericClient, metadata: &MetadataColumns) -> Result<u64, NapkinError> {
+    let _stmt = "INSERT INTO node_metadata(owner_id, name, value)
+        SELECT * FROM unnest($1::uuid[], $2::text[], $3::jsonb[]);";
+
+    execute(client, _stmt, &[&metadata.owner_ids, &metadata.names, &metadata.values]).await
+}
+
+pub async fn add_edge_metadata(client: &impl GenericClient, metadata: &MetadataColumns) -> Result<u64, NapkinError> {
+    let _stmt = "INSERT INTO edge_metadata(owner_id, name, value)
+        SELECT * FROM unnest($1::uuid[], $2::text[], $3::jsonb[]);";
+
+    execute(client, _stmt, &[&metadata.owner_ids, &metadata.names, &metadata.values]).await
+}
+
+pub async fn add_artifact_metadata(client: &impl GenericClient, metadata: &MetadataColumns) -> Result<u64, NapkinError> {
+    let _stmt = "INSERT INTO artifact_metadata(owner_id, name, value)
+        SELECT * FROM unnest($1::uuid[], $2::text[], $3::jsonb[]);";
+
+    execute(client, _stmt, &[&metadata.owner_ids, &metadata.names, &metadata.values]).await
+}
+
+// Silences the per-row change notifications of the rest of the transaction
+pub async fn quiet_changes(client: &impl GenericClient) -> Result<(), NapkinError> {
+    client.batch_execute("SET LOCAL napkin.bulk_changes = 'on';").await?;
+
+    Ok(())
+}
+
+// Announces a bulk write to `project` as one `imported` change, delivered on commit
+pub async fn notify_import(client: &impl GenericClient, project: &uuid::Uuid, counts: &serde_json::Value) -> Result<u64, NapkinError> {
+    let _stmt = "SELECT pg_notify($1, jsonb_build_object('entity', 'project', 'action', 'imported', 'project', $2::uuid, 'data', $3::jsonb)::text);";
+
+    execute(client, _stmt, &[&CHANGE_CHANNEL, project, counts]).await
+}
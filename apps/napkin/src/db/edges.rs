@@ -2,132 +2,145 @@ use deadpool_postgres::Client;
 use tokio_pg_mapper::FromTokioPostgresRow;
 
 use crate::{
+    db::{query, query_one},
     errors::{NapkinError, NapkinErrorRoot},
     models::edges::Edge,
 };
 
-pub async fn get_edges(client: &Client) -> Result<Vec<Edge>, NapkinError> {
-    let _stmt = "SELECT $edge_fields FROM edges";
+fn edge_not_found() -> NapkinError {
+    NapkinError {
+        code: "EDGE_NO_ID",
+        message: "Edge with ID {edge_id} Not Found",
+        root: NapkinErrorRoot::NotFound,
+    }
+}
+
+// An edge may only join live nodes of its own project, which is then live as well
+pub fn edge_endpoint_not_found() -> NapkinError {
+    NapkinError {
+        code: "EDGE_ENDPOINT_NO_ID",
+        message: "Source or Target with ID {id} Not Found in the Project",
+        root: NapkinErrorRoot::NotFound,
+    }
+}
+
+// Returns up to `limit + 1` rows so callers can tell whether another page exists;
+// a `None` limit returns every row
+pub async fn get_edges(
+    client: &Client,
+    label: &Option<String>,
+    after: &Option<uuid::Uuid>,
+    limit: Option<i64>,
+) -> Result<Vec<Edge>, NapkinError> {
+    let _stmt = "SELECT $edge_fields FROM edges WHERE deleted_at IS NULL AND ($1::text IS NULL OR label = $1) AND ($2::uuid IS NULL OR id > $2) ORDER BY id LIMIT $3;";
+    let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
+
+    query::<Edge>(client, &_stmt, &[label, after, &limit.map(|limit| limit + 1)]).await
+}
+
+pub async fn get_project_edges(
+    client: &Client,
+    project: &uuid::Uuid,
+    label: &Option<String>,
+    after: &Option<uuid::Uuid>,
+    limit: Option<i64>,
+) -> Result<Vec<Edge>, NapkinError> {
+    let _stmt = "SELECT $edge_fields FROM edges WHERE project = $1 AND deleted_at IS NULL AND ($2::text IS NULL OR label = $2) AND ($3::uuid IS NULL OR id > $3) ORDER BY id LIMIT $4;";
     let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
-    // Uuid type needs to be casted, otherwise death
-    let _stmt = _stmt.replace("id", "id::text");
-    println!("{}", _stmt);
-    let stmt = client.prepare(&_stmt).await.unwrap();
-
-    let results = client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| Edge::from_row_ref(row).unwrap())
-        .collect::<Vec<Edge>>();
-
-    Ok(results)
+
+    query::<Edge>(client, &_stmt, &[project, label, after, &limit.map(|limit| limit + 1)]).await
 }
 
 pub async fn add_edge(client: &Client, edge_info: Edge) -> Result<Edge, NapkinError> {
-    let _stmt = "INSERT INTO edges(project, source, target) VALUES ($1, $2, $3) RETURNING $edge_fields;";
+    let _stmt = "INSERT INTO edges(project, source, target, label, directed, weight)
+        SELECT $1::uuid, $2::uuid, $3::uuid, $4::text, $5::bool, $6::float8
+        WHERE EXISTS (SELECT 1 FROM nodes WHERE id = $2 AND project = $1 AND deleted_at IS NULL)
+            AND EXISTS (SELECT 1 FROM nodes WHERE id = $3 AND project = $1 AND deleted_at IS NULL)
+        RETURNING $edge_fields;";
     let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
-    let _stmt = _stmt.replace("id", "id::text");
-    println!("{}", &_stmt);
-    let stmt = client.prepare(&_stmt).await;
-    match stmt {
-        Ok(stmt) => client
-            .query(
-                &stmt,
-                &[
-                    &edge_info.project,
-                    &edge_info.source,
-                    &edge_info.target,
-                ],
-            )
-            .await?
-            .iter()
-            .map(|row| Edge::from_row_ref(row).unwrap())
-            .collect::<Vec<Edge>>()
-            .pop()
-            .ok_or(NapkinError {
-                code: "EDGE_NO_ID",
-                message: "Edge with ID {id} Not Found",
-                root: NapkinErrorRoot::NotFound,
-            }),
-        Err(e) => {
-            println!("{}", e);
-            return Err(NapkinError {
-                code: "EDGE_NO_ID",
-                message: "Project with ID {id} Not Found",
-                root: NapkinErrorRoot::NotFound,
-            });
-        }
-    }
+
+    query_one::<Edge>(
+        client,
+        &_stmt,
+        &[&edge_info.project, &edge_info.source, &edge_info.target, &edge_info.label, &edge_info.directed, &edge_info.weight],
+        edge_endpoint_not_found(),
+    )
+    .await
 }
 
-pub async fn get_edge(client: &Client, edge_id: &String) -> Result<Edge, NapkinError> {
-    let _stmt = "SELECT $edge_fields FROM edges WHERE id = ANY ('{$id}');";
+pub async fn get_edge(client: &Client, edge_id: &uuid::Uuid) -> Result<Edge, NapkinError> {
+    let _stmt = "SELECT $edge_fields FROM edges WHERE id = $1 AND deleted_at IS NULL;";
     let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
-    let _stmt = _stmt.replace("$id", edge_id);
-    let _stmt = _stmt.replace("id", "id::text");
-    let stmt = client.prepare(&_stmt).await.unwrap();
-    println!("{}", &_stmt);
-
-    client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| Edge::from_row_ref(row).unwrap())
-        .collect::<Vec<Edge>>()
-        .pop()
-        .ok_or(NapkinError {
-            code: "EDGE_NO_ID",
-            message: "Edge with ID {edge_id} Not Found",
-            root: NapkinErrorRoot::NotFound,
-        })
+
+    query_one::<Edge>(client, &_stmt, &[edge_id], edge_not_found()).await
 }
 
+// Matches no row when the edge is missing, `If-Match` is stale or an endpoint is not live;
+// `written_edge` tells these apart
 pub async fn update_edge(
     client: &Client,
-    edge_id: &String,
+    edge_id: &uuid::Uuid,
     edge_info: Edge,
+    if_match: &Option<Vec<i64>>,
 ) -> Result<Edge, NapkinError> {
-    let _stmt = "UPDATE edges $updates WHERE id = ANY ('{$id}') RETURNING $edge_fields;";
+    let _stmt = "UPDATE edges SET project = $2, source = $3, target = $4, label = $5, directed = $6, weight = $7
+        WHERE id = $1 AND deleted_at IS NULL AND ($8::bigint[] IS NULL OR version = ANY($8))
+            AND EXISTS (SELECT 1 FROM nodes WHERE id = $3 AND project = $2 AND deleted_at IS NULL)
+            AND EXISTS (SELECT 1 FROM nodes WHERE id = $4 AND project = $2 AND deleted_at IS NULL)
+        RETURNING $edge_fields;";
     let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
-    let _stmt = _stmt.replace("id", "id::text");
-    let _stmt = _stmt.replace("$id", edge_id);
-    let _stmt = _stmt.replace("$updates", &Edge::to_update_str(&edge_info));
-    let stmt = client.prepare(&_stmt).await.unwrap();
-    println!("{}", &_stmt);
-
-    client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| Edge::from_row_ref(row).unwrap())
-        .collect::<Vec<Edge>>()
-        .pop()
-        .ok_or(NapkinError {
-            code: "EDGE_NO_ID",
-            message: "Edge with ID {edge_id} Not Found",
-            root: NapkinErrorRoot::NotFound,
-        })
+
+    query_one::<Edge>(
+        client,
+        &_stmt,
+        &[edge_id, &edge_info.project, &edge_info.source, &edge_info.target, &edge_info.label, &edge_info.directed, &edge_info.weight, if_match],
+        edge_not_found(),
+    )
+    .await
 }
 
-pub async fn delete_edge(client: &Client, edge_id: &String) -> Result<Edge, NapkinError> {
-    let _stmt = "DELETE FROM edges WHERE id = ANY ('{$id}') RETURNING $edge_fields;";
-    let _stmt = _stmt.replace("$id", edge_id);
+pub async fn delete_edge(client: &Client, edge_id: &uuid::Uuid, if_match: &Option<Vec<i64>>) -> Result<Edge, NapkinError> {
+    let _stmt = "UPDATE edges SET deleted_at = now() WHERE id = $1 AND deleted_at IS NULL AND ($2::bigint[] IS NULL OR version = ANY($2)) RETURNING $edge_fields;";
     let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
-    let _stmt = _stmt.replace("id", "id::text");
-    println!("{}", &_stmt);
-    let stmt = client.prepare(&_stmt).await.unwrap();
-
-    client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| Edge::from_row_ref(row).unwrap())
-        .collect::<Vec<Edge>>()
-        .pop()
-        .ok_or(NapkinError {
-            code: "EDGE_NO_ID",
-            message: "Edge with ID {edge_id} Not Found",
-            root: NapkinErrorRoot::NotFound,
-        })
+
+    query_one::<Edge>(client, &_stmt, &[edge_id, if_match], edge_not_found()).await
+}
+
+// Edges deleted on their own, rather than along with an endpoint or their project
+pub async fn get_trashed_edges(
+    client: &Client,
+    project: &Option<uuid::Uuid>,
+    after: &Option<uuid::Uuid>,
+    limit: Option<i64>,
+) -> Result<Vec<Edge>, NapkinError> {
+    let _stmt = "SELECT $edge_fields FROM edges
+        WHERE edges.deleted_at IS NOT NULL
+            AND NOT EXISTS (SELECT 1 FROM nodes WHERE nodes.id IN (edges.source, edges.target) AND nodes.deleted_at = edges.deleted_at)
+            AND NOT EXISTS (SELECT 1 FROM projects WHERE projects.id = edges.project AND projects.deleted_at = edges.deleted_at)
+            AND ($1::uuid IS NULL OR edges.project = $1) AND ($2::uuid IS NULL OR edges.id > $2)
+        ORDER BY edges.id LIMIT $3;";
+    let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
+
+    query::<Edge>(client, &_stmt, &[project, after, &limit.map(|limit| limit + 1)]).await
+}
+
+pub async fn get_trashed_edge(client: &Client, edge_id: &uuid::Uuid) -> Result<Edge, NapkinError> {
+    let _stmt = "SELECT $edge_fields FROM edges WHERE id = $1 AND deleted_at IS NOT NULL;";
+    let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
+
+    query_one::<Edge>(client, &_stmt, &[edge_id], edge_not_found()).await
+}
+
+pub async fn restore_edge(client: &Client, edge_id: &uuid::Uuid) -> Result<Edge, NapkinError> {
+    let _stmt = "UPDATE edges SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL RETURNING $edge_fields;";
+    let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
+
+    query_one::<Edge>(client, &_stmt, &[edge_id], edge_not_found()).await
+}
+
+pub async fn purge_edge(client: &Client, edge_id: &uuid::Uuid) -> Result<Edge, NapkinError> {
+    let _stmt = "DELETE FROM edges WHERE id = $1 AND deleted_at IS NOT NULL RETURNING $edge_fields;";
+    let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
+
+    query_one::<Edge>(client, &_stmt, &[edge_id], edge_not_found()).await
 }
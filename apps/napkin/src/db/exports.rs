@@ -0,0 +1,106 @@
+use deadpool_postgres::GenericClient;
+use tokio_pg_mapper::FromTokioPostgresRow;
+
+use crate::{
+    db::{execute, query, query_one},
+    errors::{NapkinError, NapkinErrorRoot},
+    models::{
+        artifact_metadata::ArtifactMetadata, artifacts::Artifact, edge_metadata::EdgeMetadata,
+        edges::Edge, metadata_schemas::MetadataSchema, node_metadata::NodeMetadata, nodes::Node,
+        projects::Project,
+    },
+};
+
+pub async fn get_nodes(client: &impl GenericClient, project: &uuid::Uuid) -> Result<Vec<Node>, NapkinError> {
+    let _stmt = "SELECT $node_fields FROM nodes WHERE project = $1 AND deleted_at IS NULL ORDER BY id;";
+    let _stmt = _stmt.replace("$node_fields", &Node::sql_table_fields());
+
+    query::<Node>(client, &_stmt, &[project]).await
+}
+
+pub async fn get_edges(client: &impl GenericClient, project: &uuid::Uuid) -> Result<Vec<Edge>, NapkinError> {
+    let _stmt = "SELECT $edge_fields FROM edges WHERE project = $1 AND deleted_at IS NULL ORDER BY id;";
+    let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
+
+    query::<Edge>(client, &_stmt, &[project]).await
+}
+
+pub async fn get_node_metadata(client: &impl GenericClient, project: &uuid::Uuid) -> Result<Vec<NodeMetadata>, NapkinError> {
+    let _stmt = "SELECT $node_metadata_fields FROM node_metadata
+        JOIN nodes ON nodes.id = node_metadata.owner_id
+        WHERE nodes.project = $1 AND nodes.deleted_at IS NULL
+        ORDER BY node_metadata.owner_id, node_metadata.name;";
+    let _stmt = _stmt.replace("$node_metadata_fields", &NodeMetadata::sql_table_fields());
+
+    query::<NodeMetadata>(client, &_stmt, &[project]).await
+}
+
+pub async fn get_edge_metadata(client: &impl GenericClient, project: &uuid::Uuid) -> Result<Vec<EdgeMetadata>, NapkinError> {
+    let _stmt = "SELECT $edge_metadata_fields FROM edge_metadata
+        JOIN edges ON edges.id = edge_metadata.owner_id
+        WHERE edges.project = $1 AND edges.deleted_at IS NULL
+        ORDER BY edge_metadata.owner_id, edge_metadata.name;";
+    let _stmt = _stmt.replace("$edge_metadata_fields", &EdgeMetadata::sql_table_fields());
+
+    query::<EdgeMetadata>(client, &_stmt, &[project]).await
+}
+
+pub async fn get_artifacts(client: &impl GenericClient, project: &uuid::Uuid) -> Result<Vec<Artifact>, NapkinError> {
+    let _stmt = "SELECT $artifact_fields FROM artifacts
+        JOIN nodes ON nodes.id = artifacts.node_id
+        WHERE nodes.project = $1 AND nodes.deleted_at IS NULL
+        ORDER BY artifacts.node_id;";
+    let _stmt = _stmt.replace("$artifact_fields", &Artifact::sql_table_fields());
+
+    query::<Artifact>(client, &_stmt, &[project]).await
+}
+
+pub async fn get_artifact_metadata(client: &impl GenericClient, project: &uuid::Uuid) -> Result<Vec<ArtifactMetadata>, NapkinError> {
+    let _stmt = "SELECT $artifact_metadata_fields FROM artifact_metadata
+        JOIN nodes ON nodes.id = artifact_metadata.owner_id
+        WHERE nodes.project = $1 AND nodes.deleted_at IS NULL
+        ORDER BY artifact_metadata.owner_id, artifact_metadata.name;";
+    let _stmt = _stmt.replace("$artifact_metadata_fields", &ArtifactMetadata::sql_table_fields());
+
+    query::<ArtifactMetadata>(client, &_stmt, &[project]).await
+}
+
+// Inserts the project under `project_info.id`, which import chooses itself
+pub async fn add_project(client: &impl GenericClient, project_info: &Project) -> Result<Project, NapkinError> {
+    let _stmt = "INSERT INTO projects(id, scope, name) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING RETURNING $project_fields;";
+    let _stmt = _stmt.replace("$project_fields", &Project::sql_table_fields());
+
+    query_one::<Project>(
+        client,
+        &_stmt,
+        &[&project_info.id, &project_info.scope, &project_info.name],
+        NapkinError {
+            code: "PROJECT_EXISTS",
+            message: "A project with this ID or scope and name already exists",
+            root: NapkinErrorRoot::Conflict,
+        },
+    )
+    .await
+}
+
+pub async fn add_artifacts(
+    client: &impl GenericClient,
+    node_ids: &[uuid::Uuid],
+    embeddings: &[pgvector::Vector],
+) -> Result<u64, NapkinError> {
+    let _stmt = "INSERT INTO artifacts(node_id, embedding) SELECT * FROM unnest($1::uuid[], $2::vector[]);";
+
+    execute(client, _stmt, &[&node_ids, &embeddings]).await
+}
+
+pub async fn add_metadata_schemas(
+    client: &impl GenericClient,
+    project: &uuid::Uuid,
+    schemas: &[MetadataSchema],
+) -> Result<u64, NapkinError> {
+    let _stmt = "INSERT INTO metadata_schemas(project, name, schema) SELECT $1, * FROM unnest($2::text[], $3::jsonb[]);";
+    let names: Vec<&str> = schemas.iter().map(|schema| schema.name.as_str()).collect();
+    let values: Vec<&serde_json::Value> = schemas.iter().map(|schema| &schema.schema).collect();
+
+    execute(client, _stmt, &[project, &names, &values]).await
+}
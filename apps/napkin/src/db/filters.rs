@@ -0,0 +1,96 @@
+use tokio_postgres::types::ToSql;
+
+use crate::{
+    errors::{NapkinError, NapkinErrorRoot},
+    models::filters::{FilterOp, MetadataCondition, MetadataFilter},
+};
+
+pub type SqlParams = Vec<Box<dyn ToSql + Sync + Send>>;
+
+pub fn as_sql_params(params: &SqlParams) -> Vec<&(dyn ToSql + Sync)> {
+    params
+        .iter()
+        .map(|param| param.as_ref() as &(dyn ToSql + Sync))
+        .collect()
+}
+
+fn bad_filter(message: &'static str) -> NapkinError {
+    NapkinError {
+        code: "FILTER_INVALID",
+        message,
+        root: NapkinErrorRoot::BadRequest,
+    }
+}
+
+pub fn push_param(params: &mut SqlParams, param: impl ToSql + Sync + Send + 'static) -> String {
+    params.push(Box::new(param));
+    format!("${}", params.len())
+}
+
+// Compiles `filter` into a SQL boolean expression over the `node_metadata` rows
+// owned by `owner_column`, appending every value to `params` as a bound parameter
+pub fn compile_metadata_filter(
+    filter: &MetadataFilter,
+    owner_column: &str,
+    params: &mut SqlParams,
+) -> Result<String, NapkinError> {
+    match filter {
+        MetadataFilter::All { all } if all.is_empty() => Ok("TRUE".to_string()),
+        MetadataFilter::Any { any } if any.is_empty() => Ok("FALSE".to_string()),
+        MetadataFilter::All { all } => Ok(format!(
+            "({})",
+            all.iter()
+                .map(|filter| compile_metadata_filter(filter, owner_column, params))
+                .collect::<Result<Vec<String>, NapkinError>>()?
+                .join(" AND ")
+        )),
+        MetadataFilter::Any { any } => Ok(format!(
+            "({})",
+            any.iter()
+                .map(|filter| compile_metadata_filter(filter, owner_column, params))
+                .collect::<Result<Vec<String>, NapkinError>>()?
+                .join(" OR ")
+        )),
+        MetadataFilter::Not { not } => Ok(format!(
+            "(NOT {})",
+            compile_metadata_filter(not, owner_column, params)?
+        )),
+        MetadataFilter::Condition(condition) => compile_condition(condition, owner_column, params),
+    }
+}
+
+fn compile_condition(
+    condition: &MetadataCondition,
+    owner_column: &str,
+    params: &mut SqlParams,
+) -> Result<String, NapkinError> {
+    let name = push_param(params, condition.name.clone());
+    let predicate = match (condition.op, &condition.value) {
+        (FilterOp::Exists, _) => "TRUE".to_string(),
+        (FilterOp::Eq, Some(value)) => format!("m.value = {}", push_param(params, value.clone())),
+        (FilterOp::Ne, Some(value)) => format!("m.value <> {}", push_param(params, value.clone())),
+        (FilterOp::Contains, Some(value)) => format!("m.value @> {}", push_param(params, value.clone())),
+        (FilterOp::Gt | FilterOp::Gte | FilterOp::Lt | FilterOp::Lte, Some(value)) => {
+            let number = value
+                .as_f64()
+                .ok_or_else(|| bad_filter("Range filters (gt, gte, lt, lte) require a numeric value"))?;
+            let operator = match condition.op {
+                FilterOp::Gt => ">",
+                FilterOp::Gte => ">=",
+                FilterOp::Lt => "<",
+                _ => "<=",
+            };
+            format!(
+                "(CASE WHEN jsonb_typeof(m.value) = 'number' THEN (m.value #>> '{{}}')::float8 END) {} {}",
+                operator,
+                push_param(params, number)
+            )
+        }
+        (_, None) => return Err(bad_filter("Filter operator requires a `value`")),
+    };
+
+    Ok(format!(
+        "EXISTS (SELECT 1 FROM node_metadata m WHERE m.owner_id = {} AND m.name = {} AND {})",
+        owner_column, name, predicate
+    ))
+}
@@ -0,0 +1,132 @@
+use deadpool_postgres::Client;
+use tokio_pg_mapper::FromTokioPostgresRow;
+
+use crate::{
+    db::query,
+    errors::NapkinError,
+    models::{
+        artifact_metadata::ArtifactMetadata, artifacts::Artifact, edge_metadata::EdgeMetadata,
+        edges::Edge, node_metadata::NodeMetadata, nodes::Node, projects::Project,
+    },
+};
+
+// Batched lookups behind the GraphQL data loaders: one statement per batch of keys.
+// Trashed projects, nodes and edges are never loaded. List lookups page each parent's
+// rows separately by numbering them within the parent, so `limit` applies per parent.
+
+pub async fn get_projects(client: &Client, ids: &[uuid::Uuid]) -> Result<Vec<Project>, NapkinError> {
+    let _stmt = "SELECT $project_fields FROM projects WHERE id = ANY($1) AND deleted_at IS NULL;";
+    let _stmt = _stmt.replace("$project_fields", &Project::sql_table_fields());
+
+    query::<Project>(client, &_stmt, &[&ids]).await
+}
+
+pub async fn get_nodes(client: &Client, ids: &[uuid::Uuid]) -> Result<Vec<Node>, NapkinError> {
+    let _stmt = "SELECT $node_fields FROM nodes WHERE id = ANY($1) AND deleted_at IS NULL;";
+    let _stmt = _stmt.replace("$node_fields", &Node::sql_table_fields());
+
+    query::<Node>(client, &_stmt, &[&ids]).await
+}
+
+pub async fn get_project_nodes(
+    client: &Client,
+    projects: &[uuid::Uuid],
+    label: &Option<String>,
+    after: &Option<uuid::Uuid>,
+    limit: Option<i64>,
+) -> Result<Vec<Node>, NapkinError> {
+    let _stmt = "SELECT $node_fields FROM (
+            SELECT *, row_number() OVER (PARTITION BY project ORDER BY id) AS position FROM nodes
+            WHERE project = ANY($1) AND deleted_at IS NULL AND ($2::text IS NULL OR $2 = ANY(labels)) AND ($3::uuid IS NULL OR id > $3)
+        ) AS nodes
+        WHERE $4::bigint IS NULL OR position <= $4 ORDER BY project, id;";
+    let _stmt = _stmt.replace("$node_fields", &Node::sql_table_fields());
+
+    query::<Node>(client, &_stmt, &[&projects, label, after, &limit]).await
+}
+
+pub async fn get_edges(client: &Client, ids: &[uuid::Uuid]) -> Result<Vec<Edge>, NapkinError> {
+    let _stmt = "SELECT $edge_fields FROM edges WHERE id = ANY($1) AND deleted_at IS NULL;";
+    let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
+
+    query::<Edge>(client, &_stmt, &[&ids]).await
+}
+
+pub async fn get_project_edges(
+    client: &Client,
+    projects: &[uuid::Uuid],
+    label: &Option<String>,
+    after: &Option<uuid::Uuid>,
+    limit: Option<i64>,
+) -> Result<Vec<Edge>, NapkinError> {
+    let _stmt = "SELECT $edge_fields FROM (
+            SELECT *, row_number() OVER (PARTITION BY project ORDER BY id) AS position FROM edges
+            WHERE project = ANY($1) AND deleted_at IS NULL AND ($2::text IS NULL OR label = $2) AND ($3::uuid IS NULL OR id > $3)
+        ) AS edges
+        WHERE $4::bigint IS NULL OR position <= $4 ORDER BY project, id;";
+    let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
+
+    query::<Edge>(client, &_stmt, &[&projects, label, after, &limit]).await
+}
+
+pub async fn get_outgoing_edges(
+    client: &Client,
+    sources: &[uuid::Uuid],
+    label: &Option<String>,
+    after: &Option<uuid::Uuid>,
+    limit: Option<i64>,
+) -> Result<Vec<Edge>, NapkinError> {
+    let _stmt = "SELECT $edge_fields FROM (
+            SELECT *, row_number() OVER (PARTITION BY source ORDER BY id) AS position FROM edges
+            WHERE source = ANY($1) AND deleted_at IS NULL AND ($2::text IS NULL OR label = $2) AND ($3::uuid IS NULL OR id > $3)
+        ) AS edges
+        WHERE $4::bigint IS NULL OR position <= $4 ORDER BY source, id;";
+    let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
+
+    query::<Edge>(client, &_stmt, &[&sources, label, after, &limit]).await
+}
+
+pub async fn get_incoming_edges(
+    client: &Client,
+    targets: &[uuid::Uuid],
+    label: &Option<String>,
+    after: &Option<uuid::Uuid>,
+    limit: Option<i64>,
+) -> Result<Vec<Edge>, NapkinError> {
+    let _stmt = "SELECT $edge_fields FROM (
+            SELECT *, row_number() OVER (PARTITION BY target ORDER BY id) AS position FROM edges
+            WHERE target = ANY($1) AND deleted_at IS NULL AND ($2::text IS NULL OR label = $2) AND ($3::uuid IS NULL OR id > $3)
+        ) AS edges
+        WHERE $4::bigint IS NULL OR position <= $4 ORDER BY target, id;";
+    let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
+
+    query::<Edge>(client, &_stmt, &[&targets, label, after, &limit]).await
+}
+
+pub async fn get_node_metadata(client: &Client, owner_ids: &[uuid::Uuid]) -> Result<Vec<NodeMetadata>, NapkinError> {
+    let _stmt = "SELECT $node_metadata_fields FROM node_metadata WHERE owner_id = ANY($1) ORDER BY owner_id, name;";
+    let _stmt = _stmt.replace("$node_metadata_fields", &NodeMetadata::sql_table_fields());
+
+    query::<NodeMetadata>(client, &_stmt, &[&owner_ids]).await
+}
+
+pub async fn get_edge_metadata(client: &Client, owner_ids: &[uuid::Uuid]) -> Result<Vec<EdgeMetadata>, NapkinError> {
+    let _stmt = "SELECT $edge_metadata_fields FROM edge_metadata WHERE owner_id = ANY($1) ORDER BY owner_id, name;";
+    let _stmt = _stmt.replace("$edge_metadata_fields", &EdgeMetadata::sql_table_fields());
+
+    query::<EdgeMetadata>(client, &_stmt, &[&owner_ids]).await
+}
+
+pub async fn get_artifacts(client: &Client, node_ids: &[uuid::Uuid]) -> Result<Vec<Artifact>, NapkinError> {
+    let _stmt = "SELECT $artifact_fields FROM artifacts WHERE node_id = ANY($1);";
+    let _stmt = _stmt.replace("$artifact_fields", &Artifact::sql_table_fields());
+
+    query::<Artifact>(client, &_stmt, &[&node_ids]).await
+}
+
+pub async fn get_artifact_metadata(client: &Client, owner_ids: &[uuid::Uuid]) -> Result<Vec<ArtifactMetadata>, NapkinError> {
+    let _stmt = "SELECT $artifact_metadata_fields FROM artifact_metadata WHERE owner_id = ANY($1) ORDER BY owner_id, name;";
+    let _stmt = _stmt.replace("$artifact_metadata_fields", &ArtifactMetadata::sql_table_fields());
+
+    query::<ArtifactMetadata>(client, &_stmt, &[&owner_ids]).await
+}
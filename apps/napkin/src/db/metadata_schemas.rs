@@ -0,0 +1,114 @@
+use deadpool_postgres::{Client, GenericClient};
+use tokio_pg_mapper::FromTokioPostgresRow;
+
+use crate::{
+    db::{query, query_one},
+    errors::{NapkinError, NapkinErrorRoot},
+    models::metadata_schemas::MetadataSchema,
+};
+
+fn metadata_schema_not_found() -> NapkinError {
+    NapkinError {
+        code: "METADATA_SCHEMA_NO_ID",
+        message: "Metadata Schema with ID ({project}, {name}) Not Found",
+        root: NapkinErrorRoot::NotFound,
+    }
+}
+
+// Returns up to `limit + 1` rows so callers can tell whether another page exists;
+// a `None` limit returns every row
+pub async fn get_metadata_schemas(
+    client: &Client,
+    project: &uuid::Uuid,
+    after: &Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<MetadataSchema>, NapkinError> {
+    let _stmt = "SELECT $metadata_schema_fields FROM metadata_schemas WHERE project = $1 AND ($2::text IS NULL OR name > $2) ORDER BY name LIMIT $3;";
+    let _stmt = _stmt.replace("$metadata_schema_fields", &MetadataSchema::sql_table_fields());
+
+    query::<MetadataSchema>(client, &_stmt, &[project, after, &limit.map(|limit| limit + 1)]).await
+}
+
+// Every schema of `project`, for validating many values at once
+pub async fn get_all_metadata_schemas(client: &impl GenericClient, project: &uuid::Uuid) -> Result<Vec<MetadataSchema>, NapkinError> {
+    let _stmt = "SELECT $metadata_schema_fields FROM metadata_schemas WHERE project = $1;";
+    let _stmt = _stmt.replace("$metadata_schema_fields", &MetadataSchema::sql_table_fields());
+
+    query::<MetadataSchema>(client, &_stmt, &[project]).await
+}
+
+// Like `get_all_metadata_schemas`, but no schema can change or go until the transaction ends
+pub async fn lock_all_metadata_schemas(client: &impl GenericClient, project: &uuid::Uuid) -> Result<Vec<MetadataSchema>, NapkinError> {
+    let _stmt = "SELECT $metadata_schema_fields FROM metadata_schemas WHERE project = $1 FOR SHARE;";
+    let _stmt = _stmt.replace("$metadata_schema_fields", &MetadataSchema::sql_table_fields());
+
+    query::<MetadataSchema>(client, &_stmt, &[project]).await
+}
+
+pub async fn add_metadata_schema(client: &Client, metadata_schema_info: MetadataSchema) -> Result<MetadataSchema, NapkinError> {
+    let _stmt = "INSERT INTO metadata_schemas(project, name, schema) VALUES ($1, $2, $3) ON CONFLICT (project, name) DO NOTHING RETURNING $metadata_schema_fields;";
+    let _stmt = _stmt.replace("$metadata_schema_fields", &MetadataSchema::sql_table_fields());
+
+    query_one::<MetadataSchema>(
+        client,
+        &_stmt,
+        &[
+            &metadata_schema_info.project,
+            &metadata_schema_info.name,
+            &metadata_schema_info.schema,
+        ],
+        NapkinError {
+            code: "METADATA_SCHEMA_EXISTS",
+            message: "Project already has a Schema for this name, use PUT to replace it",
+            root: NapkinErrorRoot::Conflict,
+        },
+    )
+    .await
+}
+
+pub async fn get_metadata_schema(client: &Client, project: &uuid::Uuid, name: &str) -> Result<MetadataSchema, NapkinError> {
+    let _stmt = "SELECT $metadata_schema_fields FROM metadata_schemas WHERE (project = $1 AND name = $2);";
+    let _stmt = _stmt.replace("$metadata_schema_fields", &MetadataSchema::sql_table_fields());
+
+    query_one::<MetadataSchema>(client, &_stmt, &[project, &name], metadata_schema_not_found()).await
+}
+
+pub async fn update_metadata_schema(
+    client: &Client,
+    project: &uuid::Uuid,
+    name: &str,
+    schema: &serde_json::Value,
+    if_match: &Option<Vec<i64>>,
+) -> Result<MetadataSchema, NapkinError> {
+    let _stmt = "UPDATE metadata_schemas SET schema = $3 WHERE (project = $1 AND name = $2) AND ($4::bigint[] IS NULL OR version = ANY($4)) RETURNING $metadata_schema_fields;";
+    let _stmt = _stmt.replace("$metadata_schema_fields", &MetadataSchema::sql_table_fields());
+
+    query_one::<MetadataSchema>(client, &_stmt, &[project, &name, schema, if_match], metadata_schema_not_found()).await
+}
+
+pub async fn delete_metadata_schema(client: &Client, project: &uuid::Uuid, name: &str, if_match: &Option<Vec<i64>>) -> Result<MetadataSchema, NapkinError> {
+    let _stmt = "DELETE FROM metadata_schemas WHERE (project = $1 AND name = $2) AND ($3::bigint[] IS NULL OR version = ANY($3)) RETURNING $metadata_schema_fields;";
+    let _stmt = _stmt.replace("$metadata_schema_fields", &MetadataSchema::sql_table_fields());
+
+    query_one::<MetadataSchema>(client, &_stmt, &[project, &name, if_match], metadata_schema_not_found()).await
+}
+
+// Schema for metadata `name` in the project of node `owner_id`, if one is registered
+pub async fn get_node_metadata_schema(client: &Client, owner_id: &uuid::Uuid, name: &str) -> Result<Option<MetadataSchema>, NapkinError> {
+    let _stmt = "SELECT $metadata_schema_fields FROM metadata_schemas
+        JOIN nodes ON nodes.project = metadata_schemas.project
+        WHERE (nodes.id = $1 AND metadata_schemas.name = $2);";
+    let _stmt = _stmt.replace("$metadata_schema_fields", &MetadataSchema::sql_table_fields());
+
+    Ok(query::<MetadataSchema>(client, &_stmt, &[owner_id, &name]).await?.pop())
+}
+
+// Schema for metadata `name` in the project of edge `owner_id`, if one is registered
+pub async fn get_edge_metadata_schema(client: &Client, owner_id: &uuid::Uuid, name: &str) -> Result<Option<MetadataSchema>, NapkinError> {
+    let _stmt = "SELECT $metadata_schema_fields FROM metadata_schemas
+        JOIN edges ON edges.project = metadata_schemas.project
+        WHERE (edges.id = $1 AND metadata_schemas.name = $2);";
+    let _stmt = _stmt.replace("$metadata_schema_fields", &MetadataSchema::sql_table_fields());
+
+    Ok(query::<MetadataSchema>(client, &_stmt, &[owner_id, &name]).await?.pop())
+}
@@ -1,5 +1,79 @@
+use deadpool_postgres::GenericClient;
+use log::debug;
+use tokio_pg_mapper::FromTokioPostgresRow;
+use tokio_postgres::types::ToSql;
+
+use crate::errors::NapkinError;
+
 pub mod projects;
 pub mod nodes;
 pub mod edges;
 pub mod node_metadata;
-pub mod edge_metadata;
\ No newline at end of file
+pub mod edge_metadata;
+pub mod artifacts;
+pub mod artifact_metadata;
+pub mod filters;
+pub mod traversals;
+pub mod metadata_schemas;
+pub mod bulk;
+pub mod exports;
+pub mod queries;
+pub mod graphql;
+
+// Runs a fixed statement, preparing it once per pooled connection. Every value
+// must be passed through `params`; never splice user input into `stmt`.
+// `client` may be a pooled `Client` or a `Transaction` on one.
+pub async fn query<T: FromTokioPostgresRow>(
+    client: &impl GenericClient,
+    stmt: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<Vec<T>, NapkinError> {
+    debug!("{}", stmt);
+    let stmt = client.prepare_cached(stmt).await?;
+
+    client
+        .query(&stmt, params)
+        .await?
+        .iter()
+        .map(|row| T::from_row_ref(row).map_err(NapkinError::from))
+        .collect()
+}
+
+// Like `query`, for statements whose text is built at runtime (e.g. compiled
+// filters), which would otherwise grow the statement cache without bound
+pub async fn query_dynamic<T: FromTokioPostgresRow>(
+    client: &impl GenericClient,
+    stmt: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<Vec<T>, NapkinError> {
+    debug!("{}", stmt);
+    let stmt = client.prepare(stmt).await?;
+
+    client
+        .query(&stmt, params)
+        .await?
+        .iter()
+        .map(|row| T::from_row_ref(row).map_err(NapkinError::from))
+        .collect()
+}
+
+pub async fn query_one<T: FromTokioPostgresRow>(
+    client: &impl GenericClient,
+    stmt: &str,
+    params: &[&(dyn ToSql + Sync)],
+    not_found: NapkinError,
+) -> Result<T, NapkinError> {
+    query::<T>(client, stmt, params).await?.pop().ok_or(not_found)
+}
+
+// Like `query`, for statements that return no rows; yields the affected row count
+pub async fn execute(
+    client: &impl GenericClient,
+    stmt: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<u64, NapkinError> {
+    debug!("{}", stmt);
+    let stmt = client.prepare_cached(stmt).await?;
+
+    Ok(client.execute(&stmt, params).await?)
+}
@@ -2,180 +2,141 @@ use deadpool_postgres::Client;
 use tokio_pg_mapper::FromTokioPostgresRow;
 
 use crate::{
+    db::{query, query_one},
     errors::{NapkinError, NapkinErrorRoot},
     models::node_metadata::NodeMetadata,
 };
 
-pub async fn get_node_metadata(client: &Client) -> Result<Vec<NodeMetadata>, NapkinError> {
-    let _stmt = "SELECT $node_metadata_fields FROM node_metadata";
+fn node_metadata_not_found() -> NapkinError {
+    NapkinError {
+        code: "NODE_METADATA_NO_ID",
+        message: "Node Metadata with ID ({owner_id}, {name}) Not Found",
+        root: NapkinErrorRoot::NotFound,
+    }
+}
+
+// The owner is missing or in the trash
+fn owner_not_found() -> NapkinError {
+    NapkinError {
+        code: "NODE_NO_ID",
+        message: "Node with ID {owner_id} Not Found",
+        root: NapkinErrorRoot::NotFound,
+    }
+}
+
+// Returns up to `limit + 1` rows so callers can tell whether another page exists;
+// a `None` limit returns every row
+pub async fn get_node_metadata(
+    client: &Client,
+    after: &Option<(uuid::Uuid, String)>,
+    limit: Option<i64>,
+) -> Result<Vec<NodeMetadata>, NapkinError> {
+    let _stmt = "SELECT $node_metadata_fields FROM node_metadata
+        JOIN nodes ON nodes.id = node_metadata.owner_id
+        WHERE nodes.deleted_at IS NULL AND ($1::uuid IS NULL OR (node_metadata.owner_id, node_metadata.name) > ($1, $2))
+        ORDER BY node_metadata.owner_id, node_metadata.name LIMIT $3;";
+    let _stmt = _stmt.replace("$node_metadata_fields", &NodeMetadata::sql_table_fields());
+    let (after_owner_id, after_name) = match after {
+        Some((owner_id, name)) => (Some(*owner_id), Some(name.as_str())),
+        None => (None, None),
+    };
+
+    query::<NodeMetadata>(client, &_stmt, &[&after_owner_id, &after_name, &limit.map(|limit| limit + 1)]).await
+}
+
+pub async fn get_project_node_metadata(
+    client: &Client,
+    project: &uuid::Uuid,
+    after: &Option<(uuid::Uuid, String)>,
+    limit: Option<i64>,
+) -> Result<Vec<NodeMetadata>, NapkinError> {
+    let _stmt = "SELECT $node_metadata_fields FROM node_metadata
+        JOIN nodes ON nodes.id = node_metadata.owner_id
+        WHERE nodes.project = $1 AND nodes.deleted_at IS NULL AND ($2::uuid IS NULL OR (node_metadata.owner_id, node_metadata.name) > ($2, $3))
+        ORDER BY node_metadata.owner_id, node_metadata.name LIMIT $4;";
+    let _stmt = _stmt.replace("$node_metadata_fields", &NodeMetadata::sql_table_fields());
+    let (after_owner_id, after_name) = match after {
+        Some((owner_id, name)) => (Some(*owner_id), Some(name.as_str())),
+        None => (None, None),
+    };
+
+    query::<NodeMetadata>(client, &_stmt, &[project, &after_owner_id, &after_name, &limit.map(|limit| limit + 1)]).await
+}
+
+// Values stored under `name` for any of `owner_ids`
+pub async fn get_node_metadata_named(client: &Client, owner_ids: &[uuid::Uuid], name: &str) -> Result<Vec<NodeMetadata>, NapkinError> {
+    let _stmt = "SELECT $node_metadata_fields FROM node_metadata JOIN nodes ON nodes.id = node_metadata.owner_id WHERE node_metadata.owner_id = ANY($1) AND node_metadata.name = $2 AND nodes.deleted_at IS NULL;";
     let _stmt = _stmt.replace("$node_metadata_fields", &NodeMetadata::sql_table_fields());
-    // Uuid type needs to be casted, otherwise death
-    // let _stmt = _stmt.replace("id", "id::text");
-    println!("{}", _stmt);
-    let stmt = client.prepare(&_stmt).await.unwrap();
-
-    let results = client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| NodeMetadata::from_row_ref(row).unwrap())
-        .collect::<Vec<NodeMetadata>>();
-
-    Ok(results)
+
+    query::<NodeMetadata>(client, &_stmt, &[&owner_ids, &name]).await
 }
 
 pub async fn add_node_metadata(client: &Client, node_metadata_info: NodeMetadata) -> Result<NodeMetadata, NapkinError> {
-    let _stmt = "INSERT INTO node_metadata(owner_id, name, value) VALUES ($1, $2, $3) RETURNING $node_metadata_fields;";
+    let _stmt = "INSERT INTO node_metadata(owner_id, name, value) SELECT $1::uuid, $2::text, $3::jsonb WHERE EXISTS (SELECT 1 FROM nodes WHERE id = $1 AND deleted_at IS NULL) RETURNING $node_metadata_fields;";
     let _stmt = _stmt.replace("$node_metadata_fields", &NodeMetadata::sql_table_fields());
-    // let _stmt = _stmt.replace("id", "id::text");
-    println!("{}", &_stmt);
-    let stmt = client.prepare(&_stmt).await;
-    match stmt {
-        Ok(stmt) => client
-            .query(
-                &stmt,
-                &[
-                    &node_metadata_info.owner_id,
-                    &node_metadata_info.name,
-                    &node_metadata_info.value,
-                ],
-            )
-            .await?
-            .iter()
-            .map(|row| NodeMetadata::from_row_ref(row).unwrap())
-            .collect::<Vec<NodeMetadata>>()
-            .pop()
-            .ok_or(NapkinError {
-                code: "NODE_METADATA_NO_ID",
-                message: "Node Metadata with ID {id} Not Found",
-                root: NapkinErrorRoot::NotFound,
-            }),
-        Err(e) => {
-            println!("{}", e);
-            return Err(NapkinError {
-                code: "NODE_METADATA_NO_ID",
-                message: "Project with ID {id} Not Found",
-                root: NapkinErrorRoot::NotFound,
-            });
-        }
-    }
+
+    query_one::<NodeMetadata>(
+        client,
+        &_stmt,
+        &[
+            &node_metadata_info.owner_id,
+            &node_metadata_info.name,
+            &node_metadata_info.value,
+        ],
+        owner_not_found(),
+    )
+    .await
 }
 
-pub async fn get_node_metadata_singleton(client: &Client, owner_id: &String) -> Result<Vec<NodeMetadata>, NapkinError> {
-    let _stmt = "SELECT $node_metadata_fields FROM node_metadata WHERE (owner_id = '$owner_id');";
+pub async fn get_node_metadata_singleton(client: &Client, owner_id: &uuid::Uuid) -> Result<Vec<NodeMetadata>, NapkinError> {
+    let _stmt = "SELECT $node_metadata_fields FROM node_metadata JOIN nodes ON nodes.id = node_metadata.owner_id WHERE node_metadata.owner_id = $1 AND nodes.deleted_at IS NULL;";
     let _stmt = _stmt.replace("$node_metadata_fields", &NodeMetadata::sql_table_fields());
-    let _stmt = _stmt.replace("$owner_id", owner_id);
-    println!("{}", &_stmt);
-    let _stmt = client.prepare(&_stmt).await;
-    if _stmt.is_err() {
-        println!("{}", _stmt.err().unwrap());
-        return Err(NapkinError {
-            code: "NODE_METADATA_NO_ID",
-            message: "Node Metadata with ID ({owner_id}, {name}) Not Found",
-            root: NapkinErrorRoot::NotFound,
-        });
+
+    let results = query::<NodeMetadata>(client, &_stmt, &[owner_id]).await?;
+
+    if results.is_empty() {
+        Err(node_metadata_not_found())
     } else {
-        let stmt = _stmt.unwrap();
-
-        let results = client
-            .query(&stmt, &[])
-            .await?
-            .iter()
-            .map(|row| NodeMetadata::from_row_ref(row).unwrap())
-            .collect::<Vec<NodeMetadata>>();
-    
-        if results.is_empty() {
-            Err(NapkinError {
-                code: "NODE_METADATA_NO_ID",
-                message: "Node Metadata with ID ({owner_id}, {name}) Not Found",
-                root: NapkinErrorRoot::NotFound,
-            })
-        } else {
-            Ok(results)
-        }
+        Ok(results)
     }
 }
 
-pub async fn get_node_metadata_singleton_key(client: &Client, owner_id: &String, name: &String) -> Result<NodeMetadata, NapkinError> {
-    let _stmt = "SELECT $node_metadata_fields FROM node_metadata WHERE (owner_id = '$owner_id' AND name = '$name');";
+pub async fn get_node_metadata_singleton_key(client: &Client, owner_id: &uuid::Uuid, name: &str) -> Result<NodeMetadata, NapkinError> {
+    let _stmt = "SELECT $node_metadata_fields FROM node_metadata JOIN nodes ON nodes.id = node_metadata.owner_id WHERE (node_metadata.owner_id = $1 AND node_metadata.name = $2) AND nodes.deleted_at IS NULL;";
     let _stmt = _stmt.replace("$node_metadata_fields", &NodeMetadata::sql_table_fields());
-    let _stmt = _stmt.replace("$owner_id", owner_id);
-    let _stmt = _stmt.replace("$name", name);
-    println!("{}", &_stmt);
-    let _stmt = client.prepare(&_stmt).await;
-    if _stmt.is_err() {
-        println!("{}", _stmt.err().unwrap());
-        return Err(NapkinError {
-            code: "NODE_METADATA_NO_ID",
-            message: "Node Metadata with ID ({owner_id}, {name}) Not Found",
-            root: NapkinErrorRoot::NotFound,
-        });
-    } else {
-        let stmt = _stmt.unwrap();
-
-        client
-            .query(&stmt, &[])
-            .await?
-            .iter()
-            .map(|row| NodeMetadata::from_row_ref(row).unwrap())
-            .collect::<Vec<NodeMetadata>>()
-            .pop()
-            .ok_or(NapkinError {
-                code: "NODE_METADATA_NO_ID",
-                message: "Node Metadata with ID ({owner_id}, {name}) Not Found",
-                root: NapkinErrorRoot::NotFound,
-            })
-    }
+
+    query_one::<NodeMetadata>(client, &_stmt, &[owner_id, &name], node_metadata_not_found()).await
 }
 
 pub async fn update_node_metadata(
     client: &Client,
-    owner_id: &String,
-    name: &String,
+    owner_id: &uuid::Uuid,
+    name: &str,
     node_metadata_info: NodeMetadata,
+    if_match: &Option<Vec<i64>>,
 ) -> Result<NodeMetadata, NapkinError> {
-    let _stmt = "UPDATE node_metadata $updates WHERE id = ANY ('{$owner_id}', '{$name}') RETURNING $node_metadata_fields;";
+    let _stmt = "UPDATE node_metadata SET owner_id = $3, name = $4, value = $5 WHERE (owner_id = $1 AND name = $2) AND EXISTS (SELECT 1 FROM nodes WHERE id = $1 AND deleted_at IS NULL) AND EXISTS (SELECT 1 FROM nodes WHERE id = $3 AND deleted_at IS NULL) AND ($6::bigint[] IS NULL OR version = ANY($6)) RETURNING $node_metadata_fields;";
     let _stmt = _stmt.replace("$node_metadata_fields", &NodeMetadata::sql_table_fields());
-    let _stmt = _stmt.replace("owner_id", "owner_id::text");
-    let _stmt = _stmt.replace("$owner_id", owner_id);
-    let _stmt = _stmt.replace("$name", name);
-    let _stmt = _stmt.replace("$updates", &NodeMetadata::to_update_str(&node_metadata_info));
-    let stmt = client.prepare(&_stmt).await.unwrap();
-    println!("{}", &_stmt);
-
-    client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| NodeMetadata::from_row_ref(row).unwrap())
-        .collect::<Vec<NodeMetadata>>()
-        .pop()
-        .ok_or(NapkinError {
-            code: "NODE_METADATA_NO_ID",
-            message: "Node Metadata with ID ({owner_id}, {name}) Not Found",
-            root: NapkinErrorRoot::NotFound,
-        })
+
+    query_one::<NodeMetadata>(
+        client,
+        &_stmt,
+        &[
+            owner_id,
+            &name,
+            &node_metadata_info.owner_id,
+            &node_metadata_info.name,
+            &node_metadata_info.value,
+            if_match,
+        ],
+        node_metadata_not_found(),
+    )
+    .await
 }
 
-pub async fn delete_node(client: &Client, owner_id: &String, name: &String) -> Result<NodeMetadata, NapkinError> {
-    let _stmt = "DELETE FROM node_metadata WHERE id = ANY ('{$owner_id}', '{$name}') RETURNING $node_metadata_fields;";
-    let _stmt = _stmt.replace("$owner_id", owner_id);
+pub async fn delete_node_metadata(client: &Client, owner_id: &uuid::Uuid, name: &str, if_match: &Option<Vec<i64>>) -> Result<NodeMetadata, NapkinError> {
+    let _stmt = "DELETE FROM node_metadata WHERE (owner_id = $1 AND name = $2) AND EXISTS (SELECT 1 FROM nodes WHERE id = $1 AND deleted_at IS NULL) AND ($3::bigint[] IS NULL OR version = ANY($3)) RETURNING $node_metadata_fields;";
     let _stmt = _stmt.replace("$node_metadata_fields", &NodeMetadata::sql_table_fields());
-    let _stmt = _stmt.replace("owner_id", "owner_id::text");
-    let _stmt = _stmt.replace("$name", name);
-    println!("{}", &_stmt);
-    let stmt = client.prepare(&_stmt).await.unwrap();
-
-    client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| NodeMetadata::from_row_ref(row).unwrap())
-        .collect::<Vec<NodeMetadata>>()
-        .pop()
-        .ok_or(NapkinError {
-            code: "NODE_METADATA_NO_ID",
-            message: "Node Metadata with ID {node_metadata_id} Not Found",
-            root: NapkinErrorRoot::NotFound,
-        })
+
+    query_one::<NodeMetadata>(client, &_stmt, &[owner_id, &name, if_match], node_metadata_not_found()).await
 }
@@ -2,130 +2,148 @@ use deadpool_postgres::Client;
 use tokio_pg_mapper::FromTokioPostgresRow;
 
 use crate::{
+    db::{query, query_one},
     errors::{NapkinError, NapkinErrorRoot},
     models::nodes::Node,
 };
 
-pub async fn get_nodes(client: &Client) -> Result<Vec<Node>, NapkinError> {
-    let _stmt = "SELECT $node_fields FROM nodes";
+fn node_not_found() -> NapkinError {
+    NapkinError {
+        code: "NODE_NO_ID",
+        message: "Node with ID {node_id} Not Found",
+        root: NapkinErrorRoot::NotFound,
+    }
+}
+
+// Nodes are only added to projects that exist and are not in the trash
+fn project_not_found() -> NapkinError {
+    NapkinError {
+        code: "PROJECT_NO_ID",
+        message: "Project with ID {project_id} Not Found",
+        root: NapkinErrorRoot::NotFound,
+    }
+}
+
+// Returns up to `limit + 1` rows so callers can tell whether another page exists;
+// a `None` limit returns every row
+pub async fn get_nodes(
+    client: &Client,
+    label: &Option<String>,
+    after: &Option<uuid::Uuid>,
+    limit: Option<i64>,
+) -> Result<Vec<Node>, NapkinError> {
+    let _stmt = "SELECT $node_fields FROM nodes WHERE deleted_at IS NULL AND ($1::text IS NULL OR labels @> ARRAY[$1::text]) AND ($2::uuid IS NULL OR id > $2) ORDER BY id LIMIT $3;";
+    let _stmt = _stmt.replace("$node_fields", &Node::sql_table_fields());
+
+    query::<Node>(client, &_stmt, &[label, after, &limit.map(|limit| limit + 1)]).await
+}
+
+pub async fn get_project_nodes(
+    client: &Client,
+    project: &uuid::Uuid,
+    label: &Option<String>,
+    after: &Option<uuid::Uuid>,
+    limit: Option<i64>,
+) -> Result<Vec<Node>, NapkinError> {
+    let _stmt = "SELECT $node_fields FROM nodes WHERE project = $1 AND deleted_at IS NULL AND ($2::text IS NULL OR labels @> ARRAY[$2::text]) AND ($3::uuid IS NULL OR id > $3) ORDER BY id LIMIT $4;";
     let _stmt = _stmt.replace("$node_fields", &Node::sql_table_fields());
-    // Uuid type needs to be casted, otherwise death
-    let _stmt = _stmt.replace("id", "id::text");
-    println!("{}", _stmt);
-    let stmt = client.prepare(&_stmt).await.unwrap();
-
-    let results = client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| Node::from_row_ref(row).unwrap())
-        .collect::<Vec<Node>>();
-
-    Ok(results)
+
+    query::<Node>(client, &_stmt, &[project, label, after, &limit.map(|limit| limit + 1)]).await
 }
 
 pub async fn add_node(client: &Client, node_info: Node) -> Result<Node, NapkinError> {
-    let _stmt = "INSERT INTO nodes(project) VALUES ($1) RETURNING $node_fields;";
+    let _stmt = "INSERT INTO nodes(project, labels)
+        SELECT $1::uuid, $2::text[] WHERE EXISTS (SELECT 1 FROM projects WHERE id = $1 AND deleted_at IS NULL)
+        RETURNING $node_fields;";
     let _stmt = _stmt.replace("$node_fields", &Node::sql_table_fields());
-    let _stmt = _stmt.replace("id", "id::text");
-    println!("{}", &_stmt);
-    let stmt = client.prepare(&_stmt).await;
-    match stmt {
-        Ok(stmt) => client
-            .query(
-                &stmt,
-                &[
-                    &node_info.project,
-                ],
-            )
-            .await?
-            .iter()
-            .map(|row| Node::from_row_ref(row).unwrap())
-            .collect::<Vec<Node>>()
-            .pop()
-            .ok_or(NapkinError {
-                code: "NODE_NO_ID",
-                message: "Node with ID {id} Not Found",
-                root: NapkinErrorRoot::NotFound,
-            }),
-        Err(e) => {
-            println!("{}", e);
-            return Err(NapkinError {
-                code: "NODE_NO_ID",
-                message: "Project with ID {id} Not Found",
-                root: NapkinErrorRoot::NotFound,
-            });
-        }
-    }
+
+    query_one::<Node>(client, &_stmt, &[&node_info.project, &node_info.labels], project_not_found()).await
 }
 
-pub async fn get_node(client: &Client, node_id: &String) -> Result<Node, NapkinError> {
-    let _stmt = "SELECT $node_fields FROM nodes WHERE id = ANY ('{$id}');";
+pub async fn get_node(client: &Client, node_id: &uuid::Uuid) -> Result<Node, NapkinError> {
+    let _stmt = "SELECT $node_fields FROM nodes WHERE id = $1 AND deleted_at IS NULL;";
     let _stmt = _stmt.replace("$node_fields", &Node::sql_table_fields());
-    let _stmt = _stmt.replace("$id", node_id);
-    let _stmt = _stmt.replace("id", "id::text");
-    let stmt = client.prepare(&_stmt).await.unwrap();
-    println!("{}", &_stmt);
-
-    client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| Node::from_row_ref(row).unwrap())
-        .collect::<Vec<Node>>()
-        .pop()
-        .ok_or(NapkinError {
-            code: "NODE_NO_ID",
-            message: "Node with ID {node_id} Not Found",
-            root: NapkinErrorRoot::NotFound,
-        })
+
+    query_one::<Node>(client, &_stmt, &[node_id], node_not_found()).await
 }
 
 pub async fn update_node(
     client: &Client,
-    node_id: &String,
+    node_id: &uuid::Uuid,
     node_info: Node,
+    if_match: &Option<Vec<i64>>,
 ) -> Result<Node, NapkinError> {
-    let _stmt = "UPDATE nodes $updates WHERE id = ANY ('{$id}') RETURNING $node_fields;";
+    let _stmt = "UPDATE nodes SET project = $2, labels = $3 WHERE id = $1 AND deleted_at IS NULL AND ($4::bigint[] IS NULL OR version = ANY($4)) RETURNING $node_fields;";
     let _stmt = _stmt.replace("$node_fields", &Node::sql_table_fields());
-    let _stmt = _stmt.replace("id", "id::text");
-    let _stmt = _stmt.replace("$id", node_id);
-    let _stmt = _stmt.replace("$updates", &Node::to_update_str(&node_info));
-    let stmt = client.prepare(&_stmt).await.unwrap();
-    println!("{}", &_stmt);
-
-    client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| Node::from_row_ref(row).unwrap())
-        .collect::<Vec<Node>>()
-        .pop()
-        .ok_or(NapkinError {
-            code: "NODE_NO_ID",
-            message: "Node with ID {node_id} Not Found",
-            root: NapkinErrorRoot::NotFound,
-        })
+
+    query_one::<Node>(client, &_stmt, &[node_id, &node_info.project, &node_info.labels, if_match], node_not_found()).await
 }
 
-pub async fn delete_node(client: &Client, node_id: &String) -> Result<Node, NapkinError> {
-    let _stmt = "DELETE FROM nodes WHERE id = ANY ('{$id}') RETURNING $node_fields;";
-    let _stmt = _stmt.replace("$id", node_id);
+// Moves the node and its live edges to the trash under one `deleted_at`, which
+// `restore_node` later matches to bring the edges back
+pub async fn delete_node(client: &Client, node_id: &uuid::Uuid, if_match: &Option<Vec<i64>>) -> Result<Node, NapkinError> {
+    let _stmt = "WITH trashed_edges AS (
+            UPDATE edges SET deleted_at = now()
+            WHERE (source = $1 OR target = $1) AND deleted_at IS NULL
+                AND EXISTS (SELECT 1 FROM nodes WHERE id = $1 AND deleted_at IS NULL AND ($2::bigint[] IS NULL OR version = ANY($2)))
+        )
+        UPDATE nodes SET deleted_at = now()
+        WHERE id = $1 AND deleted_at IS NULL AND ($2::bigint[] IS NULL OR version = ANY($2))
+        RETURNING $node_fields;";
     let _stmt = _stmt.replace("$node_fields", &Node::sql_table_fields());
-    let _stmt = _stmt.replace("id", "id::text");
-    println!("{}", &_stmt);
-    let stmt = client.prepare(&_stmt).await.unwrap();
-
-    client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| Node::from_row_ref(row).unwrap())
-        .collect::<Vec<Node>>()
-        .pop()
-        .ok_or(NapkinError {
-            code: "NODE_NO_ID",
-            message: "Node with ID {node_id} Not Found",
-            root: NapkinErrorRoot::NotFound,
-        })
+
+    query_one::<Node>(client, &_stmt, &[node_id, if_match], node_not_found()).await
+}
+
+// Nodes deleted on their own; those trashed along with their project are listed under it
+pub async fn get_trashed_nodes(
+    client: &Client,
+    project: &Option<uuid::Uuid>,
+    after: &Option<uuid::Uuid>,
+    limit: Option<i64>,
+) -> Result<Vec<Node>, NapkinError> {
+    let _stmt = "SELECT $node_fields FROM nodes
+        WHERE nodes.deleted_at IS NOT NULL
+            AND NOT EXISTS (SELECT 1 FROM projects WHERE projects.id = nodes.project AND projects.deleted_at = nodes.deleted_at)
+            AND ($1::uuid IS NULL OR nodes.project = $1) AND ($2::uuid IS NULL OR nodes.id > $2)
+        ORDER BY nodes.id LIMIT $3;";
+    let _stmt = _stmt.replace("$node_fields", &Node::sql_table_fields());
+
+    query::<Node>(client, &_stmt, &[project, after, &limit.map(|limit| limit + 1)]).await
+}
+
+pub async fn get_trashed_node(client: &Client, node_id: &uuid::Uuid) -> Result<Node, NapkinError> {
+    let _stmt = "SELECT $node_fields FROM nodes WHERE id = $1 AND deleted_at IS NOT NULL;";
+    let _stmt = _stmt.replace("$node_fields", &Node::sql_table_fields());
+
+    query_one::<Node>(client, &_stmt, &[node_id], node_not_found()).await
+}
+
+// Restores the node and the edges trashed with it, unless their other end is still in the trash
+pub async fn restore_node(client: &Client, node_id: &uuid::Uuid) -> Result<Node, NapkinError> {
+    let _stmt = "WITH restored_edges AS (
+            UPDATE edges SET deleted_at = NULL
+            FROM nodes AS node
+            WHERE node.id = $1 AND edges.deleted_at = node.deleted_at AND (edges.source = $1 OR edges.target = $1)
+                AND NOT EXISTS (
+                    SELECT 1 FROM nodes AS other
+                    WHERE other.id IN (edges.source, edges.target) AND other.id <> $1 AND other.deleted_at IS NOT NULL
+                )
+        )
+        UPDATE nodes SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL RETURNING $node_fields;";
+    let _stmt = _stmt.replace("$node_fields", &Node::sql_table_fields());
+
+    query_one::<Node>(client, &_stmt, &[node_id], node_not_found()).await
+}
+
+// Permanently deletes a trashed node with its edges; metadata and artifacts cascade
+pub async fn purge_node(client: &Client, node_id: &uuid::Uuid) -> Result<Node, NapkinError> {
+    let _stmt = "WITH purged_edges AS (
+            DELETE FROM edges
+            WHERE (source = $1 OR target = $1) AND EXISTS (SELECT 1 FROM nodes WHERE id = $1 AND deleted_at IS NOT NULL)
+        )
+        DELETE FROM nodes WHERE id = $1 AND deleted_at IS NOT NULL RETURNING $node_fields;";
+    let _stmt = _stmt.replace("$node_fields", &Node::sql_table_fields());
+
+    query_one::<Node>(client, &_stmt, &[node_id], node_not_found()).await
 }
@@ -1,120 +1,173 @@
-use deadpool_postgres::Client;
+use deadpool_postgres::{Client, GenericClient};
 use tokio_pg_mapper::FromTokioPostgresRow;
+use tokio_postgres::error::SqlState;
 
 use crate::{
+    db::{query, query_one},
     errors::{NapkinError, NapkinErrorRoot},
     models::projects::Project,
 };
 
-pub async fn get_projects(client: &Client) -> Result<Vec<Project>, NapkinError> {
-    let _stmt = "SELECT $project_fields FROM projects";
+fn project_not_found() -> NapkinError {
+    NapkinError {
+        code: "PROJECT_NO_ID",
+        message: "Project with ID {project_id} Not Found",
+        root: NapkinErrorRoot::NotFound,
+    }
+}
+
+// A project keeps its scope and name while in the trash, so restoring it cannot clash
+fn project_name_taken(trashed: bool) -> NapkinError {
+    if trashed {
+        NapkinError {
+            code: "PROJECT_NAME_TRASHED",
+            message: "A project in the trash holds this scope and name, restore or purge it first",
+            root: NapkinErrorRoot::Conflict,
+        }
+    } else {
+        NapkinError {
+            code: "PROJECT_EXISTS",
+            message: "A project with this scope and name already exists",
+            root: NapkinErrorRoot::Conflict,
+        }
+    }
+}
+
+// Reports a write that broke `UNIQUE (scope, name)` as a conflict with the holding project
+async fn name_conflict(client: &Client, project_info: &Project, error: NapkinError) -> NapkinError {
+    match &error.root {
+        NapkinErrorRoot::PGError(cause) if cause.code() == Some(&SqlState::UNIQUE_VIOLATION) => {
+            let _stmt = "SELECT $project_fields FROM projects WHERE scope = $1 AND name = $2;";
+            let _stmt = _stmt.replace("$project_fields", &Project::sql_table_fields());
+
+            match query::<Project>(client, &_stmt, &[&project_info.scope, &project_info.name]).await {
+                Ok(holders) => project_name_taken(holders.iter().any(|holder| holder.deleted_at.is_some())),
+                Err(error) => error,
+            }
+        }
+        _ => error,
+    }
+}
+
+// Returns up to `limit + 1` rows so callers can tell whether another page exists;
+// a `None` limit returns every row
+pub async fn get_projects(client: &Client, after: &Option<uuid::Uuid>, limit: Option<i64>) -> Result<Vec<Project>, NapkinError> {
+    let _stmt = "SELECT $project_fields FROM projects WHERE deleted_at IS NULL AND ($1::uuid IS NULL OR id > $1) ORDER BY id LIMIT $2;";
     let _stmt = _stmt.replace("$project_fields", &Project::sql_table_fields());
-    // Uuid type needs to be casted, otherwise death
-    let _stmt = _stmt.replace("id", "id::text");
-    println!("{}", _stmt);
-    let stmt = client.prepare(&_stmt).await.unwrap();
-
-    let results = client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| Project::from_row_ref(row).unwrap())
-        .collect::<Vec<Project>>();
-
-    Ok(results)
+
+    query::<Project>(client, &_stmt, &[after, &limit.map(|limit| limit + 1)]).await
 }
 
 pub async fn add_project(client: &Client, project_info: Project) -> Result<Project, NapkinError> {
     let _stmt = "INSERT INTO projects(scope, name) VALUES ($1, $2) RETURNING $project_fields;";
     let _stmt = _stmt.replace("$project_fields", &Project::sql_table_fields());
-    let _stmt = _stmt.replace("id", "id::text");
-    let stmt = client.prepare(&_stmt).await.unwrap();
-    println!("{}", &_stmt);
-
-    client
-        .query(&stmt, &[&project_info.scope, &project_info.name])
-        .await?
-        .iter()
-        .map(|row| Project::from_row_ref(row).unwrap())
-        .collect::<Vec<Project>>()
-        .pop()
-        .ok_or(NapkinError {
-            code: "PROJECT_NO_ID",
-            message: "Project with ID {id} Not Found",
-            root: NapkinErrorRoot::NotFound,
-        })
+
+    match query_one::<Project>(client, &_stmt, &[&project_info.scope, &project_info.name], project_not_found()).await {
+        Err(error) => Err(name_conflict(client, &project_info, error).await),
+        added => added,
+    }
 }
 
-pub async fn get_project(client: &Client, project_id: &String) -> Result<Project, NapkinError> {
-    let _stmt = "SELECT $project_fields FROM projects WHERE id = ANY ('{$id}');";
+pub async fn get_project(client: &impl GenericClient, project_id: &uuid::Uuid) -> Result<Project, NapkinError> {
+    let _stmt = "SELECT $project_fields FROM projects WHERE id = $1 AND deleted_at IS NULL;";
     let _stmt = _stmt.replace("$project_fields", &Project::sql_table_fields());
-    let _stmt = _stmt.replace("$id", project_id);
-    let _stmt = _stmt.replace("id", "id::text");
-    let stmt = client.prepare(&_stmt).await.unwrap();
-    println!("{}", &_stmt);
-
-    client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| Project::from_row_ref(row).unwrap())
-        .collect::<Vec<Project>>()
-        .pop()
-        .ok_or(NapkinError {
-            code: "PROJECT_NO_ID",
-            message: "Project with ID {project_id} Not Found",
-            root: NapkinErrorRoot::NotFound,
-        })
+
+    query_one::<Project>(client, &_stmt, &[project_id], project_not_found()).await
+}
+
+// Like `get_project`, but keeps the project out of the trash until the transaction ends
+pub async fn lock_project(client: &impl GenericClient, project_id: &uuid::Uuid) -> Result<Project, NapkinError> {
+    let _stmt = "SELECT $project_fields FROM projects WHERE id = $1 AND deleted_at IS NULL FOR SHARE;";
+    let _stmt = _stmt.replace("$project_fields", &Project::sql_table_fields());
+
+    query_one::<Project>(client, &_stmt, &[project_id], project_not_found()).await
 }
 
 pub async fn update_project(
     client: &Client,
-    project_id: &String,
+    project_id: &uuid::Uuid,
     project_info: Project,
+    if_match: &Option<Vec<i64>>,
 ) -> Result<Project, NapkinError> {
-    let _stmt = "UPDATE projects $updates WHERE id = ANY ('{$id}') RETURNING $project_fields;";
+    let _stmt = "UPDATE projects SET scope = $2, name = $3 WHERE id = $1 AND deleted_at IS NULL AND ($4::bigint[] IS NULL OR version = ANY($4)) RETURNING $project_fields;";
     let _stmt = _stmt.replace("$project_fields", &Project::sql_table_fields());
-    let _stmt = _stmt.replace("id", "id::text");
-    let _stmt = _stmt.replace("$id", project_id);
-    let _stmt = _stmt.replace("$updates", &Project::to_update_str(&project_info));
-    let stmt = client.prepare(&_stmt).await.unwrap();
-    println!("{}", &_stmt);
-
-    client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| Project::from_row_ref(row).unwrap())
-        .collect::<Vec<Project>>()
-        .pop()
-        .ok_or(NapkinError {
-            code: "PROJECT_NO_ID",
-            message: "Project with ID {project_id} Not Found",
-            root: NapkinErrorRoot::NotFound,
-        })
+
+    match query_one::<Project>(client, &_stmt, &[project_id, &project_info.scope, &project_info.name, if_match], project_not_found()).await {
+        Err(error) => Err(name_conflict(client, &project_info, error).await),
+        updated => updated,
+    }
 }
 
-pub async fn delete_project(client: &Client, project_id: &String) -> Result<Project, NapkinError> {
-    let _stmt = "DELETE FROM projects WHERE id = ANY ('{$id}') RETURNING $project_fields;";
-    let _stmt = _stmt.replace("$id", project_id);
-    let _stmt = _stmt.replace(
-        "$project_fields",
-        &Project::sql_table_fields(),
-    );
-    let _stmt = _stmt.replace("id", "id::text");
-    println!("{}", &_stmt);
-    let stmt = client.prepare(&_stmt).await.unwrap();
-
-    client
-        .query(&stmt, &[])
-        .await?
-        .iter()
-        .map(|row| Project::from_row_ref(row).unwrap())
-        .collect::<Vec<Project>>()
-        .pop()
-        .ok_or(NapkinError {
-            code: "PROJECT_NO_ID",
-            message: "Project with ID {project_id} Not Found",
-            root: NapkinErrorRoot::NotFound,
-        })
+// Moves the project to the trash; its live nodes and edges share its `deleted_at`
+pub async fn delete_project(client: &Client, project_id: &uuid::Uuid, if_match: &Option<Vec<i64>>) -> Result<Project, NapkinError> {
+    let _stmt = "WITH project AS (
+            SELECT id FROM projects WHERE id = $1 AND deleted_at IS NULL AND ($2::bigint[] IS NULL OR version = ANY($2))
+        ), trashed_nodes AS (
+            UPDATE nodes SET deleted_at = now() WHERE project IN (SELECT id FROM project) AND deleted_at IS NULL
+        ), trashed_edges AS (
+            UPDATE edges SET deleted_at = now() WHERE project IN (SELECT id FROM project) AND deleted_at IS NULL
+        )
+        UPDATE projects SET deleted_at = now()
+        WHERE id = $1 AND deleted_at IS NULL AND ($2::bigint[] IS NULL OR version = ANY($2))
+        RETURNING $project_fields;";
+    let _stmt = _stmt.replace("$project_fields", &Project::sql_table_fields());
+
+    query_one::<Project>(client, &_stmt, &[project_id, if_match], project_not_found()).await
+}
+
+pub async fn get_trashed_projects(client: &Client, after: &Option<uuid::Uuid>, limit: Option<i64>) -> Result<Vec<Project>, NapkinError> {
+    let _stmt = "SELECT $project_fields FROM projects WHERE deleted_at IS NOT NULL AND ($1::uuid IS NULL OR id > $1) ORDER BY id LIMIT $2;";
+    let _stmt = _stmt.replace("$project_fields", &Project::sql_table_fields());
+
+    query::<Project>(client, &_stmt, &[after, &limit.map(|limit| limit + 1)]).await
+}
+
+// Restores the project with the nodes and edges that were trashed along with it
+pub async fn restore_project(client: &Client, project_id: &uuid::Uuid) -> Result<Project, NapkinError> {
+    let _stmt = "WITH project AS (
+            SELECT id, deleted_at FROM projects WHERE id = $1 AND deleted_at IS NOT NULL
+        ), restored_nodes AS (
+            UPDATE nodes SET deleted_at = NULL FROM project WHERE nodes.project = project.id AND nodes.deleted_at = project.deleted_at
+        ), restored_edges AS (
+            UPDATE edges SET deleted_at = NULL FROM project WHERE edges.project = project.id AND edges.deleted_at = project.deleted_at
+        )
+        UPDATE projects SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL RETURNING $project_fields;";
+    let _stmt = _stmt.replace("$project_fields", &Project::sql_table_fields());
+
+    query_one::<Project>(client, &_stmt, &[project_id], project_not_found()).await
+}
+
+// Permanently deletes a trashed project with its nodes and every edge touching them;
+// metadata, artifacts and schemas cascade
+pub async fn purge_project(client: &Client, project_id: &uuid::Uuid) -> Result<Project, NapkinError> {
+    let _stmt = "WITH project AS (
+            SELECT id FROM projects WHERE id = $1 AND deleted_at IS NOT NULL
+        ), purged_edges AS (
+            DELETE FROM edges
+            WHERE project IN (SELECT id FROM project)
+                OR source IN (SELECT nodes.id FROM nodes JOIN project ON nodes.project = project.id)
+                OR target IN (SELECT nodes.id FROM nodes JOIN project ON nodes.project = project.id)
+        ), purged_nodes AS (
+            DELETE FROM nodes WHERE project IN (SELECT id FROM project)
+        )
+        DELETE FROM projects WHERE id = $1 AND deleted_at IS NOT NULL RETURNING $project_fields;";
+    let _stmt = _stmt.replace("$project_fields", &Project::sql_table_fields());
+
+    query_one::<Project>(client, &_stmt, &[project_id], project_not_found()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_held_by_trashed_projects_conflict_with_their_own_code() {
+        let trashed = project_name_taken(true);
+        assert_eq!(trashed.code, "PROJECT_NAME_TRASHED");
+        assert!(matches!(trashed.root, NapkinErrorRoot::Conflict));
+
+        let live = project_name_taken(false);
+        assert_eq!(live.code, "PROJECT_EXISTS");
+        assert!(matches!(live.root, NapkinErrorRoot::Conflict));
+    }
 }
@@ -0,0 +1,519 @@
+use std::collections::HashMap;
+
+use deadpool_postgres::Client;
+use serde_json::Value;
+use tokio_postgres::error::SqlState;
+
+use crate::{
+    db::{
+        filters::{as_sql_params, push_param, SqlParams},
+        query_dynamic,
+    },
+    errors::{NapkinError, NapkinErrorRoot},
+    models::queries::QueryRow,
+    query::{CompareOp, Expr, NodePattern, Operand, Query, RelDirection, RelPattern},
+};
+
+const DEFAULT_QUERY_LIMIT: i64 = 100;
+const MAX_QUERY_LIMIT: i64 = 1000;
+// Every node pattern is a join, so a careless query can be quadratic or worse
+const QUERY_TIMEOUT: &str = "5s";
+
+fn query_invalid(violation: String) -> NapkinError {
+    NapkinError {
+        code: "QUERY_INVALID",
+        message: "Query cannot be compiled",
+        root: NapkinErrorRoot::Unprocessable(vec![violation]),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Node,
+    Edge,
+}
+
+struct Compiler<'a> {
+    bindings: &'a serde_json::Map<String, Value>,
+    project: Option<uuid::Uuid>,
+    params: SqlParams,
+    // Variable -> what it is bound to, and the table alias it is bound to
+    vars: HashMap<String, (Kind, String)>,
+    tables: Vec<String>,
+    edges: Vec<String>,
+    // (alias, name) -> alias of the joined metadata row
+    properties: HashMap<(String, String), String>,
+    joins: Vec<String>,
+    conditions: Vec<String>,
+}
+
+impl Compiler<'_> {
+    fn lookup(&self, var: &str) -> Result<(Kind, String), NapkinError> {
+        self.vars
+            .get(var)
+            .cloned()
+            .ok_or_else(|| query_invalid(format!("`{}` is not bound by MATCH", var)))
+    }
+
+    // A metadata entry of `alias`, as a nullable JSONB expression
+    fn property(&mut self, kind: Kind, alias: &str, name: &str) -> String {
+        let key = (alias.to_string(), name.to_string());
+        if let Some(joined) = self.properties.get(&key) {
+            return format!("{}.value", joined);
+        }
+        let joined = format!("p{}", self.properties.len());
+        let table = match kind {
+            Kind::Node => "node_metadata",
+            Kind::Edge => "edge_metadata",
+        };
+        let name = push_param(&mut self.params, name.to_string());
+        self.joins.push(format!(
+            "LEFT JOIN {} AS {} ON {}.owner_id = {}.id AND {}.name = {}",
+            table, joined, joined, alias, joined, name
+        ));
+        self.properties.insert(key, joined.clone());
+        format!("{}.value", joined)
+    }
+
+    fn value(&mut self, value: Value) -> String {
+        format!("{}::jsonb", push_param(&mut self.params, value))
+    }
+
+    fn property_conditions(&mut self, kind: Kind, alias: &str, properties: &[(String, Operand)]) -> Result<(), NapkinError> {
+        for (name, value) in properties.iter() {
+            let property = self.property(kind, alias, name);
+            let (value, _) = self.operand(value, false)?;
+            self.conditions.push(format!("{} = {}", property, value));
+        }
+        Ok(())
+    }
+
+    fn bind_node(&mut self, node: &NodePattern) -> Result<String, NapkinError> {
+        let alias = match node.var.as_ref().and_then(|var| self.vars.get(var)) {
+            Some((Kind::Node, alias)) => alias.clone(),
+            Some((Kind::Edge, _)) => {
+                return Err(query_invalid(format!("`{}` is a relationship, not a node", node.var.clone().unwrap_or_default())))
+            }
+            None => {
+                let alias = format!("n{}", self.tables.len());
+                self.tables.push(format!("nodes AS {}", alias));
+                self.conditions.push(format!("{}.deleted_at IS NULL", alias));
+                if let Some(var) = &node.var {
+                    self.vars.insert(var.clone(), (Kind::Node, alias.clone()));
+                }
+                if let Some(project) = self.project {
+                    let project = push_param(&mut self.params, project);
+                    self.conditions.push(format!("{}.project = {}", alias, project));
+                }
+                alias
+            }
+        };
+        if !node.labels.is_empty() {
+            let labels = push_param(&mut self.params, node.labels.clone());
+            self.conditions.push(format!("{}.labels @> {}::text[]", alias, labels));
+        }
+        self.property_conditions(Kind::Node, &alias, &node.properties)?;
+        Ok(alias)
+    }
+
+    fn bind_edge(&mut self, rel: &RelPattern, left: &str, right: &str) -> Result<(), NapkinError> {
+        let alias = format!("e{}", self.edges.len());
+        if let Some(var) = &rel.var {
+            if self.vars.contains_key(var) {
+                return Err(query_invalid(format!("`{}` is bound more than once", var)));
+            }
+            self.vars.insert(var.clone(), (Kind::Edge, alias.clone()));
+        }
+        self.tables.push(format!("edges AS {}", alias));
+        self.conditions.push(format!("{}.deleted_at IS NULL", alias));
+
+        let (from, to) = match rel.direction {
+            RelDirection::Left => (right, left),
+            RelDirection::Right | RelDirection::Either => (left, right),
+        };
+        // Undirected edges match a pattern either way round, as in traversals
+        let reverse = match rel.direction {
+            RelDirection::Either => "",
+            RelDirection::Left | RelDirection::Right => "NOT {e}.directed AND ",
+        };
+        self.conditions.push(
+            format!(
+                "(({{e}}.source = {f}.id AND {{e}}.target = {t}.id) OR ({}{{e}}.source = {t}.id AND {{e}}.target = {f}.id))",
+                reverse,
+                f = from,
+                t = to
+            )
+            .replace("{e}", &alias),
+        );
+        if !rel.types.is_empty() {
+            let types = push_param(&mut self.params, rel.types.clone());
+            self.conditions.push(format!("{}.label = ANY({})", alias, types));
+        }
+        // Like Cypher, one edge cannot match two relationships of the same query
+        for other in self.edges.iter() {
+            self.conditions.push(format!("{}.id <> {}.id", alias, other));
+        }
+        self.edges.push(alias.clone());
+        self.property_conditions(Kind::Edge, &alias, &rel.properties)
+    }
+
+    // SQL for `operand` as JSONB, and whether it is an aggregate
+    fn operand(&mut self, operand: &Operand, returned: bool) -> Result<(String, bool), NapkinError> {
+        match operand {
+            Operand::Variable(var) => {
+                if !returned {
+                    return Err(query_invalid(format!("`{}` is a whole node or edge; compare a property such as `{}.name`", var, var)));
+                }
+                let sql = match self.lookup(var)? {
+                    (Kind::Node, alias) => format!("jsonb_build_object('id', {a}.id, 'project', {a}.project, 'labels', {a}.labels)", a = alias),
+                    (Kind::Edge, alias) => format!(
+                        "jsonb_build_object('id', {a}.id, 'project', {a}.project, 'source', {a}.source, 'target', {a}.target, \
+                         'label', {a}.label, 'directed', {a}.directed, 'weight', {a}.weight)",
+                        a = alias
+                    ),
+                };
+                Ok((sql, false))
+            }
+            Operand::Property(var, name) => {
+                let (kind, alias) = self.lookup(var)?;
+                Ok((self.property(kind, &alias, name), false))
+            }
+            Operand::Id(var) => Ok((format!("to_jsonb({}.id)", self.lookup(var)?.1), false)),
+            Operand::Type(var) => match self.lookup(var)? {
+                (Kind::Edge, alias) => Ok((format!("to_jsonb({}.label)", alias), false)),
+                (Kind::Node, _) => Err(query_invalid(format!("type({}) needs a relationship; `{}` is a node", var, var))),
+            },
+            Operand::Count(_) if !returned => Err(query_invalid("count() is only allowed in RETURN".to_string())),
+            Operand::Count(None) => Ok(("to_jsonb(count(*))".to_string(), true)),
+            Operand::Count(Some(var)) => Ok((format!("to_jsonb(count({}.id))", self.lookup(var)?.1), true)),
+            Operand::Literal(value) => Ok((self.value(value.clone()), false)),
+            Operand::Param(name) => match self.bindings.get(name) {
+                Some(value) => Ok((self.value(value.clone()), false)),
+                None => Err(query_invalid(format!("`${}` is not in `params`", name))),
+            },
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Result<String, NapkinError> {
+        Ok(match expr {
+            Expr::And(left, right) => format!("({} AND {})", self.expr(left)?, self.expr(right)?),
+            Expr::Or(left, right) => format!("({} OR {})", self.expr(left)?, self.expr(right)?),
+            Expr::Not(inner) => format!("(NOT {})", self.expr(inner)?),
+            Expr::IsNull(operand, false) => {
+                let (value, _) = self.operand(operand, false)?;
+                format!("({v} IS NULL OR jsonb_typeof({v}) = 'null')", v = value)
+            }
+            Expr::IsNull(operand, true) => {
+                let (value, _) = self.operand(operand, false)?;
+                format!("({v} IS NOT NULL AND jsonb_typeof({v}) <> 'null')", v = value)
+            }
+            Expr::Compare(left, op, right) => {
+                let (l, _) = self.operand(left, false)?;
+                let (r, _) = self.operand(right, false)?;
+                // Ordering only holds between two numbers or two strings; anything else is unknown
+                let ordered = |operator: &str| {
+                    format!(
+                        "(CASE WHEN jsonb_typeof({l}) = jsonb_typeof({r}) AND jsonb_typeof({l}) IN ('number', 'string') THEN {l} {o} {r} END)",
+                        l = l,
+                        r = r,
+                        o = operator
+                    )
+                };
+                let text = |template: &str| {
+                    format!(
+                        "(CASE WHEN jsonb_typeof({l}) = 'string' AND jsonb_typeof({r}) = 'string' THEN {t} END)",
+                        l = l,
+                        r = r,
+                        t = template.replace("{l}", &format!("({} #>> '{{}}')", l)).replace("{r}", &format!("({} #>> '{{}}')", r))
+                    )
+                };
+                match op {
+                    CompareOp::Eq => format!("({} = {})", l, r),
+                    CompareOp::Ne => format!("({} <> {})", l, r),
+                    CompareOp::Lt => ordered("<"),
+                    CompareOp::Lte => ordered("<="),
+                    CompareOp::Gt => ordered(">"),
+                    CompareOp::Gte => ordered(">="),
+                    // Substring of a string, or element of an array
+                    CompareOp::Contains => format!(
+                        "(CASE WHEN jsonb_typeof({l}) = 'array' THEN {l} @> jsonb_build_array({r}) ELSE {t} END)",
+                        l = l,
+                        r = r,
+                        t = text("strpos({l}, {r}) > 0")
+                    ),
+                    CompareOp::StartsWith => text("starts_with({l}, {r})"),
+                    CompareOp::EndsWith => text("right({l}, length({r})) = {r}"),
+                }
+            }
+        })
+    }
+}
+
+// Compiles `query` to one statement yielding a JSON object per row, keyed by the returned
+// column names. Every value, name and label is bound through the returned parameters.
+pub fn compile_query(
+    query: &Query,
+    project: Option<uuid::Uuid>,
+    bindings: &serde_json::Map<String, Value>,
+) -> Result<(String, SqlParams), NapkinError> {
+    let mut compiler = Compiler {
+        bindings,
+        project,
+        params: Vec::new(),
+        vars: HashMap::new(),
+        tables: Vec::new(),
+        edges: Vec::new(),
+        properties: HashMap::new(),
+        joins: Vec::new(),
+        conditions: Vec::new(),
+    };
+
+    for pattern in query.patterns.iter() {
+        let mut left = compiler.bind_node(&pattern.start)?;
+        for (rel, node) in pattern.steps.iter() {
+            let right = compiler.bind_node(node)?;
+            compiler.bind_edge(rel, &left, &right)?;
+            left = right;
+        }
+    }
+    if let Some(filter) = &query.filter {
+        let filter = compiler.expr(filter)?;
+        compiler.conditions.push(filter);
+    }
+
+    let mut columns = Vec::new();
+    let mut group_by = Vec::new();
+    let mut aggregated = false;
+    for item in query.returns.iter() {
+        if query.returns.iter().filter(|other| other.name == item.name).count() > 1 {
+            return Err(query_invalid(format!("column `{}` is returned more than once", item.name)));
+        }
+        let (sql, aggregate) = compiler.operand(&item.operand, true)?;
+        aggregated |= aggregate;
+        if !aggregate && !matches!(item.operand, Operand::Literal(_) | Operand::Param(_)) {
+            group_by.push(sql.clone());
+        }
+        let name = push_param(&mut compiler.params, item.name.clone());
+        columns.push(format!("{}::text, {}", name, sql));
+    }
+
+    let mut order = Vec::new();
+    for (name, descending) in query.order.iter() {
+        if !query.returns.iter().any(|item| &item.name == name) {
+            return Err(query_invalid(format!("ORDER BY `{}` is not a returned column", name)));
+        }
+        let name = push_param(&mut compiler.params, name.clone());
+        order.push(format!("q.row -> {}::text{}", name, if *descending { " DESC" } else { "" }));
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_QUERY_LIMIT);
+    if limit > MAX_QUERY_LIMIT {
+        return Err(query_invalid(format!("LIMIT must be at most {}", MAX_QUERY_LIMIT)));
+    }
+    let limit = push_param(&mut compiler.params, limit);
+
+    let conditions = if compiler.conditions.is_empty() { "TRUE".to_string() } else { compiler.conditions.join(" AND ") };
+    let mut stmt = format!(
+        "SELECT {}jsonb_build_object({}) AS row FROM {} {} WHERE {}",
+        if query.distinct { "DISTINCT " } else { "" },
+        columns.join(", "),
+        compiler.tables.join(" CROSS JOIN "),
+        compiler.joins.join(" "),
+        conditions
+    );
+    if aggregated && !group_by.is_empty() {
+        stmt.push_str(&format!(" GROUP BY {}", group_by.join(", ")));
+    }
+    let mut stmt = format!("SELECT row FROM ({}) AS q", stmt);
+    if !order.is_empty() {
+        stmt.push_str(&format!(" ORDER BY {}", order.join(", ")));
+    }
+    stmt.push_str(&format!(" LIMIT {};", limit));
+
+    Ok((stmt, compiler.params))
+}
+
+// Runs a compiled query read-only and under a statement timeout
+pub async fn run_query(client: &mut Client, stmt: &str, params: &SqlParams) -> Result<Vec<QueryRow>, NapkinError> {
+    let transaction = client.build_transaction().read_only(true).start().await?;
+    transaction
+        .batch_execute(&format!("SET LOCAL statement_timeout = '{}';", QUERY_TIMEOUT))
+        .await?;
+
+    let rows = query_dynamic::<QueryRow>(&transaction, stmt, &as_sql_params(params))
+        .await
+        .map_err(|error| match &error.root {
+            NapkinErrorRoot::PGError(cause) if cause.code() == Some(&SqlState::QUERY_CANCELED) => NapkinError {
+                code: "QUERY_TIMEOUT",
+                message: "Query ran longer than 5 seconds",
+                root: NapkinErrorRoot::BadRequest,
+            },
+            _ => error,
+        })?;
+
+    transaction.commit().await?;
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::query::parse_query;
+
+    fn compile(text: &str, project: Option<uuid::Uuid>, bindings: Value) -> Result<(String, Vec<String>), NapkinError> {
+        let query = parse_query(text).unwrap_or_else(|err| panic!("`{}` did not parse: {}", text, err.message));
+        let Value::Object(bindings) = bindings else {
+            panic!("bindings must be an object");
+        };
+        let (stmt, params) = compile_query(&query, project, &bindings)?;
+        Ok((stmt, params.iter().map(|param| format!("{:?}", param)).collect()))
+    }
+
+    fn compiled(text: &str, project: Option<uuid::Uuid>, bindings: Value) -> (String, Vec<String>) {
+        compile(text, project, bindings).unwrap_or_else(|err| panic!("`{}` did not compile: {}", text, err.message))
+    }
+
+    fn violation(text: &str) -> String {
+        match compile(text, None, json!({})) {
+            Ok((stmt, _)) => panic!("`{}` compiled to {}", text, stmt),
+            Err(NapkinError { root: NapkinErrorRoot::Unprocessable(violations), .. }) => violations.join("; "),
+            Err(err) => panic!("`{}` failed with {}", text, err.message),
+        }
+    }
+
+    #[test]
+    fn compiles_directed_relationships() {
+        let (stmt, params) = compiled("MATCH (a)-[r:calls]->(b) RETURN b", Some(uuid::Uuid::nil()), json!({}));
+        assert_eq!(
+            stmt,
+            "SELECT row FROM (SELECT jsonb_build_object($4::text, jsonb_build_object('id', n1.id, 'project', n1.project, 'labels', n1.labels)) AS row \
+             FROM nodes AS n0 CROSS JOIN nodes AS n1 CROSS JOIN edges AS e0  \
+             WHERE n0.deleted_at IS NULL AND n0.project = $1 AND n1.deleted_at IS NULL AND n1.project = $2 AND e0.deleted_at IS NULL \
+             AND ((e0.source = n0.id AND e0.target = n1.id) OR (NOT e0.directed AND e0.source = n1.id AND e0.target = n0.id)) \
+             AND e0.label = ANY($3)) AS q LIMIT $5;"
+        );
+        assert_eq!(
+            params,
+            vec![
+                "00000000-0000-0000-0000-000000000000",
+                "00000000-0000-0000-0000-000000000000",
+                r#"["calls"]"#,
+                r#""b""#,
+                "100"
+            ]
+        );
+
+        // `<-` swaps the ends
+        let (stmt, params) = compiled("MATCH (a)<-[r]-(b) RETURN id(a) AS id", None, json!({}));
+        assert_eq!(
+            stmt,
+            "SELECT row FROM (SELECT jsonb_build_object($1::text, to_jsonb(n0.id)) AS row \
+             FROM nodes AS n0 CROSS JOIN nodes AS n1 CROSS JOIN edges AS e0  \
+             WHERE n0.deleted_at IS NULL AND n1.deleted_at IS NULL AND e0.deleted_at IS NULL \
+             AND ((e0.source = n1.id AND e0.target = n0.id) OR (NOT e0.directed AND e0.source = n0.id AND e0.target = n1.id))) AS q LIMIT $2;"
+        );
+        assert_eq!(params, vec![r#""id""#, "100"]);
+    }
+
+    #[test]
+    fn compiles_undirected_relationships() {
+        let (stmt, params) = compiled("MATCH (a)-[]-(b)-[]-(c) RETURN id(c) AS id", None, json!({}));
+        assert_eq!(
+            stmt,
+            "SELECT row FROM (SELECT jsonb_build_object($1::text, to_jsonb(n3.id)) AS row \
+             FROM nodes AS n0 CROSS JOIN nodes AS n1 CROSS JOIN edges AS e0 CROSS JOIN nodes AS n3 CROSS JOIN edges AS e1  \
+             WHERE n0.deleted_at IS NULL AND n1.deleted_at IS NULL AND e0.deleted_at IS NULL \
+             AND ((e0.source = n0.id AND e0.target = n1.id) OR (e0.source = n1.id AND e0.target = n0.id)) \
+             AND n3.deleted_at IS NULL AND e1.deleted_at IS NULL \
+             AND ((e1.source = n1.id AND e1.target = n3.id) OR (e1.source = n3.id AND e1.target = n1.id)) \
+             AND e1.id <> e0.id) AS q LIMIT $2;"
+        );
+        assert_eq!(params, vec![r#""id""#, "100"]);
+    }
+
+    #[test]
+    fn compiles_properties_and_params() {
+        let (stmt, params) = compiled(
+            "MATCH (a:Task {status: 'open'}) WHERE a.status = $status AND a.rank > -1 RETURN a.title",
+            None,
+            json!({"status": "open"}),
+        );
+        // `a.status` reuses the join made for the property map
+        assert_eq!(
+            stmt,
+            "SELECT row FROM (SELECT jsonb_build_object($8::text, p2.value) AS row FROM nodes AS n0 \
+             LEFT JOIN node_metadata AS p0 ON p0.owner_id = n0.id AND p0.name = $2 \
+             LEFT JOIN node_metadata AS p1 ON p1.owner_id = n0.id AND p1.name = $5 \
+             LEFT JOIN node_metadata AS p2 ON p2.owner_id = n0.id AND p2.name = $7 \
+             WHERE n0.deleted_at IS NULL AND n0.labels @> $1::text[] AND p0.value = $3::jsonb \
+             AND ((p0.value = $4::jsonb) AND (CASE WHEN jsonb_typeof(p1.value) = jsonb_typeof($6::jsonb) \
+             AND jsonb_typeof(p1.value) IN ('number', 'string') THEN p1.value > $6::jsonb END))) AS q LIMIT $9;"
+        );
+        assert_eq!(
+            params,
+            vec![
+                r#"["Task"]"#,
+                r#""status""#,
+                r#"String("open")"#,
+                r#"String("open")"#,
+                r#""rank""#,
+                "Number(-1)",
+                r#""title""#,
+                r#""a.title""#,
+                "100"
+            ]
+        );
+    }
+
+    #[test]
+    fn compiles_count_with_group_by() {
+        let (stmt, params) = compiled("MATCH (a)-[r]->(b) RETURN a.team, count(b) AS n", None, json!({}));
+        assert_eq!(
+            stmt,
+            "SELECT row FROM (SELECT jsonb_build_object($2::text, p0.value, $3::text, to_jsonb(count(n1.id))) AS row \
+             FROM nodes AS n0 CROSS JOIN nodes AS n1 CROSS JOIN edges AS e0 \
+             LEFT JOIN node_metadata AS p0 ON p0.owner_id = n0.id AND p0.name = $1 \
+             WHERE n0.deleted_at IS NULL AND n1.deleted_at IS NULL AND e0.deleted_at IS NULL \
+             AND ((e0.source = n0.id AND e0.target = n1.id) OR (NOT e0.directed AND e0.source = n1.id AND e0.target = n0.id)) \
+             GROUP BY p0.value) AS q LIMIT $4;"
+        );
+        assert_eq!(params, vec![r#""team""#, r#""a.team""#, r#""n""#, "100"]);
+
+        // Without other columns there is nothing to group by
+        let (stmt, _) = compiled("MATCH (a) RETURN count(*) AS n", None, json!({}));
+        assert!(!stmt.contains("GROUP BY"), "{}", stmt);
+    }
+
+    #[test]
+    fn compiles_distinct_and_order_by() {
+        let (stmt, params) = compiled("MATCH (a) RETURN DISTINCT a.team AS team, id(a) AS id ORDER BY team DESC, id LIMIT 7", None, json!({}));
+        assert_eq!(
+            stmt,
+            "SELECT row FROM (SELECT DISTINCT jsonb_build_object($2::text, p0.value, $3::text, to_jsonb(n0.id)) AS row FROM nodes AS n0 \
+             LEFT JOIN node_metadata AS p0 ON p0.owner_id = n0.id AND p0.name = $1 \
+             WHERE n0.deleted_at IS NULL) AS q ORDER BY q.row -> $4::text DESC, q.row -> $5::text LIMIT $6;"
+        );
+        assert_eq!(params, vec![r#""team""#, r#""team""#, r#""id""#, r#""team""#, r#""id""#, "7"]);
+    }
+
+    #[test]
+    fn rejects_rebound_variables() {
+        assert_eq!(violation("MATCH (a)-[a]->(b) RETURN b"), "`a` is bound more than once");
+        assert_eq!(violation("MATCH (a)-[r]->(b), (r) RETURN b"), "`r` is a relationship, not a node");
+        assert_eq!(violation("MATCH (a)-[r]->(b), (b)-[r]->(c) RETURN c"), "`r` is bound more than once");
+    }
+
+    #[test]
+    fn rejects_invalid_queries() {
+        assert_eq!(violation("MATCH (a) RETURN b"), "`b` is not bound by MATCH");
+        assert_eq!(violation("MATCH (a) WHERE a.x = $x RETURN a"), "`$x` is not in `params`");
+        assert_eq!(violation("MATCH (a) WHERE a = 1 RETURN a"), "`a` is a whole node or edge; compare a property such as `a.name`");
+        assert_eq!(violation("MATCH (a) RETURN type(a)"), "type(a) needs a relationship; `a` is a node");
+        assert_eq!(violation("MATCH (a) RETURN a ORDER BY a.x"), "ORDER BY `a.x` is not a returned column");
+        assert_eq!(violation("MATCH (a) RETURN a.x AS x, id(a) AS x"), "column `x` is returned more than once");
+        assert_eq!(violation("MATCH (a) RETURN a LIMIT 1001"), "LIMIT must be at most 1000");
+    }
+}
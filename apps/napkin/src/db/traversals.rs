@@ -0,0 +1,126 @@
+use deadpool_postgres::Client;
+use tokio_pg_mapper::FromTokioPostgresRow;
+use tokio_postgres::error::SqlState;
+
+use crate::{
+    db::query,
+    errors::{NapkinError, NapkinErrorRoot},
+    models::{
+        edges::Edge,
+        traversals::{Direction, NeighborNode, Path, PathParams, Step},
+    },
+};
+
+const PATH_TIMEOUT: &str = "5s";
+
+// Walks up to `depth` hops from `node_id`, including the start node at depth 0.
+// With `node_label`, the walk only steps onto nodes carrying that label.
+pub async fn get_neighbors(
+    client: &Client,
+    node_id: &uuid::Uuid,
+    depth: i32,
+    direction: Direction,
+    label: &Option<String>,
+    node_label: &Option<String>,
+) -> Result<Vec<NeighborNode>, NapkinError> {
+    let _stmt = "WITH RECURSIVE walk(id, depth) AS (
+            SELECT $1::uuid, 0
+            UNION
+            SELECT $next, walk.depth + 1
+            FROM walk
+            JOIN edges ON $join AND edges.deleted_at IS NULL AND ($3::text IS NULL OR edges.label = $3)
+            JOIN nodes AS step ON step.id = $next AND step.deleted_at IS NULL AND ($4::text IS NULL OR step.labels @> ARRAY[$4::text])
+            WHERE walk.depth < $2
+        )
+        SELECT nodes.id, nodes.project, nodes.labels, MIN(walk.depth) AS depth
+        FROM walk
+        JOIN nodes ON nodes.id = walk.id
+        GROUP BY nodes.id, nodes.project, nodes.labels
+        ORDER BY depth, nodes.id;";
+    let (join, next) = direction.step();
+    let _stmt = _stmt.replace("$join", join);
+    let _stmt = _stmt.replace("$next", next);
+
+    query::<NeighborNode>(client, &_stmt, &[node_id, &depth, label, node_label]).await
+}
+
+// Every edge whose source and target are both in `node_ids`
+pub async fn get_edges_between(
+    client: &Client,
+    node_ids: &[uuid::Uuid],
+    label: &Option<String>,
+) -> Result<Vec<Edge>, NapkinError> {
+    let _stmt = "SELECT $edge_fields FROM edges WHERE source = ANY($1) AND target = ANY($1) AND deleted_at IS NULL AND ($2::text IS NULL OR label = $2) ORDER BY id;";
+    let _stmt = _stmt.replace("$edge_fields", &Edge::sql_table_fields());
+
+    query::<Edge>(client, &_stmt, &[&node_ids, label]).await
+}
+
+// Every way to leave one of `node_ids`, for searches that expand a frontier at a time
+pub async fn get_steps(
+    client: &Client,
+    node_ids: &[uuid::Uuid],
+    direction: Direction,
+    label: &Option<String>,
+    node_label: &Option<String>,
+) -> Result<Vec<Step>, NapkinError> {
+    let _stmt = "SELECT walk.id AS node, edges.id AS edge, $next AS next, COALESCE(edges.weight, 1)::float8 AS cost
+        FROM unnest($1::uuid[]) AS walk(id)
+        JOIN edges ON $join AND edges.deleted_at IS NULL AND ($2::text IS NULL OR edges.label = $2)
+        JOIN nodes AS step ON step.id = $next AND step.deleted_at IS NULL AND ($3::text IS NULL OR step.labels @> ARRAY[$3::text])
+        ORDER BY walk.id, edges.id;";
+    let (join, next) = direction.step();
+    let _stmt = _stmt.replace("$join", join);
+    let _stmt = _stmt.replace("$next", next);
+
+    query::<Step>(client, &_stmt, &[&node_ids, label, node_label]).await
+}
+
+// Enumerates every simple path (no repeated nodes) of at most `max_length` edges from
+// `params.from` to `params.to` and returns the `limit` cheapest. The work grows with
+// the number of paths, so it runs read-only under a statement timeout.
+pub async fn get_all_paths(
+    client: &mut Client,
+    params: &PathParams,
+    max_length: i32,
+    direction: Direction,
+    limit: i64,
+) -> Result<Vec<Path>, NapkinError> {
+    let _stmt = "WITH RECURSIVE walk(id, nodes, edges, cost) AS (
+            SELECT $1::uuid, ARRAY[$1::uuid], ARRAY[]::uuid[], 0::float8
+            UNION ALL
+            SELECT $next, walk.nodes || $next, walk.edges || edges.id, walk.cost + COALESCE(edges.weight, 1)
+            FROM walk
+            JOIN edges ON $join AND edges.deleted_at IS NULL AND ($5::text IS NULL OR edges.label = $5)
+            JOIN nodes AS step ON step.id = $next AND step.deleted_at IS NULL AND ($6::text IS NULL OR step.labels @> ARRAY[$6::text])
+            WHERE walk.id <> $2 AND cardinality(walk.edges) < $3 AND NOT $next = ANY(walk.nodes)
+        )
+        SELECT nodes, edges, cost
+        FROM walk
+        WHERE id = $2
+        ORDER BY cost, cardinality(edges), edges
+        LIMIT $4;";
+    let (join, next) = direction.step();
+    let _stmt = _stmt.replace("$join", join);
+    let _stmt = _stmt.replace("$next", next);
+
+    let transaction = client.build_transaction().read_only(true).start().await?;
+    transaction
+        .batch_execute(&format!("SET LOCAL statement_timeout = '{}';", PATH_TIMEOUT))
+        .await?;
+
+    let paths = query::<Path>(&transaction, &_stmt, &[&params.from, &params.to, &max_length, &limit, &params.label, &params.node_label])
+        .await
+        .map_err(|error| match &error.root {
+            NapkinErrorRoot::PGError(cause) if cause.code() == Some(&SqlState::QUERY_CANCELED) => NapkinError {
+                code: "PATH_TIMEOUT",
+                message: "Enumerating paths ran longer than 5 seconds, lower `max_length` or drop `all`",
+                root: NapkinErrorRoot::BadRequest,
+            },
+            _ => error,
+        })?;
+
+    transaction.commit().await?;
+
+    Ok(paths)
+}
@@ -21,6 +21,16 @@ pub fn handle_pool_error(x: PoolError) -> NapkinError {
 pub enum NapkinErrorRoot {
     #[display(fmt = "{{ \"error\": \"Not Found\" }}")]
     NotFound,
+    #[display(fmt = "{{ \"error\": \"Bad Request\" }}")]
+    BadRequest,
+    #[display(fmt = "{{ \"error\": \"Conflict\" }}")]
+    Conflict,
+    // `If-Match` named a version other than the current one
+    #[display(fmt = "{{ \"error\": \"Precondition Failed\" }}")]
+    PreconditionFailed,
+    // One human-readable message per violation
+    #[display(fmt = "{{ \"error\": \"Unprocessable Entity\" }}")]
+    Unprocessable(Vec<String>),
     PGError(PGError),
     PGMError(PGMError),
     PoolError(PoolError),
@@ -43,6 +53,10 @@ impl fmt::Display for NapkinError {
             self.message,
             match &self.root {
                 NapkinErrorRoot::NotFound => "NotFound".to_string(),
+                NapkinErrorRoot::BadRequest => "BadRequest".to_string(),
+                NapkinErrorRoot::Conflict => "Conflict".to_string(),
+                NapkinErrorRoot::PreconditionFailed => "PreconditionFailed".to_string(),
+                NapkinErrorRoot::Unprocessable(ref _violations) => "Unprocessable".to_string(),
                 NapkinErrorRoot::PGError(ref err) => err.to_string(),
                 NapkinErrorRoot::PGMError(ref err) => err.to_string(),
                 NapkinErrorRoot::PoolError(ref err) => err.to_string(),
@@ -63,6 +77,16 @@ impl From<PGError> for NapkinError {
     }
 }
 
+impl From<PGMError> for NapkinError {
+    fn from(err: PGMError) -> Self {
+        NapkinError {
+            code: "DB_MAPPING_ERR",
+            message: "Database Row Could Not Be Mapped.",
+            root: NapkinErrorRoot::PGMError(err),
+        }
+    }
+}
+
 impl error::ResponseError for NapkinError {
     fn error_response(&self) -> HttpResponse {
         let status_code = self.status_code();
@@ -72,6 +96,10 @@ impl error::ResponseError for NapkinError {
             "message": &self.message,
             "root": match &self.root {
                 NapkinErrorRoot::NotFound => "NotFound".to_string(),
+                NapkinErrorRoot::BadRequest => "BadRequest".to_string(),
+                NapkinErrorRoot::Conflict => "Conflict".to_string(),
+                NapkinErrorRoot::PreconditionFailed => "PreconditionFailed".to_string(),
+                NapkinErrorRoot::Unprocessable(ref violations) => violations.join("; "),
                 NapkinErrorRoot::PGError(ref err) => err.to_string(),
                 NapkinErrorRoot::PGMError(ref err) => err.to_string(),
                 NapkinErrorRoot::PoolError(ref err) => err.to_string(),
@@ -86,6 +114,14 @@ impl error::ResponseError for NapkinError {
             NapkinErrorRoot::PoolError(ref err) => HttpResponse::build(status_code)
                 .insert_header(ContentType::json())
                 .body(err.to_string()),
+            NapkinErrorRoot::Unprocessable(ref violations) => HttpResponse::build(status_code)
+                .insert_header(ContentType::json())
+                .body(::serde_json::json!({
+                    "code": &self.code,
+                    "message": &self.message,
+                    "root": "Unprocessable",
+                    "violations": violations,
+                }).to_string()),
             _ => HttpResponse::build(status_code)
                 .insert_header(ContentType::json())
                 .body(self.to_string()),
@@ -95,6 +131,10 @@ impl error::ResponseError for NapkinError {
     fn status_code(&self) -> StatusCode {
         match &self.root {
             NapkinErrorRoot::NotFound => StatusCode::NOT_FOUND,
+            NapkinErrorRoot::BadRequest => StatusCode::BAD_REQUEST,
+            NapkinErrorRoot::Conflict => StatusCode::CONFLICT,
+            NapkinErrorRoot::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
+            NapkinErrorRoot::Unprocessable(ref _violations) => StatusCode::UNPROCESSABLE_ENTITY,
             NapkinErrorRoot::PoolError(ref _err) => StatusCode::INTERNAL_SERVER_ERROR,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
@@ -0,0 +1,110 @@
+use crate::formats::Diagram;
+
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => {}
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Always a `digraph`, so projects mixing edge kinds render in one graph; undirected edges drop their arrowhead
+pub fn write_dot(diagram: &Diagram) -> String {
+    let mut dot = format!("digraph {} {{\n", quote(&diagram.name));
+    dot.push_str("  rankdir=LR;\n");
+    for node in diagram.nodes.iter() {
+        dot.push_str(&format!("  {} [label={}];\n", quote(&node.id.to_string()), quote(&node.text)));
+    }
+    for edge in diagram.edges.iter() {
+        let mut attributes = Vec::new();
+        if let Some(text) = &edge.text {
+            attributes.push(format!("label={}", quote(text)));
+        }
+        if !edge.directed {
+            attributes.push("dir=none".to_string());
+        }
+        dot.push_str(&format!("  {} -> {}", quote(&edge.source.to_string()), quote(&edge.target.to_string())));
+        if !attributes.is_empty() {
+            dot.push_str(&format!(" [{}]", attributes.join(", ")));
+        }
+        dot.push_str(";\n");
+    }
+    dot.push_str("}\n");
+
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::{DiagramEdge, DiagramNode};
+
+    // Reads back a quoted DOT string at the start of `text`, returning it and what follows
+    fn unquote(text: &str) -> (String, &str) {
+        let mut chars = text.strip_prefix('"').expect("a quoted string").char_indices();
+        let mut value = String::new();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '"' => return (value, text[index + 2..].trim_start()),
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, escaped)) => value.push(escaped),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        panic!("unterminated string in {}", text)
+    }
+
+    fn diagram() -> Diagram {
+        let (a, b) = (uuid::Uuid::from_u128(1), uuid::Uuid::from_u128(2));
+        Diagram {
+            name: "team/\"g\" \\ h".to_string(),
+            nodes: vec![
+                DiagramNode { id: a, text: "Ada \"A\"\nLovelace \\ {x} ->".to_string() },
+                DiagramNode { id: b, text: String::new() },
+            ],
+            edges: vec![
+                DiagramEdge { source: a, target: b, text: Some("cites [1]; \"see\"".to_string()), directed: true },
+                DiagramEdge { source: b, target: a, text: None, directed: false },
+            ],
+        }
+    }
+
+    #[test]
+    fn round_trips_names_and_labels() {
+        let diagram = diagram();
+        let dot = write_dot(&diagram);
+        let mut lines = dot.lines();
+
+        let (name, rest) = unquote(lines.next().unwrap().strip_prefix("digraph ").unwrap());
+        assert_eq!((name.as_str(), rest), (diagram.name.as_str(), "{"));
+        assert_eq!(lines.next(), Some("  rankdir=LR;"));
+        for node in diagram.nodes.iter() {
+            let (id, rest) = unquote(lines.next().unwrap().trim_start());
+            let (text, rest) = unquote(rest.strip_prefix("[label=").unwrap());
+            assert_eq!((id, text.as_str(), rest), (node.id.to_string(), node.text.as_str(), "];"));
+        }
+
+        let (source, rest) = unquote(lines.next().unwrap().trim_start());
+        let (target, rest) = unquote(rest.strip_prefix("-> ").unwrap());
+        let (text, rest) = unquote(rest.strip_prefix("[label=").unwrap());
+        assert_eq!((source, target, Some(text), rest), (diagram.nodes[0].id.to_string(), diagram.nodes[1].id.to_string(), diagram.edges[0].text.clone(), "];"));
+
+        let (source, rest) = unquote(lines.next().unwrap().trim_start());
+        let (target, rest) = unquote(rest.strip_prefix("-> ").unwrap());
+        assert_eq!((source, target, rest), (diagram.nodes[1].id.to_string(), diagram.nodes[0].id.to_string(), "[dir=none];"));
+
+        assert_eq!(lines.next(), Some("}"));
+        assert_eq!(lines.next(), None);
+    }
+}
@@ -0,0 +1,613 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::Value;
+
+use crate::errors::{NapkinError, NapkinErrorRoot};
+use crate::models::{
+    edge_metadata::EdgeMetadata, edges::Edge, exports::{ProjectExport, EXPORT_FORMAT, EXPORT_VERSION},
+    node_metadata::NodeMetadata, nodes::Node, projects::Project,
+};
+
+const GRAPHML_NS: &str = "http://graphml.graphdrawing.org/xmlns";
+// `<desc>` of string keys whose values are JSON text, so objects and arrays survive a round trip
+const JSON_DESC: &str = "napkin:json";
+
+// Key IDs carrying napkin's own columns; namespaced so metadata named `label` or `weight` keeps
+// its own key. The `attr.name`s stay plain, which is what Gephi reads `label` and `weight` by.
+const NODE_LABELS_KEY: (&str, &str) = ("napkin:labels", "labels");
+const EDGE_LABEL_KEY: (&str, &str) = ("napkin:label", "label");
+const EDGE_WEIGHT_KEY: (&str, &str) = ("napkin:weight", "weight");
+
+// Keys napkin maps onto node and edge columns rather than metadata
+fn reserved(key_id: &str) -> bool {
+    [NODE_LABELS_KEY.0, EDGE_LABEL_KEY.0, EDGE_WEIGHT_KEY.0].contains(&key_id)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum KeyType {
+    Boolean,
+    Long,
+    Double,
+    String,
+    Json,
+}
+
+impl KeyType {
+    fn of(value: &Value) -> KeyType {
+        match value {
+            Value::Bool(_) => KeyType::Boolean,
+            Value::Number(number) if number.is_i64() => KeyType::Long,
+            Value::Number(_) => KeyType::Double,
+            Value::String(_) => KeyType::String,
+            _ => KeyType::Json,
+        }
+    }
+
+    // Narrowest type holding values of both `self` and `other`
+    fn merge(self, other: KeyType) -> KeyType {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (KeyType::Long, KeyType::Double) | (KeyType::Double, KeyType::Long) => KeyType::Double,
+            _ => KeyType::Json,
+        }
+    }
+
+    fn attr_type(self) -> &'static str {
+        match self {
+            KeyType::Boolean => "boolean",
+            KeyType::Long => "long",
+            KeyType::Double => "double",
+            KeyType::String | KeyType::Json => "string",
+        }
+    }
+
+    fn parse(attr_type: &str, json: bool) -> Option<KeyType> {
+        match attr_type {
+            "boolean" => Some(KeyType::Boolean),
+            "int" | "long" => Some(KeyType::Long),
+            "float" | "double" => Some(KeyType::Double),
+            "string" if json => Some(KeyType::Json),
+            "string" => Some(KeyType::String),
+            _ => None,
+        }
+    }
+
+    fn write(self, value: &Value) -> String {
+        match (self, value) {
+            (KeyType::Json, value) => value.to_string(),
+            (_, Value::String(text)) => text.clone(),
+            (_, value) => value.to_string(),
+        }
+    }
+
+    fn read(self, text: &str) -> Option<Value> {
+        match self {
+            KeyType::Boolean => match text.trim() {
+                "true" | "1" => Some(Value::Bool(true)),
+                "false" | "0" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            KeyType::Long => text.trim().parse::<i64>().ok().map(Value::from),
+            // Integral text stays an integer, as mixed integer/float keys are exported as double
+            KeyType::Double => KeyType::Long.read(text).or_else(|| text.trim().parse::<f64>().ok().and_then(serde_json::Number::from_f64).map(Value::Number)),
+            KeyType::String => Some(Value::String(text.to_string())),
+            KeyType::Json => serde_json::from_str(text).ok(),
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Metadata name -> (key ID, type), typed by every value stored under that name
+fn metadata_keys<'a>(prefix: &str, values: impl Iterator<Item = (&'a str, &'a Value)>) -> BTreeMap<&'a str, (String, KeyType)> {
+    let mut types: BTreeMap<&str, KeyType> = BTreeMap::new();
+    for (name, value) in values {
+        let key_type = KeyType::of(value);
+        types
+            .entry(name)
+            .and_modify(|existing| *existing = existing.merge(key_type))
+            .or_insert(key_type);
+    }
+    types
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, key_type))| (name, (format!("{}{}", prefix, index), key_type)))
+        .collect()
+}
+
+fn write_key(xml: &mut String, id: &str, domain: &str, name: &str, key_type: KeyType) {
+    xml.push_str(&format!(
+        "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"",
+        escape(id),
+        domain,
+        escape(name),
+        key_type.attr_type()
+    ));
+    if key_type == KeyType::Json {
+        xml.push_str(&format!("><desc>{}</desc></key>\n", JSON_DESC));
+    } else {
+        xml.push_str("/>\n");
+    }
+}
+
+fn write_data(xml: &mut String, key: &str, text: &str) {
+    xml.push_str(&format!("      <data key=\"{}\">{}</data>\n", escape(key), escape(text)));
+}
+
+// Artifacts and metadata schemas have no GraphML counterpart and are left out
+pub fn write_graphml(document: &ProjectExport) -> String {
+    let node_keys = metadata_keys("n", document.node_metadata.iter().map(|metadata| (metadata.name.as_str(), &metadata.value)));
+    let edge_keys = metadata_keys("e", document.edge_metadata.iter().map(|metadata| (metadata.name.as_str(), &metadata.value)));
+    let mut node_metadata: HashMap<uuid::Uuid, Vec<&NodeMetadata>> = HashMap::new();
+    for metadata in document.node_metadata.iter() {
+        node_metadata.entry(metadata.owner_id).or_default().push(metadata);
+    }
+    let mut edge_metadata: HashMap<uuid::Uuid, Vec<&EdgeMetadata>> = HashMap::new();
+    for metadata in document.edge_metadata.iter() {
+        edge_metadata.entry(metadata.owner_id).or_default().push(metadata);
+    }
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!("<graphml xmlns=\"{}\">\n", GRAPHML_NS));
+    write_key(&mut xml, NODE_LABELS_KEY.0, "node", NODE_LABELS_KEY.1, KeyType::String);
+    write_key(&mut xml, EDGE_LABEL_KEY.0, "edge", EDGE_LABEL_KEY.1, KeyType::String);
+    write_key(&mut xml, EDGE_WEIGHT_KEY.0, "edge", EDGE_WEIGHT_KEY.1, KeyType::Double);
+    for (name, (id, key_type)) in node_keys.iter() {
+        write_key(&mut xml, id, "node", name, *key_type);
+    }
+    for (name, (id, key_type)) in edge_keys.iter() {
+        write_key(&mut xml, id, "edge", name, *key_type);
+    }
+
+    xml.push_str(&format!(
+        "  <graph id=\"{}\" edgedefault=\"directed\">\n",
+        escape(&format!("{}/{}", document.project.scope, document.project.name))
+    ));
+    for node in document.nodes.iter() {
+        let id = node.id.unwrap_or_default();
+        xml.push_str(&format!("    <node id=\"{}\">\n", id));
+        if !node.labels.is_empty() {
+            // Neo4j's GraphML convention, e.g. `:Person:Author`
+            write_data(&mut xml, NODE_LABELS_KEY.0, &format!(":{}", node.labels.join(":")));
+        }
+        for metadata in node_metadata.get(&id).into_iter().flatten() {
+            let (key, key_type) = &node_keys[metadata.name.as_str()];
+            write_data(&mut xml, key, &key_type.write(&metadata.value));
+        }
+        xml.push_str("    </node>\n");
+    }
+    for edge in document.edges.iter() {
+        let id = edge.id.unwrap_or_default();
+        xml.push_str(&format!("    <edge id=\"{}\" source=\"{}\" target=\"{}\"", id, edge.source, edge.target));
+        if !edge.directed {
+            xml.push_str(" directed=\"false\"");
+        }
+        xml.push_str(">\n");
+        if let Some(label) = &edge.label {
+            write_data(&mut xml, EDGE_LABEL_KEY.0, label);
+        }
+        if let Some(weight) = edge.weight {
+            write_data(&mut xml, EDGE_WEIGHT_KEY.0, &weight.to_string());
+        }
+        for metadata in edge_metadata.get(&id).into_iter().flatten() {
+            let (key, key_type) = &edge_keys[metadata.name.as_str()];
+            write_data(&mut xml, key, &key_type.write(&metadata.value));
+        }
+        xml.push_str("    </edge>\n");
+    }
+    xml.push_str("  </graph>\n</graphml>\n");
+
+    xml
+}
+
+struct Key<'a> {
+    id: &'a str,
+    domain: &'a str,
+    name: &'a str,
+    key_type: KeyType,
+    default: Option<String>,
+}
+
+fn graphml_malformed() -> NapkinError {
+    NapkinError {
+        code: "GRAPHML_MALFORMED",
+        message: "Body is not a well-formed GraphML document",
+        root: NapkinErrorRoot::BadRequest,
+    }
+}
+
+fn elements<'a, 'input>(parent: roxmltree::Node<'a, 'input>, name: &'static str) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> {
+    parent.children().filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+fn text_of(node: roxmltree::Node) -> String {
+    node.descendants().filter_map(|child| if child.is_text() { child.text() } else { None }).collect()
+}
+
+// `<data>` values of `element` by key ID, falling back to key defaults
+fn data_of<'a>(element: roxmltree::Node, keys: &'a HashMap<&str, Key>, domain: &str) -> Vec<(&'a Key<'a>, String)> {
+    let mut values: HashMap<&str, String> = elements(element, "data")
+        .filter_map(|data| data.attribute("key").map(|key| (key, text_of(data))))
+        .collect();
+    let mut data: Vec<(&Key, String)> = keys
+        .iter()
+        .filter(|(_, key)| key.domain == domain || key.domain == "all")
+        .filter_map(|(id, key)| values.remove(id).or_else(|| key.default.clone()).map(|text| (key, text)))
+        .collect();
+    data.sort_by(|a, b| a.0.name.cmp(b.0.name));
+    data
+}
+
+// Reads the first `<graph>` into a document whose IDs are placeholders, to be imported with fresh IDs.
+// Node and edge IDs in GraphML are arbitrary strings, so they cannot be restored verbatim.
+pub fn read_graphml(xml: &str) -> Result<ProjectExport, NapkinError> {
+    let document = roxmltree::Document::parse(xml).map_err(|_| graphml_malformed())?;
+    let root = document.root_element();
+    if root.tag_name().name() != "graphml" {
+        return Err(graphml_malformed());
+    }
+    let graph = elements(root, "graph").next().ok_or_else(graphml_malformed)?;
+
+    let mut violations = Vec::new();
+    let mut keys: HashMap<&str, Key> = HashMap::new();
+    for key in elements(root, "key") {
+        let Some(id) = key.attribute("id") else {
+            violations.push("key: missing `id`".to_string());
+            continue;
+        };
+        let json = elements(key, "desc").any(|desc| text_of(desc).trim() == JSON_DESC);
+        let attr_type = key.attribute("attr.type").unwrap_or("string");
+        let Some(key_type) = KeyType::parse(attr_type, json) else {
+            violations.push(format!("key `{}`: unsupported attr.type `{}`", id, attr_type));
+            continue;
+        };
+        let domain = key.attribute("for").unwrap_or("all");
+        let name = key.attribute("attr.name").unwrap_or(id);
+        // Two metadata keys with one name would store two values under it for the same owner
+        let clash = keys
+            .values()
+            .filter(|other| !reserved(id) && !reserved(other.id) && other.name == name)
+            .filter(|other| other.domain == domain || other.domain == "all" || domain == "all")
+            .map(|other| other.id)
+            .min();
+        if let Some(other) = clash {
+            violations.push(format!("key `{}`: attr.name `{}` is already used by key `{}`", id, name, other));
+            continue;
+        }
+        keys.insert(
+            id,
+            Key {
+                id,
+                domain,
+                name,
+                key_type,
+                default: elements(key, "default").next().map(text_of),
+            },
+        );
+    }
+
+    let project_id = uuid::Uuid::nil();
+    let mut next_id: u128 = 0;
+    let mut placeholder = || {
+        next_id += 1;
+        uuid::Uuid::from_u128(next_id)
+    };
+
+    let mut node_ids: HashMap<&str, uuid::Uuid> = HashMap::new();
+    let mut nodes = Vec::new();
+    let mut node_metadata = Vec::new();
+    for node in elements(graph, "node") {
+        let Some(name) = node.attribute("id") else {
+            violations.push("node: missing `id`".to_string());
+            continue;
+        };
+        if node_ids.contains_key(name) {
+            violations.push(format!("node `{}`: duplicate `id`", name));
+            continue;
+        }
+        let id = placeholder();
+        node_ids.insert(name, id);
+        let mut labels = Vec::new();
+        for (key, text) in data_of(node, &keys, "node") {
+            if key.id == NODE_LABELS_KEY.0 {
+                labels = text.split(':').map(str::trim).filter(|label| !label.is_empty()).map(String::from).collect();
+                continue;
+            }
+            match key.key_type.read(&text) {
+                Some(value) => node_metadata.push(NodeMetadata { owner_id: id, name: key.name.to_string(), value, version: 0 }),
+                None => violations.push(format!("node `{}`/{}: `{}` is not a valid {}", name, key.name, text, key.key_type.attr_type())),
+            }
+        }
+        nodes.push(Node { id: Some(id), project: project_id, labels, version: 0, deleted_at: None });
+    }
+
+    let directed_default = graph.attribute("edgedefault") != Some("undirected");
+    let mut edges = Vec::new();
+    let mut edge_metadata = Vec::new();
+    for (index, edge) in elements(graph, "edge").enumerate() {
+        let name = edge.attribute("id").map(String::from).unwrap_or_else(|| format!("#{}", index));
+        let mut endpoints = [uuid::Uuid::nil(); 2];
+        for (endpoint, field) in endpoints.iter_mut().zip(["source", "target"]) {
+            match edge.attribute(field).and_then(|node| node_ids.get(node)) {
+                Some(id) => *endpoint = *id,
+                None => violations.push(format!("edge `{}`/{}: not a node in the graph", name, field)),
+            }
+        }
+        let id = placeholder();
+        let mut label = None;
+        let mut weight = None;
+        for (key, text) in data_of(edge, &keys, "edge") {
+            if key.id == EDGE_LABEL_KEY.0 {
+                label = Some(text);
+                continue;
+            }
+            let value = key.key_type.read(&text);
+            if key.id == EDGE_WEIGHT_KEY.0 {
+                match value.as_ref().and_then(Value::as_f64) {
+                    Some(value) => weight = Some(value),
+                    None => violations.push(format!("edge `{}`/{}: `{}` is not a valid number", name, key.name, text)),
+                }
+                continue;
+            }
+            match value {
+                Some(value) => edge_metadata.push(EdgeMetadata { owner_id: id, name: key.name.to_string(), value, version: 0 }),
+                None => violations.push(format!("edge `{}`/{}: `{}` is not a valid {}", name, key.name, text, key.key_type.attr_type())),
+            }
+        }
+        edges.push(Edge {
+            id: Some(id),
+            project: project_id,
+            source: endpoints[0],
+            target: endpoints[1],
+            label,
+            directed: edge.attribute("directed").map(|directed| directed == "true").unwrap_or(directed_default),
+            weight,
+            version: 0,
+            deleted_at: None,
+        });
+    }
+
+    if !violations.is_empty() {
+        return Err(NapkinError {
+            code: "GRAPHML_INVALID",
+            message: "GraphML document cannot be mapped onto a project, nothing was imported",
+            root: NapkinErrorRoot::Unprocessable(violations),
+        });
+    }
+
+    Ok(ProjectExport {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        project: Project {
+            id: Some(project_id),
+            scope: String::new(),
+            name: graph.attribute("id").unwrap_or_default().to_string(),
+            version: 0,
+            deleted_at: None,
+        },
+        nodes,
+        edges,
+        node_metadata,
+        edge_metadata,
+        artifacts: Vec::new(),
+        artifact_metadata: Vec::new(),
+        metadata_schemas: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn document(nodes: Vec<Node>, edges: Vec<Edge>, node_metadata: Vec<NodeMetadata>, edge_metadata: Vec<EdgeMetadata>) -> ProjectExport {
+        ProjectExport {
+            format: EXPORT_FORMAT.to_string(),
+            version: EXPORT_VERSION,
+            project: Project { id: Some(uuid::Uuid::nil()), scope: "team".to_string(), name: "g & <h>".to_string(), version: 1, deleted_at: None },
+            nodes,
+            edges,
+            node_metadata,
+            edge_metadata,
+            artifacts: Vec::new(),
+            artifact_metadata: Vec::new(),
+            metadata_schemas: Vec::new(),
+        }
+    }
+
+    fn node(id: u128, labels: &[&str]) -> Node {
+        Node { id: Some(uuid::Uuid::from_u128(id)), project: uuid::Uuid::nil(), labels: labels.iter().map(|label| label.to_string()).collect(), version: 1, deleted_at: None }
+    }
+
+    fn edge(id: u128, source: u128, target: u128, label: Option<&str>, directed: bool, weight: Option<f64>) -> Edge {
+        Edge {
+            id: Some(uuid::Uuid::from_u128(id)),
+            project: uuid::Uuid::nil(),
+            source: uuid::Uuid::from_u128(source),
+            target: uuid::Uuid::from_u128(target),
+            label: label.map(String::from),
+            directed,
+            weight,
+            version: 1,
+            deleted_at: None,
+        }
+    }
+
+    fn node_metadata(owner: u128, name: &str, value: Value) -> NodeMetadata {
+        NodeMetadata { owner_id: uuid::Uuid::from_u128(owner), name: name.to_string(), value, version: 1 }
+    }
+
+    fn edge_metadata(owner: u128, name: &str, value: Value) -> EdgeMetadata {
+        EdgeMetadata { owner_id: uuid::Uuid::from_u128(owner), name: name.to_string(), value, version: 1 }
+    }
+
+    // The graph with node and edge IDs replaced by their position, as imports get fresh IDs
+    fn shape(document: &ProjectExport) -> Value {
+        let node_index = |id: uuid::Uuid| document.nodes.iter().position(|node| node.id == Some(id));
+        let nodes: Vec<Value> = document
+            .nodes
+            .iter()
+            .map(|node| {
+                let metadata: serde_json::Map<String, Value> = document
+                    .node_metadata
+                    .iter()
+                    .filter(|metadata| Some(metadata.owner_id) == node.id)
+                    .map(|metadata| (metadata.name.clone(), metadata.value.clone()))
+                    .collect();
+                json!({ "labels": node.labels, "metadata": metadata })
+            })
+            .collect();
+        let edges: Vec<Value> = document
+            .edges
+            .iter()
+            .map(|edge| {
+                let metadata: serde_json::Map<String, Value> = document
+                    .edge_metadata
+                    .iter()
+                    .filter(|metadata| Some(metadata.owner_id) == edge.id)
+                    .map(|metadata| (metadata.name.clone(), metadata.value.clone()))
+                    .collect();
+                json!({
+                    "source": node_index(edge.source),
+                    "target": node_index(edge.target),
+                    "label": edge.label,
+                    "directed": edge.directed,
+                    "weight": edge.weight,
+                    "metadata": metadata,
+                })
+            })
+            .collect();
+        json!({ "nodes": nodes, "edges": edges })
+    }
+
+    fn read(xml: &str) -> ProjectExport {
+        match read_graphml(xml) {
+            Ok(document) => document,
+            Err(err) => panic!("{} ({:?}) reading\n{}", err.message, err.root, xml),
+        }
+    }
+
+    #[test]
+    fn round_trips_a_project() {
+        let original = document(
+            vec![node(1, &["Person", "Author"]), node(2, &[]), node(3, &["Book"])],
+            vec![
+                edge(11, 1, 3, Some("wrote"), true, Some(2.5)),
+                edge(12, 1, 2, None, false, None),
+                edge(13, 3, 2, Some("cites <see> & \"quote\""), true, Some(1.0)),
+            ],
+            vec![
+                node_metadata(1, "name", json!("Ada \"A\" <Lovelace> & co")),
+                node_metadata(1, "born", json!(1815)),
+                node_metadata(2, "born", json!(1.5)),
+                node_metadata(1, "alive", json!(false)),
+                node_metadata(3, "tags", json!(["a", {"b": null}])),
+                node_metadata(3, "count", json!("12")),
+            ],
+            vec![edge_metadata(11, "year", json!(1843)), edge_metadata(13, "note", json!({"page": 4}))],
+        );
+
+        let xml = write_graphml(&original);
+        let read = read(&xml);
+        assert_eq!(read.project.name, "team/g & <h>");
+        assert_eq!(shape(&read), shape(&original));
+    }
+
+    #[test]
+    fn keeps_metadata_named_like_reserved_keys() {
+        let original = document(
+            vec![node(1, &["Task"]), node(2, &[])],
+            vec![edge(11, 1, 2, Some("blocks"), true, Some(3.0)), edge(12, 2, 1, None, true, None)],
+            vec![node_metadata(1, "labels", json!(":not:labels")), node_metadata(2, "labels", json!(["x"]))],
+            vec![
+                edge_metadata(11, "label", json!("metadata label")),
+                edge_metadata(11, "weight", json!("heavy")),
+                edge_metadata(12, "weight", json!(7)),
+            ],
+        );
+
+        let xml = write_graphml(&original);
+        assert!(xml.contains("<key id=\"napkin:weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>"), "{}", xml);
+        assert_eq!(shape(&read(&xml)), shape(&original));
+    }
+
+    #[test]
+    fn reads_foreign_keys_as_metadata() {
+        let xml = r#"<?xml version="1.0"?>
+            <graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+              <key id="d0" for="edge" attr.name="weight" attr.type="double"><default>1.5</default></key>
+              <key id="label" for="node" attr.name="label" attr.type="string"/>
+              <graph id="G" edgedefault="undirected">
+                <node id="a"><data key="label">A</data></node>
+                <node id="b"/>
+                <edge source="a" target="b"/>
+                <edge source="b" target="a" directed="true"><data key="d0">2</data></edge>
+              </graph>
+            </graphml>"#;
+
+        let read = read(xml);
+        assert_eq!(
+            shape(&read),
+            json!({
+                "nodes": [{ "labels": [], "metadata": { "label": "A" } }, { "labels": [], "metadata": {} }],
+                "edges": [
+                    { "source": 0, "target": 1, "label": null, "directed": false, "weight": null, "metadata": { "weight": 1.5 } },
+                    { "source": 1, "target": 0, "label": null, "directed": true, "weight": null, "metadata": { "weight": 2 } },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        assert!(matches!(read_graphml("<graph/>").err().map(|err| err.root), Some(NapkinErrorRoot::BadRequest)));
+        let xml = r#"<graphml>
+              <key id="napkin:weight" for="edge" attr.name="weight" attr.type="double"/>
+              <graph><node id="a"/><node id="a"/><edge source="a" target="c"><data key="napkin:weight">NaN</data></edge></graph>
+            </graphml>"#;
+        match read_graphml(xml).err().map(|err| err.root) {
+            Some(NapkinErrorRoot::Unprocessable(violations)) => assert_eq!(
+                violations,
+                vec![
+                    "node `a`: duplicate `id`".to_string(),
+                    "edge `#0`/target: not a node in the graph".to_string(),
+                    "edge `#0`/weight: `NaN` is not a valid number".to_string(),
+                ]
+            ),
+            root => panic!("expected violations, found {:?}", root),
+        }
+    }
+
+    #[test]
+    fn rejects_keys_sharing_a_metadata_name() {
+        let xml = r#"<graphml>
+              <key id="napkin:weight" for="edge" attr.name="weight" attr.type="double"/>
+              <key id="d0" for="edge" attr.name="weight" attr.type="string"/>
+              <key id="d1" for="node" attr.name="weight" attr.type="string"/>
+              <key id="d2" for="all" attr.name="weight" attr.type="int"/>
+              <key id="d3" for="edge" attr.name="d0" attr.type="string"/>
+              <graph><node id="a"/></graph>
+            </graphml>"#;
+        match read_graphml(xml).err().map(|err| err.root) {
+            Some(NapkinErrorRoot::Unprocessable(violations)) => {
+                assert_eq!(violations, vec!["key `d2`: attr.name `weight` is already used by key `d0`".to_string()])
+            }
+            root => panic!("expected violations, found {:?}", root),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use crate::formats::Diagram;
+
+// Mermaid strings cannot escape quotes with backslashes, only with entity codes; `#` is
+// escaped too so text that looks like an entity code stays literal
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '#' => quoted.push_str("#35;"),
+            '"' => quoted.push_str("#quot;"),
+            '<' => quoted.push_str("#lt;"),
+            '>' => quoted.push_str("#gt;"),
+            '\n' => quoted.push_str("<br/>"),
+            '\r' => {}
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// The frontmatter is YAML, where Mermaid's entity codes would show up verbatim
+fn yaml_quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// A `flowchart` (Mermaid's newer name for `graph`); node IDs are shortened to `n0`, `n1`, ...
+pub fn write_mermaid(diagram: &Diagram) -> String {
+    let node_ids: HashMap<uuid::Uuid, String> = diagram
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (node.id, format!("n{}", index)))
+        .collect();
+
+    let mut mermaid = format!("---\ntitle: {}\n---\n", yaml_quote(&diagram.name));
+    mermaid.push_str("flowchart LR\n");
+    for node in diagram.nodes.iter() {
+        mermaid.push_str(&format!("    {}[{}]\n", node_ids[&node.id], quote(&node.text)));
+    }
+    for edge in diagram.edges.iter() {
+        let (Some(source), Some(target)) = (node_ids.get(&edge.source), node_ids.get(&edge.target)) else {
+            continue;
+        };
+        let arrow = if edge.directed { "-->" } else { "---" };
+        match &edge.text {
+            Some(text) => mermaid.push_str(&format!("    {} {}|{}| {}\n", source, arrow, quote(text), target)),
+            None => mermaid.push_str(&format!("    {} {} {}\n", source, arrow, target)),
+        }
+    }
+
+    mermaid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::{DiagramEdge, DiagramNode};
+
+    // Reads back a string written by `quote`
+    fn unquote(quoted: &str) -> String {
+        let inner = quoted.strip_prefix('"').and_then(|text| text.strip_suffix('"')).expect("a quoted string");
+        assert!(!inner.contains('"'), "unescaped quote in {}", quoted);
+        let mut text = String::new();
+        let mut rest = inner;
+        while let Some(start) = rest.find(['#', '<']) {
+            text.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let (decoded, length) = [("#quot;", '"'), ("#lt;", '<'), ("#gt;", '>'), ("#35;", '#'), ("<br/>", '\n')]
+                .into_iter()
+                .find_map(|(entity, c)| rest.starts_with(entity).then_some((c, entity.len())))
+                .unwrap_or_else(|| panic!("stray `{}` in {}", &rest[..1], quoted));
+            text.push(decoded);
+            rest = &rest[length..];
+        }
+        text.push_str(rest);
+        text
+    }
+
+    #[test]
+    fn round_trips_names_and_labels() {
+        let (a, b, c) = (uuid::Uuid::from_u128(1), uuid::Uuid::from_u128(2), uuid::Uuid::from_u128(3));
+        let diagram = Diagram {
+            name: "team/\"g\" <h> #quot; \\ #1\nnext".to_string(),
+            nodes: vec![
+                DiagramNode { id: a, text: "Ada \"A\"\nLovelace <b>#quot; #1".to_string() },
+                DiagramNode { id: b, text: "[x] --> |y|".to_string() },
+            ],
+            edges: vec![
+                DiagramEdge { source: a, target: b, text: Some("cites \"#lt;\" | <see>".to_string()), directed: true },
+                DiagramEdge { source: b, target: a, text: None, directed: false },
+                // Edges to nodes outside the diagram are skipped
+                DiagramEdge { source: a, target: c, text: None, directed: true },
+            ],
+        };
+        let mermaid = write_mermaid(&diagram);
+        let lines: Vec<&str> = mermaid.lines().collect();
+
+        assert_eq!(lines[0], "---");
+        assert_eq!(lines[1], r#"title: "team/\"g\" <h> #quot; \\ #1\nnext""#);
+        assert_eq!(lines[2..4], ["---", "flowchart LR"]);
+        for (index, node) in diagram.nodes.iter().enumerate() {
+            let shape = lines[4 + index].trim_start().strip_prefix(&format!("n{}[", index)).and_then(|rest| rest.strip_suffix(']')).unwrap();
+            assert_eq!(unquote(shape), node.text);
+        }
+        let (arrow, label) = lines[6].trim_start().strip_prefix("n0 ").unwrap().split_once('|').unwrap();
+        let (label, target) = label.rsplit_once('|').unwrap();
+        assert_eq!((arrow, Some(unquote(label)), target), ("-->", diagram.edges[0].text.clone(), " n1"));
+        assert_eq!(lines[7], "    n1 --- n0");
+        assert_eq!(lines.len(), 8);
+    }
+}
@@ -0,0 +1,23 @@
+pub mod dot;
+pub mod graphml;
+pub mod mermaid;
+pub mod rdf;
+
+// Render-ready graph shared by the diagram formats
+pub struct Diagram {
+    pub name: String,
+    pub nodes: Vec<DiagramNode>,
+    pub edges: Vec<DiagramEdge>,
+}
+
+pub struct DiagramNode {
+    pub id: uuid::Uuid,
+    pub text: String,
+}
+
+pub struct DiagramEdge {
+    pub source: uuid::Uuid,
+    pub target: uuid::Uuid,
+    pub text: Option<String>,
+    pub directed: bool,
+}
@@ -0,0 +1,449 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use crate::models::{edge_metadata::EdgeMetadata, edges::Edge, node_metadata::NodeMetadata, nodes::Node};
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+// Namespace for labels and metadata names unless the caller maps them onto an ontology
+pub const DEFAULT_VOCAB: &str = "urn:napkin:vocab:";
+// Predicate of edges without a label
+const UNLABELLED_EDGE: &str = "related";
+const WEIGHT: &str = "weight";
+
+pub enum Object {
+    Iri(String),
+    Literal(Value),
+}
+
+pub struct Triple {
+    pub subject: String,
+    pub predicate: String,
+    pub object: Object,
+}
+
+// Nodes and edges are identified by their UUIDs (RFC 4122 `urn:uuid:`)
+fn resource(id: &uuid::Uuid) -> String {
+    format!("urn:uuid:{}", id)
+}
+
+// `name` appended to `vocab`, percent-encoding anything but unreserved characters
+fn term(vocab: &str, name: &str) -> String {
+    let mut iri = vocab.to_string();
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            iri.push(byte as char);
+        } else {
+            iri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    iri
+}
+
+// An absolute IRI that can be written between `<>` as-is
+pub fn valid_vocab(vocab: &str) -> bool {
+    vocab.contains(':') && !vocab.chars().any(|c| c.is_whitespace() || c.is_control() || "<>\"{}|^`\\".contains(c))
+}
+
+// Node labels become `rdf:type`s. Undirected edges are stated both ways. Edges with a
+// weight or metadata are also reified as `rdf:Statement`s carrying those as literals.
+pub fn project_triples(
+    vocab: &str,
+    nodes: &[Node],
+    edges: &[Edge],
+    node_metadata: &[NodeMetadata],
+    edge_metadata: &[EdgeMetadata],
+) -> Vec<Triple> {
+    let rdf_type = format!("{}type", RDF);
+    let mut triples = Vec::new();
+
+    for node in nodes.iter() {
+        let subject = resource(&node.id.unwrap_or_default());
+        for label in node.labels.iter() {
+            triples.push(Triple { subject: subject.clone(), predicate: rdf_type.clone(), object: Object::Iri(term(vocab, label)) });
+        }
+    }
+    for metadata in node_metadata.iter() {
+        triples.push(Triple {
+            subject: resource(&metadata.owner_id),
+            predicate: term(vocab, &metadata.name),
+            object: Object::Literal(metadata.value.clone()),
+        });
+    }
+
+    let mut metadata_by_edge: HashMap<uuid::Uuid, Vec<&EdgeMetadata>> = HashMap::new();
+    for metadata in edge_metadata.iter() {
+        metadata_by_edge.entry(metadata.owner_id).or_default().push(metadata);
+    }
+    for edge in edges.iter() {
+        let id = edge.id.unwrap_or_default();
+        let (source, target) = (resource(&edge.source), resource(&edge.target));
+        let predicate = term(vocab, edge.label.as_deref().unwrap_or(UNLABELLED_EDGE));
+        triples.push(Triple { subject: source.clone(), predicate: predicate.clone(), object: Object::Iri(target.clone()) });
+        if !edge.directed {
+            triples.push(Triple { subject: target.clone(), predicate: predicate.clone(), object: Object::Iri(source.clone()) });
+        }
+
+        let metadata = metadata_by_edge.remove(&id).unwrap_or_default();
+        if edge.weight.is_none() && metadata.is_empty() {
+            continue;
+        }
+        let statement = resource(&id);
+        for (name, object) in [
+            ("type", Object::Iri(format!("{}Statement", RDF))),
+            ("subject", Object::Iri(source)),
+            ("predicate", Object::Iri(predicate)),
+            ("object", Object::Iri(target)),
+        ] {
+            triples.push(Triple { subject: statement.clone(), predicate: format!("{}{}", RDF, name), object });
+        }
+        if let Some(weight) = edge.weight {
+            triples.push(Triple { subject: statement.clone(), predicate: term(vocab, WEIGHT), object: Object::Literal(json!(weight)) });
+        }
+        for metadata in metadata {
+            triples.push(Triple {
+                subject: statement.clone(),
+                predicate: term(vocab, &metadata.name),
+                object: Object::Literal(metadata.value.clone()),
+            });
+        }
+    }
+
+    triples
+}
+
+// Triples grouped by subject, subjects in order of first appearance
+fn by_subject(triples: &[Triple]) -> Vec<(&str, Vec<&Triple>)> {
+    let mut groups: Vec<(&str, Vec<&Triple>)> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for triple in triples.iter() {
+        let position = *index.entry(triple.subject.as_str()).or_insert_with(|| {
+            groups.push((triple.subject.as_str(), Vec::new()));
+            groups.len() - 1
+        });
+        groups[position].1.push(triple);
+    }
+    groups
+}
+
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Lexical form and datatype IRI; strings are plain literals
+fn literal(value: &Value) -> (String, Option<String>) {
+    match value {
+        Value::String(text) => (text.clone(), None),
+        Value::Bool(flag) => (flag.to_string(), Some(format!("{}boolean", XSD))),
+        Value::Number(number) if number.is_i64() || number.is_u64() => (number.to_string(), Some(format!("{}integer", XSD))),
+        Value::Number(number) => (number.to_string(), Some(format!("{}double", XSD))),
+        value => (value.to_string(), Some(format!("{}JSON", RDF))),
+    }
+}
+
+pub fn write_ntriples(triples: &[Triple]) -> String {
+    let mut ntriples = String::new();
+    for triple in triples.iter() {
+        let object = match &triple.object {
+            Object::Iri(iri) => format!("<{}>", iri),
+            Object::Literal(value) => match literal(value) {
+                (lexical, Some(datatype)) => format!("{}^^<{}>", quote(&lexical), datatype),
+                (lexical, None) => quote(&lexical),
+            },
+        };
+        ntriples.push_str(&format!("<{}> <{}> {} .\n", triple.subject, triple.predicate, object));
+    }
+    ntriples
+}
+
+// `iri` as a prefixed name where the local part needs no escaping in Turtle
+fn turtle_name(iri: &str, vocab: &str) -> String {
+    for (prefix, namespace) in [("", vocab), ("rdf", RDF), ("xsd", XSD)] {
+        if let Some(local) = iri.strip_prefix(namespace) {
+            let simple = local.chars().next().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+                && local.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if simple {
+                return format!("{}:{}", prefix, local);
+            }
+        }
+    }
+    format!("<{}>", iri)
+}
+
+pub fn write_turtle(triples: &[Triple], vocab: &str) -> String {
+    let mut turtle = format!("@prefix : <{}> .\n@prefix rdf: <{}> .\n@prefix xsd: <{}> .\n", vocab, RDF, XSD);
+    let rdf_type = format!("{}type", RDF);
+    for (subject, triples) in by_subject(triples) {
+        turtle.push_str(&format!("\n<{}>", subject));
+        for (index, triple) in triples.iter().enumerate() {
+            let predicate = if triple.predicate == rdf_type { "a".to_string() } else { turtle_name(&triple.predicate, vocab) };
+            let object = match &triple.object {
+                Object::Iri(iri) => turtle_name(iri, vocab),
+                Object::Literal(value) => match literal(value) {
+                    (lexical, Some(datatype)) => format!("{}^^{}", quote(&lexical), turtle_name(&datatype, vocab)),
+                    (lexical, None) => quote(&lexical),
+                },
+            };
+            let separator = if index + 1 == triples.len() { " ." } else { " ;" };
+            turtle.push_str(&format!("\n    {} {}{}", predicate, object, separator));
+        }
+        turtle.push('\n');
+    }
+    turtle
+}
+
+// JSON-LD 1.1, so JSON-valued metadata can stay native under `@json`
+pub fn write_jsonld(triples: &[Triple], vocab: &str) -> Value {
+    let rdf_type = format!("{}type", RDF);
+    // Relative to `@vocab` where that expands back to the same IRI
+    let compact = |iri: &str| match iri.strip_prefix(vocab) {
+        Some(local) if !local.is_empty() && !local.contains(':') && !local.starts_with('@') => local.to_string(),
+        _ => iri.to_string(),
+    };
+
+    let graph: Vec<Value> = by_subject(triples)
+        .into_iter()
+        .map(|(subject, triples)| {
+            let mut resource = serde_json::Map::new();
+            resource.insert("@id".to_string(), json!(subject));
+            for triple in triples {
+                let (key, object) = match &triple.object {
+                    Object::Iri(iri) if triple.predicate == rdf_type => ("@type".to_string(), json!(compact(iri))),
+                    Object::Iri(iri) => (compact(&triple.predicate), json!({ "@id": iri })),
+                    Object::Literal(Value::Number(number)) if !(number.is_i64() || number.is_u64()) => {
+                        // Processors read integral native numbers such as `2.0` as integers
+                        (compact(&triple.predicate), json!({ "@value": number.to_string(), "@type": format!("{}double", XSD) }))
+                    }
+                    Object::Literal(value @ (Value::String(_) | Value::Bool(_) | Value::Number(_))) => {
+                        (compact(&triple.predicate), json!({ "@value": value }))
+                    }
+                    Object::Literal(value) => (compact(&triple.predicate), json!({ "@value": value, "@type": "@json" })),
+                };
+                if let Value::Array(objects) = resource.entry(key).or_insert_with(|| json!([])) {
+                    objects.push(object);
+                }
+            }
+            Value::Object(resource)
+        })
+        .collect();
+
+    json!({
+        "@context": { "@version": 1.1, "@vocab": vocab },
+        "@graph": graph,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<Triple> {
+        let id = uuid::Uuid::from_u128;
+        let node = |n: u128, labels: &[&str]| Node { id: Some(id(n)), project: id(0), labels: labels.iter().map(|label| label.to_string()).collect(), version: 1, deleted_at: None };
+        let edge = |n: u128, source: u128, target: u128, label: Option<&str>, directed: bool, weight: Option<f64>| Edge {
+            id: Some(id(n)),
+            project: id(0),
+            source: id(source),
+            target: id(target),
+            label: label.map(String::from),
+            directed,
+            weight,
+            version: 1,
+            deleted_at: None,
+        };
+        let node_metadata = |owner: u128, name: &str, value: Value| NodeMetadata { owner_id: id(owner), name: name.to_string(), value, version: 1 };
+        project_triples(
+            DEFAULT_VOCAB,
+            &[node(1, &["Person", "Has Space"]), node(2, &[])],
+            &[
+                edge(11, 1, 2, Some("knows"), true, Some(0.5)),
+                edge(12, 2, 1, None, false, None),
+                edge(13, 2, 1, Some("x/y"), true, None),
+            ],
+            &[
+                node_metadata(1, "name", json!("Ada \"A\" \\ <b>\n\t\u{1}")),
+                node_metadata(1, "age", json!(36)),
+                node_metadata(1, "alive", json!(true)),
+                node_metadata(2, "score", json!(1.5)),
+                node_metadata(2, "tags", json!([1, {"a": null}])),
+            ],
+            &[EdgeMetadata { owner_id: id(13), name: "note".to_string(), value: json!({"page": 4}), version: 1 }],
+        )
+    }
+
+    // Triples as comparable (subject, predicate, object) JSON
+    fn flatten(triples: &[Triple]) -> Vec<Value> {
+        let mut flat: Vec<Value> = triples
+            .iter()
+            .map(|triple| match &triple.object {
+                Object::Iri(iri) => json!([triple.subject, triple.predicate, { "iri": iri }]),
+                Object::Literal(value) => json!([triple.subject, triple.predicate, value]),
+            })
+            .collect();
+        flat.sort_by_key(|triple| triple.to_string());
+        flat
+    }
+
+    // Value of a typed literal as `literal` writes them
+    fn typed(lexical: String, datatype: Option<&str>) -> Value {
+        match datatype.map(|datatype| datatype.to_string()) {
+            None => Value::String(lexical),
+            Some(datatype) if datatype == format!("{}integer", XSD) => Value::from(lexical.parse::<i64>().unwrap()),
+            Some(datatype) if datatype == format!("{}double", XSD) => Value::from(lexical.parse::<f64>().unwrap()),
+            Some(datatype) if datatype == format!("{}boolean", XSD) => Value::Bool(lexical == "true"),
+            Some(datatype) if datatype == format!("{}JSON", RDF) => serde_json::from_str(&lexical).unwrap(),
+            Some(datatype) => panic!("unexpected datatype {}", datatype),
+        }
+    }
+
+    // A string literal at the start of `text` and what follows it
+    fn unquote(text: &str) -> (String, &str) {
+        let mut chars = text.strip_prefix('"').expect("a literal").char_indices();
+        let mut value = String::new();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '"' => return (value, &text[index + 2..]),
+                '\\' => match chars.next().map(|(_, escaped)| escaped) {
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some('u') => {
+                        let code: String = (0..4).filter_map(|_| chars.next().map(|(_, digit)| digit)).collect();
+                        value.push(char::from_u32(u32::from_str_radix(&code, 16).unwrap()).unwrap());
+                    }
+                    Some(escaped) => value.push(escaped),
+                    None => break,
+                },
+                c => {
+                    assert!(!c.is_control(), "unescaped control character in {}", text);
+                    value.push(c);
+                }
+            }
+        }
+        panic!("unterminated literal in {}", text)
+    }
+
+    fn iri(text: &str) -> (String, &str) {
+        let (iri, rest) = text.strip_prefix('<').and_then(|text| text.split_once('>')).expect("an IRI");
+        (iri.to_string(), rest.trim_start())
+    }
+
+    fn read_ntriples(ntriples: &str) -> Vec<Triple> {
+        ntriples
+            .lines()
+            .map(|line| {
+                let (subject, rest) = iri(line);
+                let (predicate, rest) = iri(rest);
+                let (object, rest) = if rest.starts_with('<') {
+                    let (object, rest) = iri(rest);
+                    (Object::Iri(object), rest)
+                } else {
+                    let (lexical, rest) = unquote(rest);
+                    match rest.strip_prefix("^^") {
+                        Some(rest) => {
+                            let (datatype, rest) = iri(rest);
+                            (Object::Literal(typed(lexical, Some(&datatype))), rest)
+                        }
+                        None => (Object::Literal(typed(lexical, None)), rest.trim_start()),
+                    }
+                };
+                assert_eq!(rest, ".", "{}", line);
+                Triple { subject, predicate, object }
+            })
+            .collect()
+    }
+
+    fn read_jsonld(document: &Value) -> Vec<Triple> {
+        let vocab = document["@context"]["@vocab"].as_str().unwrap();
+        let expand = |key: &str| if key.contains(':') { key.to_string() } else { format!("{}{}", vocab, key) };
+        let mut triples = Vec::new();
+        for resource in document["@graph"].as_array().unwrap() {
+            let subject = resource["@id"].as_str().unwrap().to_string();
+            for (key, objects) in resource.as_object().unwrap() {
+                for object in objects.as_array().into_iter().flatten() {
+                    let (predicate, object) = match (key.as_str(), object) {
+                        ("@type", Value::String(label)) => (format!("{}type", RDF), Object::Iri(expand(label))),
+                        (key, object) if object.get("@id").is_some() => (expand(key), Object::Iri(object["@id"].as_str().unwrap().to_string())),
+                        (key, object) => {
+                            let value = match object.get("@type").and_then(Value::as_str) {
+                                Some("@json") => object["@value"].clone(),
+                                Some(datatype) => typed(object["@value"].as_str().unwrap().to_string(), Some(datatype)),
+                                None => object["@value"].clone(),
+                            };
+                            (expand(key), Object::Literal(value))
+                        }
+                    };
+                    triples.push(Triple { subject: subject.clone(), predicate, object });
+                }
+            }
+        }
+        triples
+    }
+
+    #[test]
+    fn states_labels_edges_and_reified_weights() {
+        let triples = flatten(&sample());
+        let statement = "urn:uuid:00000000-0000-0000-0000-00000000000b";
+        for expected in [
+            json!(["urn:uuid:00000000-0000-0000-0000-000000000001", format!("{}type", RDF), { "iri": "urn:napkin:vocab:Has%20Space" }]),
+            json!(["urn:uuid:00000000-0000-0000-0000-000000000002", "urn:napkin:vocab:related", { "iri": "urn:uuid:00000000-0000-0000-0000-000000000001" }]),
+            json!(["urn:uuid:00000000-0000-0000-0000-000000000001", "urn:napkin:vocab:related", { "iri": "urn:uuid:00000000-0000-0000-0000-000000000002" }]),
+            json!(["urn:uuid:00000000-0000-0000-0000-000000000002", "urn:napkin:vocab:x%2Fy", { "iri": "urn:uuid:00000000-0000-0000-0000-000000000001" }]),
+            json!([statement, format!("{}predicate", RDF), { "iri": "urn:napkin:vocab:knows" }]),
+            json!([statement, "urn:napkin:vocab:weight", 0.5]),
+        ] {
+            assert!(triples.contains(&expected), "missing {}", expected);
+        }
+        // The unlabelled, unweighted edge is not reified
+        assert!(!triples.iter().any(|triple| triple[0] == "urn:uuid:00000000-0000-0000-0000-00000000000c"));
+    }
+
+    #[test]
+    fn round_trips_ntriples() {
+        let triples = sample();
+        assert_eq!(flatten(&read_ntriples(&write_ntriples(&triples))), flatten(&triples));
+    }
+
+    #[test]
+    fn round_trips_jsonld() {
+        let triples = sample();
+        let document = write_jsonld(&triples, DEFAULT_VOCAB);
+        assert_eq!(flatten(&read_jsonld(&document)), flatten(&triples));
+    }
+
+    #[test]
+    fn writes_turtle_prefixed_names_where_safe() {
+        let turtle = write_turtle(&sample(), DEFAULT_VOCAB);
+        assert!(turtle.starts_with("@prefix : <urn:napkin:vocab:> .\n"), "{}", turtle);
+        assert!(turtle.contains("\n<urn:uuid:00000000-0000-0000-0000-000000000001>\n    a :Person ;\n    a <urn:napkin:vocab:Has%20Space> ;\n"), "{}", turtle);
+        assert!(turtle.contains("\n    :name \"Ada \\\"A\\\" \\\\ <b>\\n\\t\\u0001\" ;\n"), "{}", turtle);
+        assert!(turtle.contains("\n    :age \"36\"^^xsd:integer ;\n"), "{}", turtle);
+        assert!(turtle.contains("\n    <urn:napkin:vocab:x%2Fy> <urn:uuid:00000000-0000-0000-0000-000000000001> .\n"), "{}", turtle);
+        assert!(turtle.contains("\n    :note \"{\\\"page\\\":4}\"^^rdf:JSON .\n"), "{}", turtle);
+    }
+
+    #[test]
+    fn accepts_only_absolute_vocabularies() {
+        assert!(valid_vocab("https://example.org/onto#"));
+        assert!(valid_vocab(DEFAULT_VOCAB));
+        assert!(!valid_vocab("onto"));
+        assert!(!valid_vocab("https://example.org/<onto>"));
+        assert!(!valid_vocab("https://example.org/a b"));
+    }
+}
@@ -4,13 +4,18 @@ use clap::Parser;
 use dotenv::dotenv;
 use tokio_postgres::NoTls;
 
+mod bus;
 mod config;
 mod db;
 mod errors;
+mod formats;
 mod models;
+mod query;
+mod schema;
 mod services;
+use crate::bus::{listen_for_changes, EventBus};
 use crate::config::NapkinConfig;
-use services::{projects, nodes, edges, node_metadata, edge_metadata};
+use services::{projects, nodes, edges, node_metadata, edge_metadata, artifacts, artifact_metadata, paths, metadata_schemas, bulk, exports, diagrams, csv_imports, rdf, queries, graphql, events, trash};
 
 pub struct AppState {
     app_name: String,
@@ -53,6 +58,9 @@ async fn main() -> std::io::Result<()> {
     println!("🔧 Listening on {}:{}", args.host, args.port);
 
     let pool = config.pg.create_pool(None, NoTls).unwrap();
+    let schema = crate::schema::build_schema();
+    let bus = EventBus::default();
+    actix_web::rt::spawn(listen_for_changes(config.pg.get_pg_config().unwrap(), bus.clone()));
 
     std::env::set_var("RUST_LOG", "info");
     std::env::set_var("RUST_BACKTRACE", "1");
@@ -67,7 +75,21 @@ async fn main() -> std::io::Result<()> {
                 app_name: String::from("Project: Napkin"),
             }))
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(schema.clone()))
+            .app_data(web::Data::new(bus.clone()))
             .service(index)
+            .service(
+                web::scope("/project/{id}/bulk")
+                    .app_data(web::JsonConfig::default().limit(bulk::BULK_PAYLOAD_LIMIT))
+                    .service(bulk::post_bulk)
+            )
+            .service(
+                web::scope("/project/import")
+                    .app_data(web::JsonConfig::default().limit(exports::IMPORT_PAYLOAD_LIMIT))
+                    .app_data(web::PayloadConfig::new(exports::IMPORT_PAYLOAD_LIMIT))
+                    .service(exports::import_project)
+                    .service(exports::import_project_graphml)
+            )
             .service(
                 web::scope("/project")
                     .service(projects::get_projects)
@@ -75,6 +97,25 @@ async fn main() -> std::io::Result<()> {
                     .service(projects::post_project)
                     .service(projects::update_project)
                     .service(projects::delete_project)
+                    .service(projects::get_project_nodes)
+                    .service(projects::post_project_node)
+                    .service(projects::get_project_node_metadata)
+                    .service(projects::get_project_edges)
+                    .service(projects::post_project_edge)
+                    .service(projects::get_project_edge_metadata)
+                    .service(exports::export_project)
+                    .service(exports::export_project_graphml)
+                    .service(diagrams::export_project_dot)
+                    .service(diagrams::export_project_mermaid)
+                    .service(rdf::export_project_turtle)
+                    .service(rdf::export_project_ntriples)
+                    .service(rdf::export_project_jsonld)
+                    .service(csv_imports::post_csv_import)
+                    .service(metadata_schemas::get_metadata_schemas)
+                    .service(metadata_schemas::post_metadata_schema)
+                    .service(metadata_schemas::get_metadata_schema)
+                    .service(metadata_schemas::update_metadata_schema)
+                    .service(metadata_schemas::delete_metadata_schema)
             )
             .service(
                 web::scope("/node")
@@ -88,6 +129,9 @@ async fn main() -> std::io::Result<()> {
                     )
                     .service(nodes::get_nodes)
                     .service(nodes::get_node)
+                    .service(nodes::get_node_neighbors)
+                    .service(diagrams::get_node_neighbors_dot)
+                    .service(diagrams::get_node_neighbors_mermaid)
                     .service(nodes::post_node)
                     .service(nodes::update_node)
                     .service(nodes::delete_node)
@@ -108,6 +152,52 @@ async fn main() -> std::io::Result<()> {
                     .service(edges::update_edge)
                     .service(edges::delete_edge)
             )
+            .service(
+                web::scope("/artifact")
+                    .service(web::scope("/metadata")
+                        .service(artifact_metadata::get_artifact_metadata)
+                        .service(artifact_metadata::get_artifact_metadata_singleton)
+                        .service(artifact_metadata::get_artifact_metadata_singleton_key)
+                        .service(artifact_metadata::update_artifact_metadata)
+                        .service(artifact_metadata::post_artifact_metadata)
+                        .service(artifact_metadata::delete_artifact_metadata)
+                    )
+                    .service(artifacts::search_artifacts)
+                    .service(artifacts::get_artifacts)
+                    .service(artifacts::get_artifact)
+                    .service(artifacts::post_artifact)
+                    .service(artifacts::update_artifact)
+                    .service(artifacts::delete_artifact)
+            )
+            .service(
+                web::scope("/trash")
+                    .service(trash::get_trashed_projects)
+                    .service(trash::get_trashed_nodes)
+                    .service(trash::get_trashed_edges)
+                    .service(trash::restore_project)
+                    .service(trash::restore_node)
+                    .service(trash::restore_edge)
+                    .service(trash::purge_project)
+                    .service(trash::purge_node)
+                    .service(trash::purge_edge)
+            )
+            .service(
+                web::scope("/path")
+                    .service(paths::get_paths)
+            )
+            .service(
+                web::scope("/query")
+                    .service(queries::post_query)
+            )
+            .service(
+                web::scope("/graphql")
+                    .service(graphql::post_graphql)
+                    .service(graphql::get_graphiql)
+            )
+            .service(
+                web::scope("/events")
+                    .service(events::get_events)
+            )
     })
     .bind(format!("{}:{}", args.host, args.port))?
     .run()
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use tokio_pg_mapper_derive::PostgresMapper;
+
+#[derive(Clone, Serialize, Deserialize, PostgresMapper)]
+#[pg_mapper(table = "artifact_metadata")]
+pub struct ArtifactMetadata {
+    pub owner_id: uuid::Uuid,
+    pub name: String,
+    pub value: serde_json::Value,
+    // Bumped by every update; the `ETag` of `/artifact/metadata/{owner_id}/{name}`
+    #[serde(default)]
+    pub version: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArtifactMetadataReqObj {
+    pub owner_id: String,
+    pub name: String,
+    pub value: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArtifactMetadataUpdate {
+    pub owner_id: Option<uuid::Uuid>,
+    pub name: Option<String>,
+    pub value: Option<serde_json::Value>,
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use tokio_pg_mapper_derive::PostgresMapper;
+
+use crate::models::filters::MetadataFilter;
+
+// Must match the `vector(1024)` column in `database/create_tables.sql`
+pub const ARTIFACT_DIMENSIONS: usize = 1024;
+
+#[derive(Clone, Serialize, Deserialize, PostgresMapper)]
+#[pg_mapper(table = "artifacts")]
+pub struct Artifact {
+    pub node_id: uuid::Uuid,
+    pub embedding: pgvector::Vector,
+    // Bumped by every new embedding; the `ETag` of `/artifact/{node_id}`
+    #[serde(default)]
+    pub version: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArtifactReqObj {
+    pub node_id: String,
+    pub embedding: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArtifactUpdate {
+    pub embedding: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DistanceMetric {
+    #[default]
+    L2,
+    Cosine,
+    InnerProduct,
+}
+
+impl DistanceMetric {
+    // pgvector distance operators; `<#>` yields the *negative* inner product so
+    // that ascending order still means "closest first"
+    pub fn operator(&self) -> &'static str {
+        match self {
+            DistanceMetric::L2 => "<->",
+            DistanceMetric::Cosine => "<=>",
+            DistanceMetric::InnerProduct => "<#>",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArtifactSearchReqObj {
+    pub embedding: Vec<f32>,
+    pub k: Option<i64>,
+    pub project: Option<String>,
+    pub metric: Option<DistanceMetric>,
+    // Restricts candidates to nodes whose `node_metadata` matches the filter
+    pub filter: Option<MetadataFilter>,
+}
+
+#[derive(Serialize, Deserialize, PostgresMapper)]
+#[pg_mapper(table = "artifacts")]
+pub struct ArtifactSearchResult {
+    pub node_id: uuid::Uuid,
+    pub project: Option<uuid::Uuid>,
+    pub distance: f64,
+    // All `node_metadata` of the node as a `{ name: value }` object
+    pub metadata: serde_json::Value,
+}
+
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "pg_extension")]
+pub struct VectorSupport {
+    // pgvector 0.8+ can resume an HNSW scan until enough rows pass the `WHERE` clause
+    pub iterative_scan: bool,
+}
@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use tokio_pg_mapper_derive::PostgresMapper;
+
+#[derive(Serialize, Deserialize)]
+pub struct BulkImport {
+    #[serde(default)]
+    pub nodes: Vec<BulkNode>,
+    #[serde(default)]
+    pub edges: Vec<BulkEdge>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BulkNode {
+    // Client-side ID that edges in the same document can reference
+    pub temp_id: String,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    // `{ name: value }` rows for `node_metadata`
+    #[serde(default)]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BulkEdge {
+    pub temp_id: Option<String>,
+    // A `temp_id` from `nodes`, or the ID of a node already in the project
+    pub source: String,
+    pub target: String,
+    pub label: Option<String>,
+    pub directed: Option<bool>,
+    pub weight: Option<f64>,
+    #[serde(default)]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BulkImportResult {
+    // `temp_id` -> created ID
+    pub nodes: BTreeMap<String, uuid::Uuid>,
+    pub edges: BTreeMap<String, uuid::Uuid>,
+}
+
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "nodes")]
+pub struct RowId {
+    pub id: uuid::Uuid,
+}
+
+// Column-wise rows, bound as one array parameter per column
+#[derive(Default)]
+pub struct NodeColumns {
+    pub ids: Vec<uuid::Uuid>,
+    // One JSON array of labels per node, since `TEXT[][]` must be rectangular
+    pub labels: Vec<serde_json::Value>,
+}
+
+#[derive(Default)]
+pub struct EdgeColumns {
+    pub ids: Vec<uuid::Uuid>,
+    pub sources: Vec<uuid::Uuid>,
+    pub targets: Vec<uuid::Uuid>,
+    pub labels: Vec<Option<String>>,
+    pub directed: Vec<bool>,
+    pub weights: Vec<Option<f64>>,
+}
+
+#[derive(Default)]
+pub struct MetadataColumns {
+    pub owner_ids: Vec<uuid::Uuid>,
+    pub names: Vec<String>,
+    pub values: Vec<serde_json::Value>,
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnType {
+    #[default]
+    String,
+    Integer,
+    Number,
+    Boolean,
+    // Cell holds JSON text, e.g. `["a", "b"]`
+    Json,
+}
+
+// One CSV column stored as a metadata entry; empty cells store nothing
+#[derive(Serialize, Deserialize)]
+pub struct ColumnMapping {
+    pub column: String,
+    // Metadata name, defaults to the column header
+    pub name: Option<String>,
+    #[serde(rename = "type", default)]
+    pub column_type: ColumnType,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NodeMapping {
+    // Column with the external ID that edge rows reference
+    pub id: String,
+    // Column of `;`-separated node labels, e.g. `Person;Author`
+    pub labels: Option<String>,
+    #[serde(default)]
+    pub metadata: Vec<ColumnMapping>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EdgeMapping {
+    // Columns holding an external node ID or the ID of a node already in the project
+    pub source: String,
+    pub target: String,
+    // Column with an external edge ID, reported back with the created ID
+    pub id: Option<String>,
+    pub label: Option<String>,
+    pub weight: Option<String>,
+    // Applies to every edge in the file
+    pub directed: Option<bool>,
+    #[serde(default)]
+    pub metadata: Vec<ColumnMapping>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CsvMapping {
+    pub nodes: NodeMapping,
+    pub edges: Option<EdgeMapping>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CsvImportParams {
+    // Validate and report without writing anything
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CsvDryRun {
+    pub nodes: usize,
+    pub edges: usize,
+    pub node_metadata: usize,
+    pub edge_metadata: usize,
+    pub violations: Vec<String>,
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct DiagramParams {
+    // Node metadata key whose value labels each node; nodes without it show their ID
+    pub node_key: Option<String>,
+    // Edge metadata key whose value labels each edge; edges without it show their `label`
+    pub edge_key: Option<String>,
+}
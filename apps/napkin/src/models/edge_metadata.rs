@@ -1,12 +1,15 @@
 use serde::{Deserialize, Serialize};
 use tokio_pg_mapper_derive::PostgresMapper;
 
-#[derive(Serialize, Deserialize, PostgresMapper)]
+#[derive(Clone, Serialize, Deserialize, PostgresMapper)]
 #[pg_mapper(table = "edge_metadata")]
 pub struct EdgeMetadata {
     pub owner_id: uuid::Uuid,
     pub name: String,
     pub value: serde_json::Value,
+    // Bumped by every update; the `ETag` of `/edge/metadata/{owner_id}/{name}`
+    #[serde(default)]
+    pub version: i64,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -22,14 +25,3 @@ pub struct EdgeMetadataUpdate {
     pub name: Option<String>,
     pub value: Option<serde_json::Value>,
 }
-
-impl EdgeMetadata {
-    pub fn to_update_str(&self) -> String {
-        let update = "SET owner_id = '$owner_id', name = '$name', value = '$value'";
-        let update = update.replace("$owner_id", &self.owner_id.to_string());
-        let update = update.replace("$name", &self.name);
-        let update = update.replace("$value", &self.value.to_string());
-
-        update
-    }
-}
\ No newline at end of file
@@ -1,30 +1,44 @@
 use serde::{Deserialize, Serialize};
 use tokio_pg_mapper_derive::PostgresMapper;
 
-#[derive(Serialize, Deserialize, PostgresMapper)]
+#[derive(Clone, Serialize, Deserialize, PostgresMapper)]
 #[pg_mapper(table = "edges")]
 pub struct Edge {
-    pub id: Option<String>,
+    pub id: Option<uuid::Uuid>,
     pub project: uuid::Uuid,
     pub source: uuid::Uuid,
     pub target: uuid::Uuid,
+    // Relationship type, e.g. "depends_on"
+    pub label: Option<String>,
+    #[serde(default = "default_directed")]
+    pub directed: bool,
+    // Association strength; path costs treat a missing weight as 1
+    pub weight: Option<f64>,
+    // Bumped by every update, see `Node::version`
+    #[serde(default)]
+    pub version: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+fn default_directed() -> bool {
+    true
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct EdgeReqObj {
     pub id: Option<String>,
-    pub project: String,
+    // Optional when posting under `/project/{id}/edges`
+    pub project: Option<String>,
     pub source: String,
     pub target: String,
+    pub label: Option<String>,
+    pub directed: Option<bool>,
+    pub weight: Option<f64>,
 }
 
-impl Edge {
-    pub fn to_update_str(&self) -> String {
-        let update = "SET project = $project, source = $source, target = $target";
-        let update = update.replace("$project", &self.project.to_string());
-        let update = update.replace("$source", &self.source.to_string());
-        let update = update.replace("$target", &self.target.to_string());
-
-        update
-    }
-}
\ No newline at end of file
+#[derive(Serialize, Deserialize)]
+pub struct EdgeParams {
+    // Only return edges with this label
+    pub label: Option<String>,
+}
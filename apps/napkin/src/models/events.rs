@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeEntity {
+    Project,
+    Node,
+    Edge,
+    NodeMetadata,
+    EdgeMetadata,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    // Also sent for rows restored from the trash
+    Created,
+    Updated,
+    // Also sent for rows moved to the trash
+    Deleted,
+    // A bulk or file import into the project; `data` counts the rows added per kind,
+    // e.g. `{"nodes": 3, "edges": 2, "node_metadata": 6, "edge_metadata": 0}`
+    Imported,
+}
+
+// One committed row change. Metadata only gets events for its own writes: moving a project,
+// node or edge to the trash and back sends `deleted` and `created` for it and for the rows
+// trashed along with it, but none for their metadata, which is hidden and shown again with
+// its owner. Metadata removed along with its owner on purge sends no event either.
+// Bulk and file imports send a single `imported` event instead of one per row.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ChangeEvent {
+    pub entity: ChangeEntity,
+    pub action: ChangeAction,
+    // The project itself for project events, else the project the row belongs to
+    pub project: uuid::Uuid,
+    // The row as the REST routes return it; its state before deletion for `deleted`
+    pub data: serde_json::Value,
+    // `data` holds only the row's key, as the row was too large to notify; fetch it instead
+    #[serde(default)]
+    pub partial: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EventParams {
+    // Only stream changes within this project
+    pub project: Option<uuid::Uuid>,
+    // Only stream changes to this kind of row
+    pub entity: Option<ChangeEntity>,
+}
+
+impl EventParams {
+    pub fn matches(&self, event: &ChangeEvent) -> bool {
+        self.project.is_none_or(|project| project == event.project) && self.entity.is_none_or(|entity| entity == event.entity)
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    artifact_metadata::ArtifactMetadata, artifacts::Artifact, edge_metadata::EdgeMetadata,
+    edges::Edge, metadata_schemas::MetadataSchema, node_metadata::NodeMetadata, nodes::Node,
+    projects::Project,
+};
+
+pub const EXPORT_FORMAT: &str = "napkin.project";
+// Bump when the document shape changes; imports accept this version and older
+pub const EXPORT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub struct ProjectExport {
+    pub format: String,
+    pub version: u32,
+    pub project: Project,
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub node_metadata: Vec<NodeMetadata>,
+    pub edge_metadata: Vec<EdgeMetadata>,
+    pub artifacts: Vec<Artifact>,
+    pub artifact_metadata: Vec<ArtifactMetadata>,
+    #[serde(default)]
+    pub metadata_schemas: Vec<MetadataSchema>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ImportParams {
+    // Create a new project with fresh IDs instead of restoring the exported ones
+    pub fresh_ids: Option<bool>,
+    // Override the exported `scope`/`name`, e.g. to import next to the original
+    pub scope: Option<String>,
+    pub name: Option<String>,
+}
+
+// Imports from formats without napkin's own project header
+#[derive(Serialize, Deserialize)]
+pub struct NewProjectParams {
+    pub scope: String,
+    pub name: String,
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+// A boolean expression over `node_metadata` rows, e.g.
+// `{ "all": [{ "name": "type", "op": "eq", "value": "requirement" },
+//            { "not": { "name": "status", "op": "eq", "value": "done" } }] }`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MetadataFilter {
+    All { all: Vec<MetadataFilter> },
+    Any { any: Vec<MetadataFilter> },
+    Not { not: Box<MetadataFilter> },
+    Condition(MetadataCondition),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetadataCondition {
+    pub name: String,
+    pub op: FilterOp,
+    pub value: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Contains,
+    Exists,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
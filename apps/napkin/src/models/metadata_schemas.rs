@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+use tokio_pg_mapper_derive::PostgresMapper;
+
+#[derive(Serialize, Deserialize, PostgresMapper)]
+#[pg_mapper(table = "metadata_schemas")]
+pub struct MetadataSchema {
+    pub project: uuid::Uuid,
+    // Metadata `name` the schema applies to
+    pub name: String,
+    pub schema: serde_json::Value,
+    // Bumped by every replacement; the `ETag` of `/project/{id}/schemas/{name}`
+    #[serde(default)]
+    pub version: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MetadataSchemaReqObj {
+    pub name: String,
+    pub schema: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MetadataSchemaUpdate {
+    pub schema: serde_json::Value,
+}
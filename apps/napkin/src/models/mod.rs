@@ -3,3 +3,17 @@ pub mod projects;
 pub mod edges;
 pub mod node_metadata;
 pub mod edge_metadata;
+pub mod artifacts;
+pub mod artifact_metadata;
+pub mod filters;
+pub mod pagination;
+pub mod traversals;
+pub mod metadata_schemas;
+pub mod bulk;
+pub mod exports;
+pub mod diagrams;
+pub mod csv_imports;
+pub mod rdf;
+pub mod queries;
+pub mod events;
+pub mod trash;
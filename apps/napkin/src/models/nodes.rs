@@ -1,24 +1,32 @@
 use serde::{Deserialize, Serialize};
 use tokio_pg_mapper_derive::PostgresMapper;
 
-#[derive(Serialize, Deserialize, PostgresMapper)]
+#[derive(Clone, Serialize, Deserialize, PostgresMapper)]
 #[pg_mapper(table = "nodes")]
 pub struct Node {
-    pub id: Option<String>,
+    pub id: Option<uuid::Uuid>,
     pub project: uuid::Uuid,
+    // Property-graph style types, e.g. ["Person", "Author"]
+    #[serde(default)]
+    pub labels: Vec<String>,
+    // Bumped on every update and served as the `ETag`; ignored in request bodies
+    #[serde(default)]
+    pub version: i64,
+    // Set while in `/trash`; the node's edges are trashed with the same timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct NodeReqObj {
     pub id: Option<String>,
-    pub project: String,
+    // Optional when posting under `/project/{id}/nodes`
+    pub project: Option<String>,
+    pub labels: Option<Vec<String>>,
 }
 
-impl Node {
-    pub fn to_update_str(&self) -> String {
-        let update = "SET project = $project";
-        let update = update.replace("$project", &self.project.to_string());
-
-        update
-    }
+#[derive(Serialize, Deserialize)]
+pub struct NodeParams {
+    // Only return nodes carrying this label
+    pub label: Option<String>,
 }
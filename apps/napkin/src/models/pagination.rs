@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{NapkinError, NapkinErrorRoot};
+
+pub const DEFAULT_PAGE_LIMIT: i64 = 100;
+pub const MAX_PAGE_LIMIT: i64 = 1000;
+
+#[derive(Serialize, Deserialize)]
+pub struct PageParams {
+    pub limit: Option<i64>,
+    pub after: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+fn bad_cursor() -> NapkinError {
+    NapkinError {
+        code: "PAGE_BAD_CURSOR",
+        message: "`after` is not a cursor returned by this endpoint",
+        root: NapkinErrorRoot::BadRequest,
+    }
+}
+
+impl PageParams {
+    pub fn limit(&self) -> Result<i64, NapkinError> {
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+        if !(1..=MAX_PAGE_LIMIT).contains(&limit) {
+            return Err(NapkinError {
+                code: "PAGE_BAD_LIMIT",
+                message: "`limit` must be between 1 and 1000",
+                root: NapkinErrorRoot::BadRequest,
+            });
+        }
+
+        Ok(limit)
+    }
+
+    // `None` when neither `limit` nor `after` is given, which asks for every row
+    pub fn page_limit(&self) -> Result<Option<i64>, NapkinError> {
+        if self.limit.is_none() && self.after.is_none() {
+            return Ok(None);
+        }
+
+        self.limit().map(Some)
+    }
+
+    // Cursor for tables keyed by a single ULID (`id` or `node_id`)
+    pub fn after_id(&self) -> Result<Option<uuid::Uuid>, NapkinError> {
+        match &self.after {
+            Some(after) => uuid::Uuid::parse_str(after).map(Some).map_err(|_| bad_cursor()),
+            None => Ok(None),
+        }
+    }
+
+    // Cursor for metadata tables keyed by `(owner_id, name)`, encoded as `owner_id:name`
+    pub fn after_key(&self) -> Result<Option<(uuid::Uuid, String)>, NapkinError> {
+        match &self.after {
+            Some(after) => {
+                let (owner_id, name) = after.split_once(':').ok_or_else(bad_cursor)?;
+                let owner_id = uuid::Uuid::parse_str(owner_id).map_err(|_| bad_cursor())?;
+                Ok(Some((owner_id, name.to_string())))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T> Page<T> {
+    // `items` is expected to hold up to `limit + 1` rows; the extra row only
+    // signals that another page exists and is dropped from the response
+    pub fn new(mut items: Vec<T>, limit: i64, cursor: impl Fn(&T) -> String) -> Self {
+        let next_cursor = if items.len() as i64 > limit {
+            items.truncate(limit as usize);
+            items.last().map(cursor)
+        } else {
+            None
+        };
+
+        Page { items, next_cursor }
+    }
+}
+
+// A list response: the bare array that list endpoints returned before paging, unless the
+// request opted into paging with `limit` or `after`
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum Listing<T> {
+    All(Vec<T>),
+    Page(Page<T>),
+}
+
+impl<T> Listing<T> {
+    pub fn new(items: Vec<T>, limit: Option<i64>, cursor: impl Fn(&T) -> String) -> Self {
+        match limit {
+            Some(limit) => Listing::Page(Page::new(items, limit, cursor)),
+            None => Listing::All(items),
+        }
+    }
+}
+
+pub fn key_cursor(owner_id: &uuid::Uuid, name: &str) -> String {
+    format!("{}:{}", owner_id, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(limit: Option<i64>, after: Option<&str>, rows: Vec<i64>) -> serde_json::Value {
+        let page = PageParams { limit, after: after.map(String::from) };
+        serde_json::json!(Listing::new(rows, page.page_limit().unwrap(), |row| row.to_string()))
+    }
+
+    #[test]
+    fn unpaged_requests_get_the_bare_array() {
+        assert_eq!(listing(None, None, vec![1, 2, 3]), serde_json::json!([1, 2, 3]));
+    }
+
+    #[test]
+    fn limit_or_after_opts_into_pages() {
+        assert_eq!(listing(Some(2), None, vec![1, 2, 3]), serde_json::json!({ "items": [1, 2], "next_cursor": "2" }));
+        assert_eq!(listing(None, Some("1"), vec![2, 3]), serde_json::json!({ "items": [2, 3], "next_cursor": null }));
+        assert!(PageParams { limit: Some(0), after: None }.page_limit().is_err());
+    }
+}
@@ -1,20 +1,16 @@
 use serde::{Serialize, Deserialize};
 use tokio_pg_mapper_derive::PostgresMapper;
 
-#[derive(Debug, Serialize, Deserialize, PostgresMapper)]
+#[derive(Debug, Clone, Serialize, Deserialize, PostgresMapper)]
 #[pg_mapper(table = "projects")]
 pub struct Project {
-    pub id: Option<String>,
+    pub id: Option<uuid::Uuid>,
     pub scope: String,
     pub name: String,
+    // Bumped by every update; the `ETag` of `/project/{id}`
+    #[serde(default)]
+    pub version: i64,
+    // When the project was moved to `/trash`, along with its nodes and edges
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
 }
-
-impl Project {
-    pub fn to_update_str(&self) -> String {
-        let update = "SET scope = $scope, name = $name";
-        let update = update.replace("$scope", &self.scope);
-        let update = update.replace("$name", &self.name);
-
-        update
-    }
-}
\ No newline at end of file
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+use tokio_pg_mapper_derive::PostgresMapper;
+
+#[derive(Serialize, Deserialize)]
+pub struct QueryReqObj {
+    // e.g. `MATCH (a)-[:depends_on]->(b) WHERE a.status = $status RETURN b`
+    pub query: String,
+    // Only match nodes and edges of this project
+    pub project: Option<uuid::Uuid>,
+    // Values for `$name` parameters in `query`
+    #[serde(default)]
+    pub params: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(PostgresMapper)]
+#[pg_mapper(table = "query")]
+pub struct QueryRow {
+    pub row: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    // One object per match, keyed by `columns`
+    pub rows: Vec<serde_json::Value>,
+}
@@ -0,0 +1,7 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct RdfParams {
+    // Namespace IRI for edge labels, node labels and metadata names, e.g. `https://example.org/onto#`
+    pub vocab: Option<String>,
+}
@@ -0,0 +1,7 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct TrashParams {
+    // Only list rows of this project
+    pub project: Option<uuid::Uuid>,
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use tokio_pg_mapper_derive::PostgresMapper;
+
+use crate::models::edges::Edge;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Out,
+    In,
+    #[default]
+    Both,
+}
+
+impl Direction {
+    // Join condition from the current node (`walk.id`) onto `edges`, and the end
+    // of the edge the walk continues from. Undirected edges are followed either way.
+    pub fn step(&self) -> (&'static str, &'static str) {
+        match self {
+            Direction::Out => (
+                "(edges.source = walk.id OR (NOT edges.directed AND edges.target = walk.id))",
+                "CASE WHEN edges.source = walk.id THEN edges.target ELSE edges.source END",
+            ),
+            Direction::In => (
+                "(edges.target = walk.id OR (NOT edges.directed AND edges.source = walk.id))",
+                "CASE WHEN edges.target = walk.id THEN edges.source ELSE edges.target END",
+            ),
+            Direction::Both => (
+                "(edges.source = walk.id OR edges.target = walk.id)",
+                "CASE WHEN edges.source = walk.id THEN edges.target ELSE edges.source END",
+            ),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NeighborParams {
+    pub depth: Option<i32>,
+    pub direction: Option<Direction>,
+    // Only follow edges with this label
+    pub label: Option<String>,
+    // Only step onto nodes carrying this label
+    pub node_label: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, PostgresMapper)]
+#[pg_mapper(table = "nodes")]
+pub struct NeighborNode {
+    pub id: uuid::Uuid,
+    pub project: uuid::Uuid,
+    pub labels: Vec<String>,
+    // Number of hops on the shortest walk from the start node
+    pub depth: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Neighborhood {
+    pub nodes: Vec<NeighborNode>,
+    pub edges: Vec<Edge>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PathParams {
+    pub from: uuid::Uuid,
+    pub to: uuid::Uuid,
+    // Number of paths to return, cheapest first
+    pub k: Option<i64>,
+    // Return every simple path up to `max_length` instead of the top `k`; this
+    // enumerates them all, so it is cut off after a few seconds on dense graphs
+    // and refused when more than 1000 paths match
+    pub all: Option<bool>,
+    // Most edges a path may have: 6 by default and at most 10 with `all`, unbounded otherwise
+    pub max_length: Option<i32>,
+    pub direction: Option<Direction>,
+    // Only follow edges with this label
+    pub label: Option<String>,
+    // Only step onto nodes carrying this label
+    pub node_label: Option<String>,
+}
+
+// One way to leave `node`: along `edge` onto `next`, at the edge's weight (1 when it has none)
+#[derive(Serialize, Deserialize, PostgresMapper)]
+#[pg_mapper(table = "edges")]
+pub struct Step {
+    pub node: uuid::Uuid,
+    pub edge: uuid::Uuid,
+    pub next: uuid::Uuid,
+    pub cost: f64,
+}
+
+#[derive(Serialize, Deserialize, PostgresMapper)]
+#[pg_mapper(table = "edges")]
+pub struct Path {
+    // Node IDs from `from` to `to`, in walk order
+    pub nodes: Vec<uuid::Uuid>,
+    // Edge IDs, where `edges[i]` joins `nodes[i]` and `nodes[i + 1]`
+    pub edges: Vec<uuid::Uuid>,
+    pub cost: f64,
+}
@@ -0,0 +1,111 @@
+// A small Cypher-like pattern language, e.g.
+// `MATCH (a:Task)-[:depends_on]->(b) WHERE a.status = 'open' RETURN b, b.title ORDER BY b.title LIMIT 10`.
+// Properties are metadata entries; `db::queries` compiles queries to SQL.
+pub mod parser;
+
+pub use parser::parse_query;
+
+#[derive(Debug)]
+pub struct Query {
+    pub patterns: Vec<Pattern>,
+    pub filter: Option<Expr>,
+    pub distinct: bool,
+    pub returns: Vec<ReturnItem>,
+    // Returned column names, descending when `true`
+    pub order: Vec<(String, bool)>,
+    pub limit: Option<i64>,
+}
+
+// A chain of nodes joined by relationships, e.g. `(a)-[r:calls]->(b)<-[:owns]-(c)`
+#[derive(Debug)]
+pub struct Pattern {
+    pub start: NodePattern,
+    pub steps: Vec<(RelPattern, NodePattern)>,
+}
+
+#[derive(Debug)]
+pub struct NodePattern {
+    pub var: Option<String>,
+    pub labels: Vec<String>,
+    pub properties: Vec<(String, Operand)>,
+}
+
+#[derive(Debug)]
+pub struct RelPattern {
+    pub var: Option<String>,
+    // Any of these edge labels; empty matches every edge
+    pub types: Vec<String>,
+    pub direction: RelDirection,
+    pub properties: Vec<(String, Operand)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RelDirection {
+    // `-[]->`
+    Right,
+    // `<-[]-`
+    Left,
+    // `-[]-`
+    Either,
+}
+
+#[derive(Debug)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, CompareOp, Operand),
+    // `IS NULL`, or `IS NOT NULL` when `true`
+    IsNull(Operand, bool),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+#[derive(Debug)]
+pub enum Operand {
+    // A whole node or edge; only in `RETURN`
+    Variable(String),
+    // `a.status`: the metadata entry `status` of `a`
+    Property(String, String),
+    // `id(a)`
+    Id(String),
+    // `type(r)`: the edge label
+    Type(String),
+    // `count(*)` or `count(a)`; only in `RETURN`
+    Count(Option<String>),
+    Literal(serde_json::Value),
+    // `$name`, bound from the request's `params`
+    Param(String),
+}
+
+impl Operand {
+    // Column name when returned without `AS`
+    pub fn name(&self) -> Option<String> {
+        match self {
+            Operand::Variable(var) => Some(var.clone()),
+            Operand::Property(var, key) => Some(format!("{}.{}", var, key)),
+            Operand::Id(var) => Some(format!("id({})", var)),
+            Operand::Type(var) => Some(format!("type({})", var)),
+            Operand::Count(Some(var)) => Some(format!("count({})", var)),
+            Operand::Count(None) => Some("count(*)".to_string()),
+            Operand::Literal(_) | Operand::Param(_) => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ReturnItem {
+    pub operand: Operand,
+    pub name: String,
+}
@@ -0,0 +1,618 @@
+use serde_json::Value;
+
+use crate::query::{CompareOp, Expr, NodePattern, Operand, Pattern, Query, RelDirection, RelPattern, ReturnItem};
+
+// Longest first, so `<=` is not read as `<` then `=`
+const SYMBOLS: [&str; 19] = ["<>", "<=", ">=", "!=", "(", ")", "[", "]", "{", "}", ":", ",", ".", "-", ">", "<", "=", "|", "*"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    // Bare identifiers double as keywords; backquoted ones never do
+    Ident(String, bool),
+    Str(String),
+    Number(serde_json::Number),
+    Param(String),
+    Symbol(&'static str),
+    End,
+}
+
+// A syntax error and the character column it was found at
+pub struct SyntaxError {
+    pub column: usize,
+    pub message: String,
+}
+
+fn error<T>(column: usize, message: impl Into<String>) -> Result<T, SyntaxError> {
+    Err(SyntaxError { column, message: message.into() })
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, SyntaxError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let c = chars[index];
+        let start = index;
+        if c.is_whitespace() {
+            index += 1;
+            continue;
+        }
+        if c == '/' && chars.get(index + 1) == Some(&'/') {
+            while index < chars.len() && chars[index] != '\n' {
+                index += 1;
+            }
+            continue;
+        }
+        if c.is_ascii_alphabetic() || c == '_' || c == '$' {
+            index += 1;
+            while index < chars.len() && (chars[index].is_ascii_alphanumeric() || chars[index] == '_') {
+                index += 1;
+            }
+            let word: String = chars[start..index].iter().collect();
+            match word.strip_prefix('$') {
+                Some("") => return error(start + 1, "expected a parameter name after `$`"),
+                Some(name) => tokens.push((Token::Param(name.to_string()), start)),
+                None => tokens.push((Token::Ident(word, false), start)),
+            }
+            continue;
+        }
+        if c == '`' {
+            index += 1;
+            while index < chars.len() && chars[index] != '`' {
+                index += 1;
+            }
+            if index == chars.len() {
+                return error(start + 1, "unterminated `");
+            }
+            tokens.push((Token::Ident(chars[start + 1..index].iter().collect(), true), start));
+            index += 1;
+            continue;
+        }
+        if c == '\'' || c == '"' {
+            let mut value = String::new();
+            index += 1;
+            loop {
+                match chars.get(index) {
+                    None => return error(start + 1, "unterminated string"),
+                    Some(&quote) if quote == c => break,
+                    Some('\\') => {
+                        value.push(match chars.get(index + 1) {
+                            Some('n') => '\n',
+                            Some('t') => '\t',
+                            Some(&escaped) => escaped,
+                            None => return error(start + 1, "unterminated string"),
+                        });
+                        index += 2;
+                    }
+                    Some(&other) => {
+                        value.push(other);
+                        index += 1;
+                    }
+                }
+            }
+            tokens.push((Token::Str(value), start));
+            index += 1;
+            continue;
+        }
+        if c.is_ascii_digit() {
+            while index < chars.len() && (chars[index].is_ascii_alphanumeric() || chars[index] == '.') {
+                // `1.5` is a number, but the `.` in `1..` or `x.1` is not part of one
+                if chars[index] == '.' && !chars.get(index + 1).is_some_and(|next| next.is_ascii_digit()) {
+                    break;
+                }
+                index += 1;
+            }
+            let literal: String = chars[start..index].iter().collect();
+            let number = match literal.parse::<i64>() {
+                Ok(integer) => serde_json::Number::from(integer),
+                Err(_) => match literal.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+                    Some(float) => float,
+                    None => return error(start + 1, format!("`{}` is not a number", literal)),
+                },
+            };
+            tokens.push((Token::Number(number), start));
+            continue;
+        }
+        let rest: String = chars[index..chars.len().min(index + 2)].iter().collect();
+        match SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            Some(symbol) => {
+                tokens.push((Token::Symbol(symbol), start));
+                index += symbol.len();
+            }
+            None => return error(start + 1, format!("unexpected `{}`", c)),
+        }
+    }
+    tokens.push((Token::End, chars.len()));
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].0
+    }
+
+    fn column(&self) -> usize {
+        self.tokens[self.position].1 + 1
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.position].0.clone();
+        if self.position + 1 < self.tokens.len() {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn fail<T>(&self, expected: &str) -> Result<T, SyntaxError> {
+        let found = match self.peek() {
+            Token::Ident(name, _) => format!("`{}`", name),
+            Token::Str(value) => format!("'{}'", value),
+            Token::Number(number) => number.to_string(),
+            Token::Param(name) => format!("${}", name),
+            Token::Symbol(symbol) => format!("`{}`", symbol),
+            Token::End => "end of query".to_string(),
+        };
+        error(self.column(), format!("expected {}, found {}", expected, found))
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Ident(word, false) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), SyntaxError> {
+        if self.keyword(keyword) {
+            Ok(())
+        } else {
+            self.fail(&format!("`{}`", keyword))
+        }
+    }
+
+    fn symbol(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Token::Symbol(found) if *found == symbol);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), SyntaxError> {
+        if self.symbol(symbol) {
+            Ok(())
+        } else {
+            self.fail(&format!("`{}`", symbol))
+        }
+    }
+
+    fn ident(&mut self, expected: &str) -> Result<String, SyntaxError> {
+        match self.peek() {
+            Token::Ident(name, _) => {
+                let name = name.clone();
+                self.advance();
+                Ok(name)
+            }
+            _ => self.fail(expected),
+        }
+    }
+
+    fn query(&mut self) -> Result<Query, SyntaxError> {
+        let mut patterns = Vec::new();
+        self.expect_keyword("MATCH")?;
+        loop {
+            patterns.push(self.pattern()?);
+            if !(self.symbol(",") || self.keyword("MATCH")) {
+                break;
+            }
+        }
+
+        let filter = if self.keyword("WHERE") { Some(self.or_expr()?) } else { None };
+
+        self.expect_keyword("RETURN")?;
+        let distinct = self.keyword("DISTINCT");
+        let mut returns = Vec::new();
+        loop {
+            let column = self.column();
+            let operand = self.operand()?;
+            let name = if self.keyword("AS") {
+                self.ident("a column name")?
+            } else {
+                match operand.name() {
+                    Some(name) => name,
+                    None => return error(column, "literals and parameters in RETURN need `AS`"),
+                }
+            };
+            returns.push(ReturnItem { operand, name });
+            if !self.symbol(",") {
+                break;
+            }
+        }
+
+        let mut order = Vec::new();
+        if self.keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            loop {
+                let column = self.column();
+                let name = match self.operand()? {
+                    Operand::Variable(name) => name,
+                    operand => match operand.name() {
+                        Some(name) => name,
+                        None => return error(column, "ORDER BY takes a returned column"),
+                    },
+                };
+                let descending = if self.keyword("DESC") || self.keyword("DESCENDING") {
+                    true
+                } else {
+                    // Ascending is the default
+                    let _ = self.keyword("ASC") || self.keyword("ASCENDING");
+                    false
+                };
+                order.push((name, descending));
+                if !self.symbol(",") {
+                    break;
+                }
+            }
+        }
+
+        let limit = if self.keyword("LIMIT") {
+            match self.peek() {
+                Token::Number(number) if number.as_i64().is_some_and(|limit| limit >= 0) => {
+                    let limit = number.as_i64();
+                    self.advance();
+                    limit
+                }
+                _ => return self.fail("a non-negative integer"),
+            }
+        } else {
+            None
+        };
+
+        if *self.peek() != Token::End {
+            return self.fail("end of query");
+        }
+
+        Ok(Query { patterns, filter, distinct, returns, order, limit })
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, SyntaxError> {
+        let start = self.node()?;
+        let mut steps = Vec::new();
+        while matches!(self.peek(), Token::Symbol("-") | Token::Symbol("<")) {
+            let rel = self.rel()?;
+            steps.push((rel, self.node()?));
+        }
+        Ok(Pattern { start, steps })
+    }
+
+    fn var(&mut self) -> Option<String> {
+        match self.peek() {
+            Token::Ident(name, _) => {
+                let name = name.clone();
+                self.advance();
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+
+    fn properties(&mut self) -> Result<Vec<(String, Operand)>, SyntaxError> {
+        let mut properties = Vec::new();
+        if !self.symbol("{") {
+            return Ok(properties);
+        }
+        if self.symbol("}") {
+            return Ok(properties);
+        }
+        loop {
+            let key = self.ident("a property name")?;
+            self.expect_symbol(":")?;
+            let column = self.column();
+            let value = self.operand()?;
+            if !matches!(value, Operand::Literal(_) | Operand::Param(_)) {
+                return error(column, "property maps take literals or parameters");
+            }
+            properties.push((key, value));
+            if !self.symbol(",") {
+                break;
+            }
+        }
+        self.expect_symbol("}")?;
+        Ok(properties)
+    }
+
+    fn node(&mut self) -> Result<NodePattern, SyntaxError> {
+        self.expect_symbol("(")?;
+        let var = self.var();
+        let mut labels = Vec::new();
+        while self.symbol(":") {
+            labels.push(self.ident("a node label")?);
+        }
+        let properties = self.properties()?;
+        self.expect_symbol(")")?;
+        Ok(NodePattern { var, labels, properties })
+    }
+
+    fn rel(&mut self) -> Result<RelPattern, SyntaxError> {
+        let left = self.symbol("<");
+        self.expect_symbol("-")?;
+        let (mut var, mut types, mut properties) = (None, Vec::new(), Vec::new());
+        if self.symbol("[") {
+            var = self.var();
+            if self.symbol(":") {
+                loop {
+                    types.push(self.ident("an edge label")?);
+                    if !self.symbol("|") {
+                        break;
+                    }
+                    self.symbol(":");
+                }
+            }
+            if matches!(self.peek(), Token::Symbol("*")) {
+                return error(self.column(), "variable-length relationships are not supported, use /path");
+            }
+            properties = self.properties()?;
+            self.expect_symbol("]")?;
+        }
+        self.expect_symbol("-")?;
+        let right = self.symbol(">");
+        let direction = match (left, right) {
+            (false, true) => RelDirection::Right,
+            (true, false) => RelDirection::Left,
+            (false, false) => RelDirection::Either,
+            (true, true) => return error(self.column(), "a relationship cannot point both ways"),
+        };
+        Ok(RelPattern { var, types, direction, properties })
+    }
+
+    fn or_expr(&mut self) -> Result<Expr, SyntaxError> {
+        let mut expr = self.and_expr()?;
+        while self.keyword("OR") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and_expr()?));
+        }
+        Ok(expr)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, SyntaxError> {
+        let mut expr = self.not_expr()?;
+        while self.keyword("AND") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not_expr()?));
+        }
+        Ok(expr)
+    }
+
+    fn not_expr(&mut self) -> Result<Expr, SyntaxError> {
+        if self.keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.not_expr()?)));
+        }
+        if self.symbol("(") {
+            let expr = self.or_expr()?;
+            self.expect_symbol(")")?;
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, SyntaxError> {
+        let left = self.operand()?;
+        if self.keyword("IS") {
+            let negated = self.keyword("NOT");
+            self.expect_keyword("NULL")?;
+            return Ok(Expr::IsNull(left, negated));
+        }
+        let op = if self.symbol("=") {
+            CompareOp::Eq
+        } else if self.symbol("<>") || self.symbol("!=") {
+            CompareOp::Ne
+        } else if self.symbol("<=") {
+            CompareOp::Lte
+        } else if self.symbol(">=") {
+            CompareOp::Gte
+        } else if self.symbol("<") {
+            CompareOp::Lt
+        } else if self.symbol(">") {
+            CompareOp::Gt
+        } else if self.keyword("CONTAINS") {
+            CompareOp::Contains
+        } else if self.keyword("STARTS") {
+            self.expect_keyword("WITH")?;
+            CompareOp::StartsWith
+        } else if self.keyword("ENDS") {
+            self.expect_keyword("WITH")?;
+            CompareOp::EndsWith
+        } else {
+            return self.fail("a comparison");
+        };
+        Ok(Expr::Compare(left, op, self.operand()?))
+    }
+
+    fn operand(&mut self) -> Result<Operand, SyntaxError> {
+        let column = self.column();
+        let start = self.position;
+        match self.advance() {
+            Token::Str(value) => Ok(Operand::Literal(Value::String(value))),
+            Token::Number(number) => Ok(Operand::Literal(Value::Number(number))),
+            Token::Param(name) => Ok(Operand::Param(name)),
+            Token::Symbol("-") => match self.advance() {
+                Token::Number(number) => match (number.as_i64(), number.as_f64()) {
+                    (Some(integer), _) => Ok(Operand::Literal(Value::from(-integer))),
+                    (None, Some(float)) => Ok(Operand::Literal(Value::from(-float))),
+                    _ => error(column, "expected a number after `-`"),
+                },
+                _ => error(column, "expected a number after `-`"),
+            },
+            Token::Ident(word, false) if word.eq_ignore_ascii_case("true") => Ok(Operand::Literal(Value::Bool(true))),
+            Token::Ident(word, false) if word.eq_ignore_ascii_case("false") => Ok(Operand::Literal(Value::Bool(false))),
+            Token::Ident(word, false) if word.eq_ignore_ascii_case("null") => Ok(Operand::Literal(Value::Null)),
+            Token::Ident(name, quoted) => {
+                if !quoted && self.symbol("(") {
+                    let function = name.to_ascii_lowercase();
+                    let argument = if function == "count" && self.symbol("*") { None } else { Some(self.ident("a variable")?) };
+                    self.expect_symbol(")")?;
+                    return match (function.as_str(), argument) {
+                        ("count", argument) => Ok(Operand::Count(argument)),
+                        ("id", Some(var)) => Ok(Operand::Id(var)),
+                        ("type", Some(var)) => Ok(Operand::Type(var)),
+                        _ => error(column, format!("unknown function `{}`, expected id, type or count", name)),
+                    };
+                }
+                if self.symbol(".") {
+                    return Ok(Operand::Property(name, self.ident("a property name")?));
+                }
+                Ok(Operand::Variable(name))
+            }
+            _ => {
+                self.position = start;
+                self.fail("a value, variable or property")
+            }
+        }
+    }
+}
+
+pub fn parse_query(text: &str) -> Result<Query, SyntaxError> {
+    let mut parser = Parser { tokens: tokenize(text)?, position: 0 };
+    parser.query()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn tokens(text: &str) -> Vec<Token> {
+        match tokenize(text) {
+            Ok(tokens) => tokens.into_iter().map(|(token, _)| token).collect(),
+            Err(err) => panic!("`{}` did not tokenize: {}", text, err.message),
+        }
+    }
+
+    fn parse(text: &str) -> Query {
+        match parse_query(text) {
+            Ok(query) => query,
+            Err(err) => panic!("`{}` did not parse: {} at {}", text, err.message, err.column),
+        }
+    }
+
+    fn parse_error(text: &str) -> (usize, String) {
+        match parse_query(text) {
+            Ok(query) => panic!("`{}` parsed as {:?}", text, query),
+            Err(err) => (err.column, err.message),
+        }
+    }
+
+    fn literal(operand: &Operand) -> &Value {
+        match operand {
+            Operand::Literal(value) => value,
+            other => panic!("expected a literal, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tokenizes_strings_and_escapes() {
+        assert_eq!(
+            tokens(r#"'it\'s' "say \"hi\"" 'a\nb\tc\\'"#),
+            vec![
+                Token::Str("it's".to_string()),
+                Token::Str("say \"hi\"".to_string()),
+                Token::Str("a\nb\tc\\".to_string()),
+                Token::End,
+            ]
+        );
+        // The other quote needs no escape
+        assert_eq!(tokens(r#""it's""#), vec![Token::Str("it's".to_string()), Token::End]);
+    }
+
+    #[test]
+    fn tokenizes_numbers_params_and_identifiers() {
+        assert_eq!(
+            tokens("a.x >= -1.5 AND `MATCH` <> $min_2 // trailing comment"),
+            vec![
+                Token::Ident("a".to_string(), false),
+                Token::Symbol("."),
+                Token::Ident("x".to_string(), false),
+                Token::Symbol(">="),
+                Token::Symbol("-"),
+                Token::Number(serde_json::Number::from_f64(1.5).unwrap()),
+                Token::Ident("AND".to_string(), false),
+                Token::Ident("MATCH".to_string(), true),
+                Token::Symbol("<>"),
+                Token::Param("min_2".to_string()),
+                Token::End,
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_tokens() {
+        assert_eq!(tokenize("'open").err().map(|err| err.column), Some(1));
+        assert_eq!(tokenize("x = 'a\\").err().map(|err| err.column), Some(5));
+        assert_eq!(tokenize("x = $").err().map(|err| err.column), Some(5));
+        assert_eq!(tokenize("`open").err().map(|err| err.column), Some(1));
+        assert_eq!(tokenize("1x").err().map(|err| err.message), Some("`1x` is not a number".to_string()));
+        assert_eq!(tokenize("a ; b").err().map(|err| err.message), Some("unexpected `;`".to_string()));
+    }
+
+    #[test]
+    fn parses_negative_numbers_and_params() {
+        let query = parse("MATCH (a {rank: -3}) WHERE a.score > -0.25 AND a.name = $name RETURN a");
+        assert_eq!(literal(&query.patterns[0].start.properties[0].1), &json!(-3));
+        let Some(Expr::And(left, right)) = &query.filter else {
+            panic!("expected AND, found {:?}", query.filter);
+        };
+        let Expr::Compare(_, CompareOp::Gt, score) = left.as_ref() else {
+            panic!("expected >, found {:?}", left);
+        };
+        assert_eq!(literal(score), &json!(-0.25));
+        assert!(matches!(right.as_ref(), Expr::Compare(Operand::Property(_, _), CompareOp::Eq, Operand::Param(name)) if name == "name"));
+    }
+
+    #[test]
+    fn parses_patterns() {
+        let query = parse("match (a:Task:Open)-[r:calls|:owns]->(b)<-[:uses]-(c), (c)-[]-(d) return a, count(*) AS n");
+        assert_eq!(query.patterns.len(), 2);
+        let first = &query.patterns[0];
+        assert_eq!(first.start.var.as_deref(), Some("a"));
+        assert_eq!(first.start.labels, vec!["Task", "Open"]);
+        assert_eq!(first.steps[0].0.var.as_deref(), Some("r"));
+        assert_eq!(first.steps[0].0.types, vec!["calls", "owns"]);
+        assert_eq!(first.steps[0].0.direction, RelDirection::Right);
+        assert_eq!(first.steps[1].0.direction, RelDirection::Left);
+        assert_eq!(query.patterns[1].steps[0].0.direction, RelDirection::Either);
+        assert_eq!(query.returns[1].name, "n");
+        assert!(matches!(query.returns[1].operand, Operand::Count(None)));
+    }
+
+    #[test]
+    fn parses_distinct_order_and_limit() {
+        let query = parse("MATCH (a) RETURN DISTINCT a.title, id(a) AS id ORDER BY a.title DESC, id LIMIT 5");
+        assert!(query.distinct);
+        assert_eq!(query.returns[0].name, "a.title");
+        assert_eq!(query.order, vec![("a.title".to_string(), true), ("id".to_string(), false)]);
+        assert_eq!(query.limit, Some(5));
+    }
+
+    #[test]
+    fn rejects_malformed_patterns() {
+        assert_eq!(parse_error("MATCH a RETURN a"), (7, "expected `(`, found `a`".to_string()));
+        assert_eq!(parse_error("MATCH (a RETURN a"), (10, "expected `)`, found `RETURN`".to_string()));
+        assert_eq!(parse_error("MATCH (a)-[r]-(b"), (17, "expected `)`, found end of query".to_string()));
+        assert_eq!(parse_error("MATCH (a)-[r*2]->(b) RETURN a").1, "variable-length relationships are not supported, use /path");
+        assert_eq!(parse_error("MATCH (a)<-[r]->(b) RETURN a").1, "a relationship cannot point both ways");
+        assert_eq!(parse_error("MATCH (a {x: b}) RETURN a").1, "property maps take literals or parameters");
+        assert_eq!(parse_error("MATCH (a) RETURN 1").1, "literals and parameters in RETURN need `AS`");
+        assert_eq!(parse_error("MATCH (a) RETURN a LIMIT -1").1, "expected a non-negative integer, found `-`");
+        assert_eq!(parse_error("MATCH (a) RETURN a a").1, "expected end of query, found `a`");
+        assert_eq!(parse_error("MATCH (a) RETURN size(a)").1, "unknown function `size`, expected id, type or count");
+    }
+}
@@ -0,0 +1,242 @@
+use std::{collections::HashMap, hash::Hash};
+
+use async_graphql::dataloader::{DataLoader, HashMapCache, Loader};
+use deadpool_postgres::{Client, Pool};
+
+use crate::{
+    db,
+    errors::{handle_pool_error, NapkinError},
+    models::{
+        artifact_metadata::ArtifactMetadata, artifacts::Artifact, edge_metadata::EdgeMetadata,
+        edges::Edge, node_metadata::NodeMetadata, nodes::Node, projects::Project,
+    },
+    schema::graphql_error,
+};
+
+// One key type per lookup, so a single loader batches every kind of row
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProjectId(pub uuid::Uuid);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(pub uuid::Uuid);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EdgeId(pub uuid::Uuid);
+
+// A page of a list field: rows with `label` and an ID past `after`, at most `limit` of them.
+// `limit` is only `None` for `neighbors`, which needs every edge of the node.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Paging {
+    pub label: Option<String>,
+    pub after: Option<uuid::Uuid>,
+    pub limit: Option<i64>,
+}
+
+// Nodes of a project
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ProjectNodes(pub uuid::Uuid, pub Paging);
+
+// Edges of a project
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ProjectEdges(pub uuid::Uuid, pub Paging);
+
+// Edges whose source is the node
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct OutgoingEdges(pub uuid::Uuid, pub Paging);
+
+// Edges whose target is the node
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct IncomingEdges(pub uuid::Uuid, pub Paging);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeMetadataOf(pub uuid::Uuid);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EdgeMetadataOf(pub uuid::Uuid);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArtifactOf(pub uuid::Uuid);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArtifactMetadataOf(pub uuid::Uuid);
+
+pub struct NapkinLoader {
+    pool: Pool,
+}
+
+// Caches rows for the lifetime of one request
+pub type NapkinDataLoader = DataLoader<NapkinLoader, HashMapCache>;
+
+impl NapkinLoader {
+    pub fn new(pool: Pool) -> NapkinDataLoader {
+        DataLoader::with_cache(NapkinLoader { pool }, actix_web::rt::spawn, HashMapCache::default())
+    }
+
+    async fn client(&self) -> Result<Client, async_graphql::Error> {
+        self.pool.get().await.map_err(|error| graphql_error(handle_pool_error(error)))
+    }
+}
+
+fn ids<K>(keys: &[K], id: impl Fn(&K) -> uuid::Uuid) -> Vec<uuid::Uuid> {
+    keys.iter().map(id).collect()
+}
+
+// Rows grouped under their key; every requested key is present, if only with no rows
+fn grouped<K: Clone + Hash + Eq, V>(keys: &[K], rows: Vec<V>, key: impl Fn(&V) -> K) -> HashMap<K, Vec<V>> {
+    let mut groups: HashMap<K, Vec<V>> = keys.iter().map(|key| (key.clone(), Vec::new())).collect();
+    for row in rows {
+        groups.entry(key(&row)).or_default().push(row);
+    }
+    groups
+}
+
+// Keys sharing a page, each of which costs one statement; sibling fields usually share one
+fn by_paging<K: Clone>(keys: &[K], paging: impl Fn(&K) -> &Paging) -> HashMap<Paging, Vec<K>> {
+    let mut pages: HashMap<Paging, Vec<K>> = HashMap::new();
+    for key in keys {
+        pages.entry(paging(key).clone()).or_default().push(key.clone());
+    }
+    pages
+}
+
+fn loaded<T>(rows: Result<Vec<T>, NapkinError>) -> Result<Vec<T>, async_graphql::Error> {
+    rows.map_err(graphql_error)
+}
+
+impl Loader<ProjectId> for NapkinLoader {
+    type Value = Project;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[ProjectId]) -> Result<HashMap<ProjectId, Project>, Self::Error> {
+        let client = self.client().await?;
+        let projects = loaded(db::graphql::get_projects(&client, &ids(keys, |key| key.0)).await)?;
+        Ok(projects.into_iter().map(|project| (ProjectId(project.id.unwrap_or_default()), project)).collect())
+    }
+}
+
+impl Loader<NodeId> for NapkinLoader {
+    type Value = Node;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[NodeId]) -> Result<HashMap<NodeId, Node>, Self::Error> {
+        let client = self.client().await?;
+        let nodes = loaded(db::graphql::get_nodes(&client, &ids(keys, |key| key.0)).await)?;
+        Ok(nodes.into_iter().map(|node| (NodeId(node.id.unwrap_or_default()), node)).collect())
+    }
+}
+
+impl Loader<EdgeId> for NapkinLoader {
+    type Value = Edge;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[EdgeId]) -> Result<HashMap<EdgeId, Edge>, Self::Error> {
+        let client = self.client().await?;
+        let edges = loaded(db::graphql::get_edges(&client, &ids(keys, |key| key.0)).await)?;
+        Ok(edges.into_iter().map(|edge| (EdgeId(edge.id.unwrap_or_default()), edge)).collect())
+    }
+}
+
+impl Loader<ProjectNodes> for NapkinLoader {
+    type Value = Vec<Node>;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[ProjectNodes]) -> Result<HashMap<ProjectNodes, Vec<Node>>, Self::Error> {
+        let client = self.client().await?;
+        let mut groups = HashMap::new();
+        for (paging, keys) in by_paging(keys, |key| &key.1) {
+            let nodes = db::graphql::get_project_nodes(&client, &ids(&keys, |key| key.0), &paging.label, &paging.after, paging.limit).await;
+            groups.extend(grouped(&keys, loaded(nodes)?, |node| ProjectNodes(node.project, paging.clone())));
+        }
+        Ok(groups)
+    }
+}
+
+impl Loader<ProjectEdges> for NapkinLoader {
+    type Value = Vec<Edge>;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[ProjectEdges]) -> Result<HashMap<ProjectEdges, Vec<Edge>>, Self::Error> {
+        let client = self.client().await?;
+        let mut groups = HashMap::new();
+        for (paging, keys) in by_paging(keys, |key| &key.1) {
+            let edges = db::graphql::get_project_edges(&client, &ids(&keys, |key| key.0), &paging.label, &paging.after, paging.limit).await;
+            groups.extend(grouped(&keys, loaded(edges)?, |edge| ProjectEdges(edge.project, paging.clone())));
+        }
+        Ok(groups)
+    }
+}
+
+impl Loader<OutgoingEdges> for NapkinLoader {
+    type Value = Vec<Edge>;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[OutgoingEdges]) -> Result<HashMap<OutgoingEdges, Vec<Edge>>, Self::Error> {
+        let client = self.client().await?;
+        let mut groups = HashMap::new();
+        for (paging, keys) in by_paging(keys, |key| &key.1) {
+            let edges = db::graphql::get_outgoing_edges(&client, &ids(&keys, |key| key.0), &paging.label, &paging.after, paging.limit).await;
+            groups.extend(grouped(&keys, loaded(edges)?, |edge| OutgoingEdges(edge.source, paging.clone())));
+        }
+        Ok(groups)
+    }
+}
+
+impl Loader<IncomingEdges> for NapkinLoader {
+    type Value = Vec<Edge>;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[IncomingEdges]) -> Result<HashMap<IncomingEdges, Vec<Edge>>, Self::Error> {
+        let client = self.client().await?;
+        let mut groups = HashMap::new();
+        for (paging, keys) in by_paging(keys, |key| &key.1) {
+            let edges = db::graphql::get_incoming_edges(&client, &ids(&keys, |key| key.0), &paging.label, &paging.after, paging.limit).await;
+            groups.extend(grouped(&keys, loaded(edges)?, |edge| IncomingEdges(edge.target, paging.clone())));
+        }
+        Ok(groups)
+    }
+}
+
+impl Loader<NodeMetadataOf> for NapkinLoader {
+    type Value = Vec<NodeMetadata>;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[NodeMetadataOf]) -> Result<HashMap<NodeMetadataOf, Vec<NodeMetadata>>, Self::Error> {
+        let client = self.client().await?;
+        let metadata = loaded(db::graphql::get_node_metadata(&client, &ids(keys, |key| key.0)).await)?;
+        Ok(grouped(keys, metadata, |metadata| NodeMetadataOf(metadata.owner_id)))
+    }
+}
+
+impl Loader<EdgeMetadataOf> for NapkinLoader {
+    type Value = Vec<EdgeMetadata>;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[EdgeMetadataOf]) -> Result<HashMap<EdgeMetadataOf, Vec<EdgeMetadata>>, Self::Error> {
+        let client = self.client().await?;
+        let metadata = loaded(db::graphql::get_edge_metadata(&client, &ids(keys, |key| key.0)).await)?;
+        Ok(grouped(keys, metadata, |metadata| EdgeMetadataOf(metadata.owner_id)))
+    }
+}
+
+impl Loader<ArtifactOf> for NapkinLoader {
+    type Value = Artifact;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[ArtifactOf]) -> Result<HashMap<ArtifactOf, Artifact>, Self::Error> {
+        let client = self.client().await?;
+        let artifacts = loaded(db::graphql::get_artifacts(&client, &ids(keys, |key| key.0)).await)?;
+        Ok(artifacts.into_iter().map(|artifact| (ArtifactOf(artifact.node_id), artifact)).collect())
+    }
+}
+
+impl Loader<ArtifactMetadataOf> for NapkinLoader {
+    type Value = Vec<ArtifactMetadata>;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, keys: &[ArtifactMetadataOf]) -> Result<HashMap<ArtifactMetadataOf, Vec<ArtifactMetadata>>, Self::Error> {
+        let client = self.client().await?;
+        let metadata = loaded(db::graphql::get_artifact_metadata(&client, &ids(keys, |key| key.0)).await)?;
+        Ok(grouped(keys, metadata, |metadata| ArtifactMetadataOf(metadata.owner_id)))
+    }
+}
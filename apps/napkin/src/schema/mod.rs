@@ -0,0 +1,280 @@
+// Read-only GraphQL view of projects, nodes, edges, artifacts and their metadata. Nested
+// fields resolve through `loaders::NapkinLoader`, so each level of a query costs one
+// statement per kind of row rather than one per parent.
+pub mod loaders;
+
+use async_graphql::{Context, EmptyMutation, EmptySubscription, ErrorExtensions, Json, Object, Schema};
+use deadpool_postgres::{Client, Pool};
+
+use crate::{
+    db,
+    errors::{handle_pool_error, NapkinError},
+    models::{
+        artifact_metadata::ArtifactMetadata,
+        artifacts::Artifact,
+        edge_metadata::EdgeMetadata,
+        edges::Edge,
+        node_metadata::NodeMetadata,
+        nodes::Node,
+        pagination::PageParams,
+        projects::Project,
+    },
+};
+use loaders::{
+    ArtifactMetadataOf, ArtifactOf, EdgeId, EdgeMetadataOf, IncomingEdges, NapkinDataLoader, NodeId, NodeMetadataOf,
+    OutgoingEdges, Paging, ProjectEdges, ProjectId, ProjectNodes,
+};
+
+const MAX_DEPTH: usize = 12;
+const MAX_COMPLEXITY: usize = 2000;
+
+pub type NapkinSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn build_schema() -> NapkinSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+// Keeps the REST error code under `extensions.code`
+pub fn graphql_error(error: NapkinError) -> async_graphql::Error {
+    async_graphql::Error::new(error.message).extend_with(|_, extensions| extensions.set("code", error.code))
+}
+
+fn loader<'a>(ctx: &Context<'a>) -> &'a NapkinDataLoader {
+    ctx.data_unchecked::<NapkinDataLoader>()
+}
+
+// Keeps the rows named in `names`, or all of them
+fn named<T>(rows: Vec<T>, names: Option<Vec<String>>, name: impl Fn(&T) -> &str) -> Vec<T> {
+    match names {
+        Some(names) => rows.into_iter().filter(|row| names.iter().any(|wanted| wanted == name(row))).collect(),
+        None => rows,
+    }
+}
+
+// Nested lists page like `projects`: by ID, `limit` rows (100 by default, at most 1000) per parent
+fn paging(label: Option<String>, after: Option<uuid::Uuid>, limit: Option<i64>) -> async_graphql::Result<Paging> {
+    let limit = PageParams { limit, after: None }.limit().map_err(graphql_error)?;
+    Ok(Paging { label, after, limit: Some(limit) })
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn projects(&self, ctx: &Context<'_>, after: Option<uuid::Uuid>, limit: Option<i64>) -> async_graphql::Result<Vec<Project>> {
+        let limit = PageParams { limit, after: None }.limit().map_err(graphql_error)?;
+        let client: Client = ctx.data_unchecked::<Pool>().get().await.map_err(|error| graphql_error(handle_pool_error(error)))?;
+        let mut projects = db::projects::get_projects(&client, &after, Some(limit)).await.map_err(graphql_error)?;
+        projects.truncate(limit as usize);
+        Ok(projects)
+    }
+
+    async fn project(&self, ctx: &Context<'_>, id: uuid::Uuid) -> async_graphql::Result<Option<Project>> {
+        loader(ctx).load_one(ProjectId(id)).await
+    }
+
+    async fn node(&self, ctx: &Context<'_>, id: uuid::Uuid) -> async_graphql::Result<Option<Node>> {
+        loader(ctx).load_one(NodeId(id)).await
+    }
+
+    // In the order of `ids`, skipping unknown IDs
+    async fn nodes(&self, ctx: &Context<'_>, ids: Vec<uuid::Uuid>) -> async_graphql::Result<Vec<Node>> {
+        let mut nodes = loader(ctx).load_many(ids.iter().map(|id| NodeId(*id))).await?;
+        Ok(ids.iter().filter_map(|id| nodes.remove(&NodeId(*id))).collect())
+    }
+
+    async fn edge(&self, ctx: &Context<'_>, id: uuid::Uuid) -> async_graphql::Result<Option<Edge>> {
+        loader(ctx).load_one(EdgeId(id)).await
+    }
+}
+
+#[Object]
+impl Project {
+    async fn id(&self) -> uuid::Uuid {
+        self.id.unwrap_or_default()
+    }
+
+    async fn scope(&self) -> &str {
+        &self.scope
+    }
+
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn nodes(
+        &self,
+        ctx: &Context<'_>,
+        label: Option<String>,
+        after: Option<uuid::Uuid>,
+        limit: Option<i64>,
+    ) -> async_graphql::Result<Vec<Node>> {
+        let key = ProjectNodes(self.id.unwrap_or_default(), paging(label, after, limit)?);
+        Ok(loader(ctx).load_one(key).await?.unwrap_or_default())
+    }
+
+    async fn edges(
+        &self,
+        ctx: &Context<'_>,
+        label: Option<String>,
+        after: Option<uuid::Uuid>,
+        limit: Option<i64>,
+    ) -> async_graphql::Result<Vec<Edge>> {
+        let key = ProjectEdges(self.id.unwrap_or_default(), paging(label, after, limit)?);
+        Ok(loader(ctx).load_one(key).await?.unwrap_or_default())
+    }
+}
+
+#[Object]
+impl Node {
+    async fn id(&self) -> uuid::Uuid {
+        self.id.unwrap_or_default()
+    }
+
+    async fn project(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Project>> {
+        loader(ctx).load_one(ProjectId(self.project)).await
+    }
+
+    async fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    async fn metadata(&self, ctx: &Context<'_>, names: Option<Vec<String>>) -> async_graphql::Result<Vec<NodeMetadata>> {
+        let metadata = loader(ctx).load_one(NodeMetadataOf(self.id.unwrap_or_default())).await?.unwrap_or_default();
+        Ok(named(metadata, names, |metadata| &metadata.name))
+    }
+
+    // Edges whose source is this node, directed or not
+    async fn outgoing(
+        &self,
+        ctx: &Context<'_>,
+        label: Option<String>,
+        after: Option<uuid::Uuid>,
+        limit: Option<i64>,
+    ) -> async_graphql::Result<Vec<Edge>> {
+        let key = OutgoingEdges(self.id.unwrap_or_default(), paging(label, after, limit)?);
+        Ok(loader(ctx).load_one(key).await?.unwrap_or_default())
+    }
+
+    // Edges whose target is this node, directed or not
+    async fn incoming(
+        &self,
+        ctx: &Context<'_>,
+        label: Option<String>,
+        after: Option<uuid::Uuid>,
+        limit: Option<i64>,
+    ) -> async_graphql::Result<Vec<Edge>> {
+        let key = IncomingEdges(self.id.unwrap_or_default(), paging(label, after, limit)?);
+        Ok(loader(ctx).load_one(key).await?.unwrap_or_default())
+    }
+
+    // Nodes sharing an edge with this one in either direction, each once
+    async fn neighbors(&self, ctx: &Context<'_>, label: Option<String>) -> async_graphql::Result<Vec<Node>> {
+        let id = self.id.unwrap_or_default();
+        let every = Paging { label, after: None, limit: None };
+        let outgoing = loader(ctx).load_one(OutgoingEdges(id, every.clone())).await?.unwrap_or_default();
+        let incoming = loader(ctx).load_one(IncomingEdges(id, every)).await?.unwrap_or_default();
+
+        let mut ids: Vec<uuid::Uuid> = Vec::new();
+        for edge in outgoing.into_iter().chain(incoming) {
+            let other = if edge.source == id { edge.target } else { edge.source };
+            if !ids.contains(&other) {
+                ids.push(other);
+            }
+        }
+        let mut nodes = loader(ctx).load_many(ids.iter().map(|id| NodeId(*id))).await?;
+        Ok(ids.iter().filter_map(|id| nodes.remove(&NodeId(*id))).collect())
+    }
+
+    async fn artifact(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Artifact>> {
+        loader(ctx).load_one(ArtifactOf(self.id.unwrap_or_default())).await
+    }
+}
+
+#[Object]
+impl Edge {
+    async fn id(&self) -> uuid::Uuid {
+        self.id.unwrap_or_default()
+    }
+
+    async fn project(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Project>> {
+        loader(ctx).load_one(ProjectId(self.project)).await
+    }
+
+    async fn source(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Node>> {
+        loader(ctx).load_one(NodeId(self.source)).await
+    }
+
+    async fn target(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Node>> {
+        loader(ctx).load_one(NodeId(self.target)).await
+    }
+
+    async fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    async fn directed(&self) -> bool {
+        self.directed
+    }
+
+    async fn weight(&self) -> Option<f64> {
+        self.weight
+    }
+
+    async fn metadata(&self, ctx: &Context<'_>, names: Option<Vec<String>>) -> async_graphql::Result<Vec<EdgeMetadata>> {
+        let metadata = loader(ctx).load_one(EdgeMetadataOf(self.id.unwrap_or_default())).await?.unwrap_or_default();
+        Ok(named(metadata, names, |metadata| &metadata.name))
+    }
+}
+
+#[Object]
+impl Artifact {
+    async fn node(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Node>> {
+        loader(ctx).load_one(NodeId(self.node_id)).await
+    }
+
+    async fn embedding(&self) -> Vec<f32> {
+        self.embedding.to_vec()
+    }
+
+    async fn metadata(&self, ctx: &Context<'_>, names: Option<Vec<String>>) -> async_graphql::Result<Vec<ArtifactMetadata>> {
+        let metadata = loader(ctx).load_one(ArtifactMetadataOf(self.node_id)).await?.unwrap_or_default();
+        Ok(named(metadata, names, |metadata| &metadata.name))
+    }
+}
+
+#[Object]
+impl NodeMetadata {
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn value(&self) -> Json<&serde_json::Value> {
+        Json(&self.value)
+    }
+}
+
+#[Object]
+impl EdgeMetadata {
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn value(&self) -> Json<&serde_json::Value> {
+        Json(&self.value)
+    }
+}
+
+#[Object]
+impl ArtifactMetadata {
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn value(&self) -> Json<&serde_json::Value> {
+        Json(&self.value)
+    }
+}
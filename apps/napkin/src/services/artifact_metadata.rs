@@ -0,0 +1,100 @@
+use actix_web::{ get, post, put, delete, web, HttpRequest, Responder, Result };
+use deadpool_postgres::{Client, Pool};
+
+use crate::models::pagination::{key_cursor, Listing, PageParams};
+use crate::models::artifact_metadata::{ArtifactMetadata, ArtifactMetadataReqObj, ArtifactMetadataUpdate};
+use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
+use crate::services::preconditions::{check_precondition, if_match, versioned, with_etag};
+use crate::db;
+
+fn parse_owner_id(owner_id: &str) -> Result<uuid::Uuid, NapkinError> {
+    uuid::Uuid::parse_str(owner_id).map_err(|_| NapkinError {
+        code: "ARTIFACT_NO_ID",
+        message: "ID `{owner_id}` Invalid or Not Found",
+        root: NapkinErrorRoot::NotFound,
+    })
+}
+
+#[get("")]
+pub async fn get_artifact_metadata(page: web::Query<PageParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let artifact_metadata = db::artifact_metadata::get_artifact_metadata(&client, &page.after_key()?, limit).await?;
+    Ok(web::Json(Listing::new(artifact_metadata, limit, |metadata| key_cursor(&metadata.owner_id, &metadata.name))))
+}
+
+#[post("")]
+pub async fn post_artifact_metadata(body: web::Json<ArtifactMetadataReqObj>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let owner_id = parse_owner_id(&body.owner_id)?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    // Surface a missing artifact as a 404 rather than a foreign key violation
+    db::artifacts::get_artifact(&client, &owner_id).await?;
+
+    let artifact_metadata_info = ArtifactMetadata {
+        owner_id,
+        name: body.name.clone(),
+        value: body.value.clone(),
+        version: 0,
+    };
+
+    let new_artifact_metadata = db::artifact_metadata::add_artifact_metadata(&client, artifact_metadata_info).await?;
+    let version = new_artifact_metadata.version;
+
+    Ok(with_etag(new_artifact_metadata, version))
+}
+
+#[get("/{owner_id}")]
+pub async fn get_artifact_metadata_singleton(owner_id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let artifact_metadata = db::artifact_metadata::get_artifact_metadata_singleton(&client, &owner_id).await?;
+
+    Ok(web::Json(artifact_metadata))
+}
+
+#[get("/{owner_id}/{name}")]
+pub async fn get_artifact_metadata_singleton_key(request: HttpRequest, param: web::Path<(uuid::Uuid, String)>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let (owner_id, name) = param.into_inner();
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let artifact_metadata_key = db::artifact_metadata::get_artifact_metadata_singleton_key(&client, &owner_id, &name).await?;
+    let version = artifact_metadata_key.version;
+
+    Ok(versioned(&request, artifact_metadata_key, version))
+}
+
+#[put("/{owner_id}/{name}")]
+pub async fn update_artifact_metadata(request: HttpRequest, param: web::Path<(uuid::Uuid, String)>, body: web::Json<ArtifactMetadataUpdate>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let (owner_id, name) = param.into_inner();
+    let artifact_metadata_info: ArtifactMetadataUpdate = body.into_inner();
+    let if_match = if_match(&request);
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    // Retrieve the existing artifact metadata to update only provided fields
+    let existing = db::artifact_metadata::get_artifact_metadata_singleton_key(&client, &owner_id, &name).await?;
+    let updated_artifact_metadata_info = ArtifactMetadata {
+        owner_id: artifact_metadata_info.owner_id.unwrap_or(existing.owner_id),
+        name: artifact_metadata_info.name.unwrap_or(existing.name),
+        value: artifact_metadata_info.value.unwrap_or(existing.value),
+        version: existing.version,
+    };
+
+    let updated_artifact_metadata = db::artifact_metadata::update_artifact_metadata(&client, &owner_id, &name, updated_artifact_metadata_info, &if_match).await;
+    let updated_artifact_metadata = check_precondition(updated_artifact_metadata, &if_match, db::artifact_metadata::get_artifact_metadata_singleton_key(&client, &owner_id, &name)).await?;
+    let version = updated_artifact_metadata.version;
+
+    Ok(with_etag(updated_artifact_metadata, version))
+}
+
+#[delete("/{owner_id}/{name}")]
+pub async fn delete_artifact_metadata(request: HttpRequest, param: web::Path<(uuid::Uuid, String)>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let (owner_id, name) = param.into_inner();
+    let if_match = if_match(&request);
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let deleted_artifact_metadata = db::artifact_metadata::delete_artifact_metadata(&client, &owner_id, &name, &if_match).await;
+    let deleted_artifact_metadata = check_precondition(deleted_artifact_metadata, &if_match, db::artifact_metadata::get_artifact_metadata_singleton_key(&client, &owner_id, &name)).await?;
+
+    Ok(web::Json(deleted_artifact_metadata))
+}
@@ -0,0 +1,139 @@
+use actix_web::{ get, post, put, delete, web, HttpRequest, Responder, Result };
+use deadpool_postgres::{Client, Pool};
+
+use crate::models::pagination::{Listing, PageParams};
+use crate::models::artifacts::{
+    Artifact, ArtifactReqObj, ArtifactSearchReqObj, ArtifactUpdate, ARTIFACT_DIMENSIONS,
+};
+use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
+use crate::services::preconditions::{check_precondition, if_match, versioned, with_etag};
+use crate::db;
+
+fn parse_node_id(node_id: &str) -> Result<uuid::Uuid, NapkinError> {
+    uuid::Uuid::parse_str(node_id).map_err(|_| NapkinError {
+        code: "ARTIFACT_NO_ID",
+        message: "Node ID `{node_id}` Invalid or Not Found",
+        root: NapkinErrorRoot::NotFound,
+    })
+}
+
+fn to_embedding(embedding: Vec<f32>) -> Result<pgvector::Vector, NapkinError> {
+    if embedding.len() != ARTIFACT_DIMENSIONS {
+        return Err(NapkinError {
+            code: "ARTIFACT_BAD_DIMENSIONS",
+            message: "Embedding must have exactly 1024 dimensions",
+            root: NapkinErrorRoot::BadRequest,
+        });
+    }
+    if embedding.iter().any(|value| !value.is_finite()) {
+        return Err(NapkinError {
+            code: "ARTIFACT_BAD_VALUE",
+            message: "Embedding must not contain NaN or infinite values",
+            root: NapkinErrorRoot::BadRequest,
+        });
+    }
+
+    Ok(pgvector::Vector::from(embedding))
+}
+
+const DEFAULT_SEARCH_K: i64 = 10;
+const MAX_SEARCH_K: i64 = 1000;
+
+#[get("")]
+pub async fn get_artifacts(page: web::Query<PageParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let artifacts = db::artifacts::get_artifacts(&client, &page.after_id()?, limit).await?;
+    Ok(web::Json(Listing::new(artifacts, limit, |artifact| artifact.node_id.to_string())))
+}
+
+#[post("")]
+pub async fn post_artifact(body: web::Json<ArtifactReqObj>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let body = body.into_inner();
+    let node_id = parse_node_id(&body.node_id)?;
+    let embedding = to_embedding(body.embedding)?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    // Surface a missing node as a 404 rather than a foreign key violation
+    db::nodes::get_node(&client, &node_id).await?;
+
+    let artifact_info = Artifact {
+        node_id,
+        embedding,
+        version: 0,
+    };
+
+    let new_artifact = db::artifacts::add_artifact(&client, artifact_info).await?;
+    let version = new_artifact.version;
+
+    Ok(with_etag(new_artifact, version))
+}
+
+#[post("/search")]
+pub async fn search_artifacts(body: web::Json<ArtifactSearchReqObj>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let body = body.into_inner();
+    let embedding = to_embedding(body.embedding)?;
+    let k = body.k.unwrap_or(DEFAULT_SEARCH_K);
+    if !(1..=MAX_SEARCH_K).contains(&k) {
+        return Err(NapkinError {
+            code: "ARTIFACT_BAD_K",
+            message: "`k` must be between 1 and 1000",
+            root: NapkinErrorRoot::BadRequest,
+        });
+    }
+    let project = match body.project {
+        Some(project) => Some(uuid::Uuid::parse_str(&project).map_err(|_| NapkinError {
+            code: "PROJECT_NO_ID",
+            message: "Project ID `{project}` Invalid or Not Found",
+            root: NapkinErrorRoot::NotFound,
+        })?),
+        None => None,
+    };
+    let mut client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let results = db::artifacts::search_artifacts(
+        &mut client,
+        embedding,
+        k,
+        project,
+        body.metric.unwrap_or_default(),
+        body.filter.as_ref(),
+    )
+    .await?;
+
+    Ok(web::Json(results))
+}
+
+#[get("/{node_id}")]
+pub async fn get_artifact(request: HttpRequest, node_id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let artifact = db::artifacts::get_artifact(&client, &node_id).await?;
+    let version = artifact.version;
+
+    Ok(versioned(&request, artifact, version))
+}
+
+#[put("/{node_id}")]
+pub async fn update_artifact(request: HttpRequest, node_id: web::Path<uuid::Uuid>, body: web::Json<ArtifactUpdate>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let embedding = to_embedding(body.into_inner().embedding)?;
+    let if_match = if_match(&request);
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let updated_artifact = db::artifacts::update_artifact(&client, &node_id, &embedding, &if_match).await;
+    let updated_artifact = check_precondition(updated_artifact, &if_match, db::artifacts::get_artifact(&client, &node_id)).await?;
+    let version = updated_artifact.version;
+
+    Ok(with_etag(updated_artifact, version))
+}
+
+#[delete("/{node_id}")]
+pub async fn delete_artifact(request: HttpRequest, node_id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let if_match = if_match(&request);
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let deleted_artifact = db::artifacts::delete_artifact(&client, &node_id, &if_match).await;
+    let deleted_artifact = check_precondition(deleted_artifact, &if_match, db::artifacts::get_artifact(&client, &node_id)).await?;
+
+    Ok(web::Json(deleted_artifact))
+}
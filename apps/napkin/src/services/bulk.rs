@@ -0,0 +1,214 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use actix_web::{ post, web, Responder, Result };
+use deadpool_postgres::{Client, Pool, Transaction};
+use jsonschema::JSONSchema;
+
+use crate::models::bulk::{BulkImport, BulkImportResult, EdgeColumns, MetadataColumns, NodeColumns};
+use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
+use crate::services::edges::finite_weight;
+use crate::services::metadata_schemas::{compile_schema, schema_violations};
+use crate::db;
+
+// Bulk documents are far larger than the default 32 KiB JSON limit
+pub const BULK_PAYLOAD_LIMIT: usize = 32 * 1024 * 1024;
+
+fn bulk_invalid(violations: Vec<String>) -> NapkinError {
+    NapkinError {
+        code: "BULK_INVALID",
+        message: "Bulk import document is invalid, nothing was imported",
+        root: NapkinErrorRoot::Unprocessable(violations),
+    }
+}
+
+// An edge endpoint: a node created by this import, or one already stored
+#[derive(Clone, Copy)]
+enum Endpoint {
+    New(usize),
+    Existing(uuid::Uuid),
+}
+
+fn resolve_endpoint(reference: &str, temp_ids: &HashMap<&str, usize>) -> Option<Endpoint> {
+    match temp_ids.get(reference) {
+        Some(index) => Some(Endpoint::New(*index)),
+        None => uuid::Uuid::parse_str(reference).ok().map(Endpoint::Existing),
+    }
+}
+
+// Where a violation points: JSON paths for bulk documents, file rows for CSV imports
+pub type Locate<'a> = &'a dyn Fn(&str, usize) -> String;
+
+fn json_location(section: &str, index: usize) -> String {
+    format!("{}/{}", section, index)
+}
+
+// `BulkImport` checked against its project; only applied when `violations` is empty.
+// Planning locks the rows it checks, so the plan holds for the rest of its transaction.
+pub struct BulkPlan {
+    endpoints: Vec<(Endpoint, Endpoint)>,
+    pub violations: Vec<String>,
+}
+
+pub async fn plan_bulk(transaction: &Transaction<'_>, id: &uuid::Uuid, body: &BulkImport, locate: Locate<'_>) -> Result<BulkPlan, NapkinError> {
+    let mut violations = Vec::new();
+
+    let mut temp_ids: HashMap<&str, usize> = HashMap::new();
+    for (index, node) in body.nodes.iter().enumerate() {
+        if temp_ids.insert(node.temp_id.as_str(), index).is_some() {
+            violations.push(format!("{}/temp_id: `{}` is used more than once", locate("nodes", index), node.temp_id));
+        }
+    }
+
+    let mut edge_temp_ids = HashSet::new();
+    let mut endpoints = Vec::with_capacity(body.edges.len());
+    for (index, edge) in body.edges.iter().enumerate() {
+        if let Some(temp_id) = &edge.temp_id {
+            if !edge_temp_ids.insert(temp_id.as_str()) {
+                violations.push(format!("{}/temp_id: `{}` is used more than once", locate("edges", index), temp_id));
+            }
+        }
+        if !finite_weight(edge.weight) {
+            violations.push(format!("{}/weight: `{}` is not a finite number", locate("edges", index), edge.weight.unwrap_or_default()));
+        }
+        let source = resolve_endpoint(&edge.source, &temp_ids);
+        let target = resolve_endpoint(&edge.target, &temp_ids);
+        if source.is_none() {
+            violations.push(format!("{}/source: `{}` is neither a node temp_id nor a node ID", locate("edges", index), edge.source));
+        }
+        if target.is_none() {
+            violations.push(format!("{}/target: `{}` is neither a node temp_id nor a node ID", locate("edges", index), edge.target));
+        }
+        endpoints.push((source, target));
+    }
+
+    if !violations.is_empty() {
+        return Ok(BulkPlan { endpoints: Vec::new(), violations });
+    }
+    let endpoints: Vec<(Endpoint, Endpoint)> = endpoints
+        .into_iter()
+        .filter_map(|(source, target)| Some((source?, target?)))
+        .collect();
+
+    db::projects::lock_project(transaction, id).await?;
+
+    // Nodes referenced by ID must already be in this project
+    let referenced: Vec<uuid::Uuid> = endpoints
+        .iter()
+        .flat_map(|(source, target)| [*source, *target])
+        .filter_map(|endpoint| match endpoint {
+            Endpoint::Existing(node_id) => Some(node_id),
+            Endpoint::New(_) => None,
+        })
+        .collect();
+    if !referenced.is_empty() {
+        let existing: HashSet<uuid::Uuid> = db::bulk::get_existing_node_ids(transaction, id, &referenced)
+            .await?
+            .into_iter()
+            .collect();
+        for (index, (source, target)) in endpoints.iter().enumerate() {
+            for (field, endpoint) in [("source", source), ("target", target)] {
+                if let Endpoint::Existing(node_id) = endpoint {
+                    if !existing.contains(node_id) {
+                        violations.push(format!("{}/{}: node `{}` is not in this project", locate("edges", index), field, node_id));
+                    }
+                }
+            }
+        }
+    }
+
+    let schemas = db::metadata_schemas::lock_all_metadata_schemas(transaction, id)
+        .await?
+        .into_iter()
+        .map(|schema| Ok((schema.name.clone(), compile_schema(&schema.schema)?)))
+        .collect::<Result<HashMap<String, JSONSchema>, NapkinError>>()?;
+    for (index, node) in body.nodes.iter().enumerate() {
+        for (name, value) in node.metadata.iter() {
+            if let Some(compiled) = schemas.get(name) {
+                violations.extend(schema_violations(compiled, &format!("{}/metadata/{}", locate("nodes", index), name), value));
+            }
+        }
+    }
+    for (index, edge) in body.edges.iter().enumerate() {
+        for (name, value) in edge.metadata.iter() {
+            if let Some(compiled) = schemas.get(name) {
+                violations.extend(schema_violations(compiled, &format!("{}/metadata/{}", locate("edges", index), name), value));
+            }
+        }
+    }
+
+    Ok(BulkPlan { endpoints, violations })
+}
+
+// `plan` must come from `plan_bulk` on the same transaction, which the caller commits
+pub async fn apply_bulk(transaction: &Transaction<'_>, id: &uuid::Uuid, body: &BulkImport, plan: &BulkPlan) -> Result<BulkImportResult, NapkinError> {
+    let node_ids = db::bulk::generate_ids(transaction, body.nodes.len() as i64).await?;
+    let edge_ids = db::bulk::generate_ids(transaction, body.edges.len() as i64).await?;
+    let node_id = |endpoint: Endpoint| match endpoint {
+        Endpoint::New(index) => node_ids[index],
+        Endpoint::Existing(node_id) => node_id,
+    };
+
+    let mut nodes = NodeColumns::default();
+    let mut node_metadata = MetadataColumns::default();
+    for (node, id) in body.nodes.iter().zip(node_ids.iter()) {
+        nodes.ids.push(*id);
+        nodes.labels.push(serde_json::json!(node.labels));
+        for (name, value) in node.metadata.iter() {
+            node_metadata.owner_ids.push(*id);
+            node_metadata.names.push(name.clone());
+            node_metadata.values.push(value.clone());
+        }
+    }
+
+    let mut edges = EdgeColumns::default();
+    let mut edge_metadata = MetadataColumns::default();
+    for ((edge, id), (source, target)) in body.edges.iter().zip(edge_ids.iter()).zip(plan.endpoints.iter()) {
+        edges.ids.push(*id);
+        edges.sources.push(node_id(*source));
+        edges.targets.push(node_id(*target));
+        edges.labels.push(edge.label.clone());
+        edges.directed.push(edge.directed.unwrap_or(true));
+        edges.weights.push(edge.weight);
+        for (name, value) in edge.metadata.iter() {
+            edge_metadata.owner_ids.push(*id);
+            edge_metadata.names.push(name.clone());
+            edge_metadata.values.push(value.clone());
+        }
+    }
+
+    db::bulk::quiet_changes(transaction).await?;
+    let counts = serde_json::json!({
+        "nodes": db::bulk::add_nodes(transaction, id, &nodes).await?,
+        "edges": db::bulk::add_edges(transaction, id, &edges).await?,
+        "node_metadata": db::bulk::add_node_metadata(transaction, &node_metadata).await?,
+        "edge_metadata": db::bulk::add_edge_metadata(transaction, &edge_metadata).await?,
+    });
+    db::bulk::notify_import(transaction, id, &counts).await?;
+
+    Ok(BulkImportResult {
+        nodes: body.nodes.iter().map(|node| node.temp_id.clone()).zip(node_ids.iter().copied()).collect(),
+        edges: body
+            .edges
+            .iter()
+            .zip(edge_ids.iter())
+            .filter_map(|(edge, id)| Some((edge.temp_id.clone()?, *id)))
+            .collect::<BTreeMap<String, uuid::Uuid>>(),
+    })
+}
+
+#[post("")]
+pub async fn post_bulk(id: web::Path<uuid::Uuid>, body: web::Json<BulkImport>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let mut client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let transaction = client.transaction().await?;
+
+    let plan = plan_bulk(&transaction, &id, &body, &json_location).await?;
+    if !plan.violations.is_empty() {
+        return Err(bulk_invalid(plan.violations));
+    }
+    let result = apply_bulk(&transaction, &id, &body, &plan).await?;
+
+    transaction.commit().await?;
+
+    Ok(web::Json(result))
+}
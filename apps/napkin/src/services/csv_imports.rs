@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+
+use actix_multipart::Multipart;
+use actix_web::{ post, web, HttpResponse, Responder, Result };
+use deadpool_postgres::{Client, Pool};
+use futures_util::TryStreamExt;
+use serde_json::Value;
+
+use crate::models::bulk::{BulkEdge, BulkImport, BulkNode};
+use crate::models::csv_imports::{ColumnMapping, ColumnType, CsvDryRun, CsvImportParams, CsvMapping};
+use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
+use crate::services::bulk::{apply_bulk, plan_bulk, BULK_PAYLOAD_LIMIT};
+
+const NODES_FILE: &str = "nodes.csv";
+const EDGES_FILE: &str = "edges.csv";
+
+fn csv_bad_upload() -> NapkinError {
+    NapkinError {
+        code: "CSV_BAD_UPLOAD",
+        message: "Expected multipart parts `mapping` (JSON), `nodes` and optionally `edges` (CSV)",
+        root: NapkinErrorRoot::BadRequest,
+    }
+}
+
+fn csv_invalid(violations: Vec<String>) -> NapkinError {
+    NapkinError {
+        code: "CSV_INVALID",
+        message: "CSV import is invalid, nothing was imported",
+        root: NapkinErrorRoot::Unprocessable(violations),
+    }
+}
+
+// Multipart part name -> contents, capped at the bulk import limit in total
+async fn read_parts(mut payload: Multipart) -> Result<HashMap<String, Vec<u8>>, NapkinError> {
+    let mut parts = HashMap::new();
+    let mut size = 0;
+    while let Some(mut field) = payload.try_next().await.map_err(|_| csv_bad_upload())? {
+        let name = field.name().unwrap_or_default().to_string();
+        let mut contents = Vec::new();
+        while let Some(chunk) = field.try_next().await.map_err(|_| csv_bad_upload())? {
+            size += chunk.len();
+            if size > BULK_PAYLOAD_LIMIT {
+                return Err(NapkinError {
+                    code: "CSV_TOO_LARGE",
+                    message: "CSV upload exceeds 32 MiB",
+                    root: NapkinErrorRoot::BadRequest,
+                });
+            }
+            contents.extend_from_slice(&chunk);
+        }
+        parts.insert(name, contents);
+    }
+    Ok(parts)
+}
+
+// Parsed rows of one file, each with the line it starts on
+struct Table {
+    file: &'static str,
+    headers: HashMap<String, usize>,
+    rows: Vec<(u64, csv::StringRecord)>,
+}
+
+impl Table {
+    fn read(file: &'static str, contents: &[u8], violations: &mut Vec<String>) -> Table {
+        let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::Headers).from_reader(contents);
+        let headers = match reader.headers() {
+            Ok(headers) => headers.iter().enumerate().map(|(index, header)| (header.to_string(), index)).collect(),
+            Err(error) => {
+                violations.push(format!("{}: {}", file, error));
+                HashMap::new()
+            }
+        };
+        let mut rows = Vec::new();
+        for record in reader.records() {
+            match record {
+                Ok(record) => rows.push((record.position().map(|position| position.line()).unwrap_or_default(), record)),
+                Err(error) => violations.push(format!("{}: {}", file, error)),
+            }
+        }
+        Table { file, headers, rows }
+    }
+
+    fn column(&self, name: &str, violations: &mut Vec<String>) -> Option<usize> {
+        let index = self.headers.get(name).copied();
+        if index.is_none() {
+            violations.push(format!("{}: no column `{}`", self.file, name));
+        }
+        index
+    }
+
+    fn location(&self, index: usize) -> String {
+        format!("{}:{}", self.file, self.rows[index].0)
+    }
+}
+
+fn type_name(column_type: ColumnType) -> &'static str {
+    match column_type {
+        ColumnType::String => "string",
+        ColumnType::Integer => "integer",
+        ColumnType::Number => "number",
+        ColumnType::Boolean => "boolean",
+        ColumnType::Json => "JSON value",
+    }
+}
+
+fn cell_value(text: &str, column_type: ColumnType) -> Option<Value> {
+    match column_type {
+        ColumnType::String => Some(Value::String(text.to_string())),
+        ColumnType::Integer => text.trim().parse::<i64>().ok().map(Value::from),
+        // `NaN`, `inf` and overflowing literals parse as floats but have no JSON number
+        ColumnType::Number => text
+            .trim()
+            .parse::<i64>()
+            .ok()
+            .map(Value::from)
+            .or_else(|| text.trim().parse::<f64>().ok().filter(|float| float.is_finite()).and_then(serde_json::Number::from_f64).map(Value::Number)),
+        ColumnType::Boolean => match text.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" => Some(Value::Bool(true)),
+            "false" | "0" | "no" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        ColumnType::Json => serde_json::from_str(text).ok(),
+    }
+}
+
+// Metadata columns resolved against a table's header
+fn metadata_columns<'a>(table: &Table, mappings: &'a [ColumnMapping], violations: &mut Vec<String>) -> Vec<(usize, &'a ColumnMapping)> {
+    mappings
+        .iter()
+        .filter_map(|mapping| Some((table.column(&mapping.column, violations)?, mapping)))
+        .collect()
+}
+
+fn row_metadata(
+    table: &Table,
+    index: usize,
+    columns: &[(usize, &ColumnMapping)],
+    violations: &mut Vec<String>,
+) -> serde_json::Map<String, Value> {
+    let record = &table.rows[index].1;
+    let mut metadata = serde_json::Map::new();
+    for (column, mapping) in columns {
+        let text = record.get(*column).unwrap_or_default();
+        if text.is_empty() {
+            continue;
+        }
+        match cell_value(text, mapping.column_type) {
+            Some(value) => {
+                metadata.insert(mapping.name.clone().unwrap_or_else(|| mapping.column.clone()), value);
+            }
+            None => violations.push(format!(
+                "{}/{}: `{}` is not a valid {}",
+                table.location(index),
+                mapping.column,
+                text,
+                type_name(mapping.column_type)
+            )),
+        }
+    }
+    metadata
+}
+
+fn non_empty(record: &csv::StringRecord, column: Option<usize>) -> Option<String> {
+    column
+        .and_then(|column| record.get(column))
+        .filter(|text| !text.is_empty())
+        .map(String::from)
+}
+
+// Maps both tables onto a bulk import; row-level problems go to `violations`
+fn build_import(mapping: &CsvMapping, nodes: &Table, edges: Option<&Table>, violations: &mut Vec<String>) -> BulkImport {
+    let mut import = BulkImport { nodes: Vec::new(), edges: Vec::new() };
+
+    let id_column = nodes.column(&mapping.nodes.id, violations);
+    let labels_column = mapping.nodes.labels.as_ref().and_then(|labels| nodes.column(labels, violations));
+    let node_metadata = metadata_columns(nodes, &mapping.nodes.metadata, violations);
+    if let Some(id_column) = id_column {
+        for (index, (_, record)) in nodes.rows.iter().enumerate() {
+            let Some(temp_id) = non_empty(record, Some(id_column)) else {
+                violations.push(format!("{}/{}: missing node ID", nodes.location(index), mapping.nodes.id));
+                continue;
+            };
+            import.nodes.push(BulkNode {
+                temp_id,
+                labels: non_empty(record, labels_column)
+                    .map(|labels| labels.split(';').map(str::trim).filter(|label| !label.is_empty()).map(String::from).collect())
+                    .unwrap_or_default(),
+                metadata: row_metadata(nodes, index, &node_metadata, violations),
+            });
+        }
+    }
+
+    let (Some(edge_mapping), Some(edges)) = (&mapping.edges, edges) else {
+        return import;
+    };
+    let source_column = edges.column(&edge_mapping.source, violations);
+    let target_column = edges.column(&edge_mapping.target, violations);
+    let id_column = edge_mapping.id.as_ref().and_then(|id| edges.column(id, violations));
+    let label_column = edge_mapping.label.as_ref().and_then(|label| edges.column(label, violations));
+    let weight_column = edge_mapping.weight.as_ref().and_then(|weight| edges.column(weight, violations));
+    let edge_metadata = metadata_columns(edges, &edge_mapping.metadata, violations);
+    let (Some(source_column), Some(target_column)) = (source_column, target_column) else {
+        return import;
+    };
+    for (index, (_, record)) in edges.rows.iter().enumerate() {
+        let weight = non_empty(record, weight_column).and_then(|text| match text.trim().parse::<f64>() {
+            Ok(weight) if weight.is_finite() => Some(weight),
+            _ => {
+                violations.push(format!("{}/{}: `{}` is not a finite number", edges.location(index), edge_mapping.weight.as_deref().unwrap_or_default(), text));
+                None
+            }
+        });
+        import.edges.push(BulkEdge {
+            temp_id: non_empty(record, id_column),
+            source: record.get(source_column).unwrap_or_default().to_string(),
+            target: record.get(target_column).unwrap_or_default().to_string(),
+            label: non_empty(record, label_column),
+            directed: edge_mapping.directed,
+            weight,
+            metadata: row_metadata(edges, index, &edge_metadata, violations),
+        });
+    }
+
+    import
+}
+
+fn dry_run(import: &BulkImport, violations: Vec<String>) -> HttpResponse {
+    HttpResponse::Ok().json(CsvDryRun {
+        nodes: import.nodes.len(),
+        edges: import.edges.len(),
+        node_metadata: import.nodes.iter().map(|node| node.metadata.len()).sum(),
+        edge_metadata: import.edges.iter().map(|edge| edge.metadata.len()).sum(),
+        violations,
+    })
+}
+
+// Multipart upload with parts `mapping` (a `CsvMapping` as JSON), `nodes` and optionally `edges`
+#[post("/{id}/import/csv")]
+pub async fn post_csv_import(
+    id: web::Path<uuid::Uuid>,
+    params: web::Query<CsvImportParams>,
+    payload: Multipart,
+    db_pool: web::Data<Pool>,
+) -> Result<impl Responder, NapkinError> {
+    let parts = read_parts(payload).await?;
+    let mapping: CsvMapping = parts
+        .get("mapping")
+        .and_then(|mapping| serde_json::from_slice(mapping).ok())
+        .ok_or_else(csv_bad_upload)?;
+    let nodes = parts.get("nodes").ok_or_else(csv_bad_upload)?;
+    if mapping.edges.is_some() != parts.contains_key("edges") {
+        return Err(csv_bad_upload());
+    }
+    let dry = params.dry_run.unwrap_or(false);
+
+    let mut violations = Vec::new();
+    let nodes = Table::read(NODES_FILE, nodes, &mut violations);
+    let edges = parts.get("edges").map(|edges| Table::read(EDGES_FILE, edges, &mut violations));
+    let import = build_import(&mapping, &nodes, edges.as_ref(), &mut violations);
+
+    let mut client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    // Bulk checks index the imported rows, so they only run while every CSV record became one
+    let aligned = import.nodes.len() == nodes.rows.len()
+        && import.edges.len() == edges.as_ref().map(|edges| edges.rows.len()).unwrap_or_default();
+    let locate = |section: &str, index: usize| match (section, &edges) {
+        ("edges", Some(edges)) => edges.location(index),
+        _ => nodes.location(index),
+    };
+    let transaction = client.transaction().await?;
+    let plan = if aligned { Some(plan_bulk(&transaction, &id, &import, &locate).await?) } else { None };
+    violations.extend(plan.iter().flat_map(|plan| plan.violations.iter().cloned()));
+
+    if dry {
+        return Ok(dry_run(&import, violations));
+    }
+    let plan = match plan {
+        Some(plan) if violations.is_empty() => plan,
+        _ => return Err(csv_invalid(violations)),
+    };
+    let result = apply_bulk(&transaction, &id, &import, &plan).await?;
+
+    transaction.commit().await?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
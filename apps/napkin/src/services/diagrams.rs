@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use actix_web::{ get, web, HttpResponse, Responder, Result };
+use deadpool_postgres::{Client, Pool};
+
+use crate::models::diagrams::DiagramParams;
+use crate::models::edges::Edge;
+use crate::models::traversals::NeighborParams;
+use crate::formats::{Diagram, DiagramEdge, DiagramNode};
+use crate::errors::{ NapkinError, handle_pool_error };
+use crate::services::nodes::load_neighborhood;
+use crate::{db, formats};
+
+const DOT_CONTENT_TYPE: &str = "text/vnd.graphviz; charset=utf-8";
+const MERMAID_CONTENT_TYPE: &str = "text/plain; charset=utf-8";
+
+fn metadata_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+async fn build_diagram(
+    client: &Client,
+    name: String,
+    node_ids: Vec<uuid::Uuid>,
+    edges: Vec<Edge>,
+    params: &DiagramParams,
+) -> Result<Diagram, NapkinError> {
+    let mut node_text: HashMap<uuid::Uuid, String> = HashMap::new();
+    if let Some(key) = &params.node_key {
+        for metadata in db::node_metadata::get_node_metadata_named(client, &node_ids, key).await? {
+            node_text.insert(metadata.owner_id, metadata_text(&metadata.value));
+        }
+    }
+    let mut edge_text: HashMap<uuid::Uuid, String> = HashMap::new();
+    if let Some(key) = &params.edge_key {
+        let edge_ids: Vec<uuid::Uuid> = edges.iter().filter_map(|edge| edge.id).collect();
+        for metadata in db::edge_metadata::get_edge_metadata_named(client, &edge_ids, key).await? {
+            edge_text.insert(metadata.owner_id, metadata_text(&metadata.value));
+        }
+    }
+
+    Ok(Diagram {
+        name,
+        nodes: node_ids
+            .into_iter()
+            .map(|id| DiagramNode { id, text: node_text.remove(&id).unwrap_or_else(|| id.to_string()) })
+            .collect(),
+        edges: edges
+            .into_iter()
+            .map(|edge| DiagramEdge {
+                source: edge.source,
+                target: edge.target,
+                text: edge.id.and_then(|id| edge_text.remove(&id)).or(edge.label),
+                directed: edge.directed,
+            })
+            .collect(),
+    })
+}
+
+async fn project_diagram(client: &Client, id: &uuid::Uuid, params: &DiagramParams) -> Result<Diagram, NapkinError> {
+    let project = db::projects::get_project(client, id).await?;
+    let node_ids = db::exports::get_nodes(client, id).await?.into_iter().filter_map(|node| node.id).collect();
+    let edges = db::exports::get_edges(client, id).await?;
+
+    build_diagram(client, format!("{}/{}", project.scope, project.name), node_ids, edges, params).await
+}
+
+async fn neighborhood_diagram(client: &Client, id: &uuid::Uuid, neighbors: &NeighborParams, params: &DiagramParams) -> Result<Diagram, NapkinError> {
+    let neighborhood = load_neighborhood(client, id, neighbors).await?;
+    let node_ids = neighborhood.nodes.iter().map(|node| node.id).collect();
+
+    build_diagram(client, id.to_string(), node_ids, neighborhood.edges, params).await
+}
+
+#[get("/{id}/export/dot")]
+pub async fn export_project_dot(id: web::Path<uuid::Uuid>, params: web::Query<DiagramParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let diagram = project_diagram(&client, &id, &params).await?;
+
+    Ok(HttpResponse::Ok().content_type(DOT_CONTENT_TYPE).body(formats::dot::write_dot(&diagram)))
+}
+
+#[get("/{id}/export/mermaid")]
+pub async fn export_project_mermaid(id: web::Path<uuid::Uuid>, params: web::Query<DiagramParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let diagram = project_diagram(&client, &id, &params).await?;
+
+    Ok(HttpResponse::Ok().content_type(MERMAID_CONTENT_TYPE).body(formats::mermaid::write_mermaid(&diagram)))
+}
+
+#[get("/{id}/neighbors/dot")]
+pub async fn get_node_neighbors_dot(
+    id: web::Path<uuid::Uuid>,
+    neighbors: web::Query<NeighborParams>,
+    params: web::Query<DiagramParams>,
+    db_pool: web::Data<Pool>,
+) -> Result<impl Responder, NapkinError> {
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let diagram = neighborhood_diagram(&client, &id, &neighbors, &params).await?;
+
+    Ok(HttpResponse::Ok().content_type(DOT_CONTENT_TYPE).body(formats::dot::write_dot(&diagram)))
+}
+
+#[get("/{id}/neighbors/mermaid")]
+pub async fn get_node_neighbors_mermaid(
+    id: web::Path<uuid::Uuid>,
+    neighbors: web::Query<NeighborParams>,
+    params: web::Query<DiagramParams>,
+    db_pool: web::Data<Pool>,
+) -> Result<impl Responder, NapkinError> {
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let diagram = neighborhood_diagram(&client, &id, &neighbors, &params).await?;
+
+    Ok(HttpResponse::Ok().content_type(MERMAID_CONTENT_TYPE).body(formats::mermaid::write_mermaid(&diagram)))
+}
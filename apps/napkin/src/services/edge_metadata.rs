@@ -1,15 +1,19 @@
-use actix_web::{ get, post, put, delete, web, Responder, Result };
+use actix_web::{ get, post, put, delete, web, HttpRequest, Responder, Result };
 use deadpool_postgres::{Client, Pool};
 
+use crate::models::pagination::{key_cursor, Listing, PageParams};
 use crate::models::edge_metadata::{EdgeMetadata, EdgeMetadataReqObj, EdgeMetadataUpdate};
 use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
+use crate::services::metadata_schemas::validate_metadata;
+use crate::services::preconditions::{check_precondition, if_match, versioned, with_etag};
 use crate::db;
 
 #[get("")]
-pub async fn get_edge_metadata(db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn get_edge_metadata(page: web::Query<PageParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
-    let edge_metadata = db::edge_metadata::get_edge_metadata(&client).await?;
-    Ok(web::Json(edge_metadata))
+    let edge_metadata = db::edge_metadata::get_edge_metadata(&client, &page.after_key()?, limit).await?;
+    Ok(web::Json(Listing::new(edge_metadata, limit, |metadata| key_cursor(&metadata.owner_id, &metadata.name))))
 }
 
 #[post("")]
@@ -18,8 +22,6 @@ pub async fn post_edge_metadata(body: web::Json<EdgeMetadataReqObj>, db_pool: we
 
     let owner_id_uuid = uuid::Uuid::parse_str(&body.owner_id);
 
-    // TODO: Check if owner_id exists
-
     if owner_id_uuid.is_err() {
         return Err(NapkinError {
             code: "EDGE_NO_ID",
@@ -31,16 +33,20 @@ pub async fn post_edge_metadata(body: web::Json<EdgeMetadataReqObj>, db_pool: we
         owner_id: owner_id_uuid.unwrap(),
         name: body.name.clone(),
         value: body.value.clone(),
+        version: 0,
     };
 
+    let schema = db::metadata_schemas::get_edge_metadata_schema(&client, &edge_metadata_info.owner_id, &edge_metadata_info.name).await?;
+    validate_metadata(schema.as_ref(), &edge_metadata_info.value)?;
+
     let new_edge_metadata = db::edge_metadata::add_edge_metadata(&client, edge_metadata_info).await?;
+    let version = new_edge_metadata.version;
 
-    Ok(web::Json(new_edge_metadata))
+    Ok(with_etag(new_edge_metadata, version))
 }
 
 #[get("/{owner_id}")]
-pub async fn get_edge_metadata_singleton(owner_id: web::Path<String>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
-    println!("{}", owner_id);
+pub async fn get_edge_metadata_singleton(owner_id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
     let edge_metadata = db::edge_metadata::get_edge_metadata_singleton(&client, &owner_id).await?;
@@ -49,20 +55,21 @@ pub async fn get_edge_metadata_singleton(owner_id: web::Path<String>, db_pool: w
 }
 
 #[get("/{owner_id}/{name}")]
-pub async fn get_edge_metadata_singleton_key(param: web::Path<(String, String)>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn get_edge_metadata_singleton_key(request: HttpRequest, param: web::Path<(uuid::Uuid, String)>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
     let (owner_id, name) = param.into_inner();
-    println!("{} {}", owner_id, name);
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
     let edge_metadata_key = db::edge_metadata::get_edge_metadata_singleton_key(&client, &owner_id, &name).await?;
+    let version = edge_metadata_key.version;
 
-    Ok(web::Json(edge_metadata_key))
+    Ok(versioned(&request, edge_metadata_key, version))
 }
 
 #[put("/{owner_id}/{name}")]
-pub async fn update_edge_metadata(param: web::Path<(String, String)>, body: web::Json<EdgeMetadataUpdate>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn update_edge_metadata(request: HttpRequest, param: web::Path<(uuid::Uuid, String)>, body: web::Json<EdgeMetadataUpdate>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
     let (owner_id, name) = param.into_inner();
     let edge_info: EdgeMetadataUpdate = body.into_inner();
+    let if_match = if_match(&request);
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
     // Retrieve the existing edge metadata to update only provided fields
@@ -80,19 +87,27 @@ pub async fn update_edge_metadata(param: web::Path<(String, String)>, body: web:
             Some(v) => v,
             None => existing_edge.value,
         },
+        version: existing_edge.version,
     };
 
-    let updated_edge = db::edge_metadata::update_edge_metadata(&client, &owner_id, &name, updated_edge_info).await?;
+    let schema = db::metadata_schemas::get_edge_metadata_schema(&client, &updated_edge_info.owner_id, &updated_edge_info.name).await?;
+    validate_metadata(schema.as_ref(), &updated_edge_info.value)?;
+
+    let updated_edge = db::edge_metadata::update_edge_metadata(&client, &owner_id, &name, updated_edge_info, &if_match).await;
+    let updated_edge = check_precondition(updated_edge, &if_match, db::edge_metadata::get_edge_metadata_singleton_key(&client, &owner_id, &name)).await?;
+    let version = updated_edge.version;
 
-    Ok(web::Json(updated_edge))
+    Ok(with_etag(updated_edge, version))
 }
 
 #[delete("/{owner_id}/{name}")]
-pub async fn delete_edge_metadata(param: web::Path<(String, String)>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn delete_edge_metadata(request: HttpRequest, param: web::Path<(uuid::Uuid, String)>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
     let (owner_id, name) = param.into_inner();
+    let if_match = if_match(&request);
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
-    let deleted_edge = db::edge_metadata::delete_edge(&client, &owner_id, &name).await?;
+    let deleted_edge = db::edge_metadata::delete_edge_metadata(&client, &owner_id, &name, &if_match).await;
+    let deleted_edge = check_precondition(deleted_edge, &if_match, db::edge_metadata::get_edge_metadata_singleton_key(&client, &owner_id, &name)).await?;
 
     Ok(web::Json(deleted_edge))
 }
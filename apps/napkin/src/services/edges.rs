@@ -1,36 +1,42 @@
-use actix_web::{ get, post, put, delete, web, Responder, Result };
+use actix_web::{ get, post, put, delete, web, HttpRequest, Responder, Result };
 use deadpool_postgres::{Client, Pool};
 
-use crate::models::edges::{Edge, EdgeReqObj};
+use crate::models::pagination::{Listing, PageParams};
+use crate::models::edges::{Edge, EdgeParams, EdgeReqObj};
 use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
+use crate::services::projects::resolve_project;
+use crate::services::preconditions::{check_precondition, if_match, precondition_failed, versioned, with_etag};
 use crate::db;
 
 #[get("")]
-pub async fn get_edges(db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
-    // let edges: Vec<Edge> = Vec::new();
+pub async fn get_edges(page: web::Query<PageParams>, params: web::Query<EdgeParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
-    let edges = db::edges::get_edges(&client).await?;
-    Ok(web::Json(edges))
+    let edges = db::edges::get_edges(&client, &params.label, &page.after_id()?, limit).await?;
+    Ok(web::Json(Listing::new(edges, limit, |edge| {
+        edge.id.map(|id| id.to_string()).unwrap_or_default()
+    })))
 }
 
-#[post("")]
-pub async fn post_edge(body: web::Json<EdgeReqObj>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
-    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+// Path costs add weights up, so NaN and infinities are refused wherever an edge is written
+pub fn finite_weight(weight: Option<f64>) -> bool {
+    weight.is_none_or(f64::is_finite)
+}
+
+fn edge_bad_weight(weight: Option<f64>) -> NapkinError {
+    NapkinError {
+        code: "EDGE_BAD_WEIGHT",
+        message: "Edge weight must be a finite number",
+        root: NapkinErrorRoot::Unprocessable(vec![format!("weight: `{}` is not a finite number", weight.unwrap_or_default())]),
+    }
+}
 
-    // let embedding: pgvector::Vector = pgvector::Vector::from(body.embedding.clone());
-    let project_uuid = uuid::Uuid::parse_str(&body.project);
+// Builds the edge to insert; `project` is set when posting under `/project/{id}/edges`
+pub fn edge_from_req_obj(body: &EdgeReqObj, project: Option<uuid::Uuid>) -> Result<Edge, NapkinError> {
+    let project = resolve_project(project, &body.project)?;
     let source_uuid = uuid::Uuid::parse_str(&body.source);
     let target_uuid = uuid::Uuid::parse_str(&body.target);
 
-    // TODO: Check if project exists
-
-    if project_uuid.is_err() {
-        return Err(NapkinError {
-            code: "EDGE_NO_ID",
-            message: "Project with ID {id} Not Found",
-            root: NapkinErrorRoot::NotFound,
-        });
-    }
     if source_uuid.is_err() {
         return Err(NapkinError {
             code: "EDGE_NO_ID",
@@ -45,42 +51,82 @@ pub async fn post_edge(body: web::Json<EdgeReqObj>, db_pool: web::Data<Pool>) ->
             root: NapkinErrorRoot::NotFound,
         });
     }
-    let edge_info = Edge {
-        id: body.id.clone(),
-        project: project_uuid.unwrap(),
+    if !finite_weight(body.weight) {
+        return Err(edge_bad_weight(body.weight));
+    }
+
+    Ok(Edge {
+        id: None,
+        project,
         source: source_uuid.unwrap(),
         target: target_uuid.unwrap(),
-    };
+        label: body.label.clone(),
+        directed: body.directed.unwrap_or(true),
+        weight: body.weight,
+        version: 0,
+        deleted_at: None,
+    })
+}
+
+#[post("")]
+pub async fn post_edge(body: web::Json<EdgeReqObj>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let edge_info = edge_from_req_obj(&body, None)?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
     let new_edge = db::edges::add_edge(&client, edge_info).await?;
+    let version = new_edge.version;
 
-    Ok(web::Json(new_edge))
+    Ok(with_etag(new_edge, version))
 }
 
 #[get("/{id}")]
-pub async fn get_edge(id: web::Path<String>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn get_edge(request: HttpRequest, id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
     let edge = db::edges::get_edge(&client, &id).await?;
+    let version = edge.version;
+
+    Ok(versioned(&request, edge, version))
+}
 
-    Ok(web::Json(edge))
+// An update that matched no row of a live edge either lost to a concurrent writer
+// or named a source or target that is missing or in the trash
+async fn written_edge(client: &Client, id: &uuid::Uuid, written: Result<Edge, NapkinError>, if_match: &Option<Vec<i64>>) -> Result<Edge, NapkinError> {
+    match written {
+        Err(NapkinError { root: NapkinErrorRoot::NotFound, .. }) => {
+            let current = db::edges::get_edge(client, id).await?;
+            match if_match {
+                Some(versions) if !versions.contains(&current.version) => Err(precondition_failed()),
+                _ => Err(db::edges::edge_endpoint_not_found()),
+            }
+        }
+        written => written,
+    }
 }
 
 #[put("/{id}")]
-pub async fn update_edge(id: web::Path<String>, body: web::Json<Edge>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn update_edge(request: HttpRequest, id: web::Path<uuid::Uuid>, body: web::Json<Edge>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
     let edge_info: Edge = body.into_inner();
+    if !finite_weight(edge_info.weight) {
+        return Err(edge_bad_weight(edge_info.weight));
+    }
+    let if_match = if_match(&request);
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
-    let updated_edge = db::edges::update_edge(&client, &id, edge_info).await?;
+    let updated_edge = db::edges::update_edge(&client, &id, edge_info, &if_match).await;
+    let updated_edge = written_edge(&client, &id, updated_edge, &if_match).await?;
+    let version = updated_edge.version;
 
-    Ok(web::Json(updated_edge))
+    Ok(with_etag(updated_edge, version))
 }
 
 #[delete("/{id}")]
-pub async fn delete_edge(id: web::Path<String>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn delete_edge(request: HttpRequest, id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let if_match = if_match(&request);
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
-    let deleted_edge = db::edges::delete_edge(&client, &id).await?;
+    let deleted_edge = db::edges::delete_edge(&client, &id, &if_match).await;
+    let deleted_edge = check_precondition(deleted_edge, &if_match, db::edges::get_edge(&client, &id)).await?;
 
     Ok(web::Json(deleted_edge))
 }
\ No newline at end of file
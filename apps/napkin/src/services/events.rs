@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use actix_web::{ get, web, HttpResponse, Responder };
+use actix_web::http::header::{CacheControl, CacheDirective};
+use futures_util::stream;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::bus::EventBus;
+use crate::models::events::EventParams;
+
+// Comment frames keep idle connections from being dropped by proxies
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+// Server-Sent Events: one `data:` frame per change as a `ChangeEvent`, or a `lagged` event
+// when this subscriber fell too far behind and should re-fetch what it mirrors
+#[get("")]
+pub async fn get_events(params: web::Query<EventParams>, bus: web::Data<EventBus>) -> impl Responder {
+    let state = (bus.subscribe(), params.into_inner());
+    let frames = stream::unfold(state, |(mut receiver, params)| async move {
+        let frame = loop {
+            match actix_web::rt::time::timeout(KEEP_ALIVE, receiver.recv()).await {
+                Ok(Ok(event)) if params.matches(&event) => {
+                    break format!("data: {}\n\n", serde_json::to_string(&event).unwrap_or_default());
+                }
+                Ok(Ok(_)) => continue,
+                Ok(Err(RecvError::Lagged(missed))) => break format!("event: lagged\ndata: {{\"missed\":{}}}\n\n", missed),
+                Ok(Err(RecvError::Closed)) => return None,
+                Err(_) => break ": keep-alive\n\n".to_string(),
+            }
+        };
+        Some((Ok::<_, actix_web::Error>(web::Bytes::from(frame)), (receiver, params)))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(CacheControl(vec![CacheDirective::NoCache]))
+        .streaming(frames)
+}
@@ -0,0 +1,319 @@
+use std::collections::{HashMap, HashSet};
+
+use actix_web::{ get, post, web, HttpResponse, Responder, Result };
+use deadpool_postgres::{Client, Pool};
+use tokio_postgres::IsolationLevel;
+
+use crate::models::artifacts::ARTIFACT_DIMENSIONS;
+use crate::models::bulk::{EdgeColumns, MetadataColumns, NodeColumns};
+use crate::models::exports::{ImportParams, NewProjectParams, ProjectExport, EXPORT_FORMAT, EXPORT_VERSION};
+use crate::models::projects::Project;
+use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
+use crate::{db, formats};
+
+// Exports carry 1024-dimension embeddings, so they dwarf bulk documents
+pub const IMPORT_PAYLOAD_LIMIT: usize = 256 * 1024 * 1024;
+
+async fn load_document(client: &mut Client, id: &uuid::Uuid) -> Result<ProjectExport, NapkinError> {
+    // One snapshot, so rows written mid-export cannot leave dangling references
+    let transaction = client
+        .build_transaction()
+        .isolation_level(IsolationLevel::RepeatableRead)
+        .read_only(true)
+        .start()
+        .await?;
+
+    let document = ProjectExport {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        project: db::projects::get_project(&transaction, id).await?,
+        nodes: db::exports::get_nodes(&transaction, id).await?,
+        edges: db::exports::get_edges(&transaction, id).await?,
+        node_metadata: db::exports::get_node_metadata(&transaction, id).await?,
+        edge_metadata: db::exports::get_edge_metadata(&transaction, id).await?,
+        artifacts: db::exports::get_artifacts(&transaction, id).await?,
+        artifact_metadata: db::exports::get_artifact_metadata(&transaction, id).await?,
+        metadata_schemas: db::metadata_schemas::get_all_metadata_schemas(&transaction, id).await?,
+    };
+
+    transaction.commit().await?;
+
+    Ok(document)
+}
+
+#[get("/{id}/export")]
+pub async fn export_project(id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let mut client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let document = load_document(&mut client, &id).await?;
+
+    Ok(web::Json(document))
+}
+
+#[get("/{id}/export/graphml")]
+pub async fn export_project_graphml(id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let mut client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let document = load_document(&mut client, &id).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/graphml+xml")
+        .body(formats::graphml::write_graphml(&document)))
+}
+
+// Every reference in `document` that does not resolve within the document itself
+fn dangling_references(document: &ProjectExport) -> Vec<String> {
+    let mut violations = Vec::new();
+    let node_ids: HashSet<uuid::Uuid> = document.nodes.iter().filter_map(|node| node.id).collect();
+    let edge_ids: HashSet<uuid::Uuid> = document.edges.iter().filter_map(|edge| edge.id).collect();
+    let artifact_ids: HashSet<uuid::Uuid> = document.artifacts.iter().map(|artifact| artifact.node_id).collect();
+
+    if document.project.id.is_none() {
+        violations.push("project/id: missing".to_string());
+    }
+    for (index, node) in document.nodes.iter().enumerate() {
+        if node.id.is_none() {
+            violations.push(format!("nodes/{}/id: missing", index));
+        }
+    }
+    for (index, edge) in document.edges.iter().enumerate() {
+        if edge.id.is_none() {
+            violations.push(format!("edges/{}/id: missing", index));
+        }
+        for (field, node_id) in [("source", edge.source), ("target", edge.target)] {
+            if !node_ids.contains(&node_id) {
+                violations.push(format!("edges/{}/{}: node `{}` is not in the document", index, field, node_id));
+            }
+        }
+    }
+    let owners = [
+        ("node_metadata", &node_ids, document.node_metadata.iter().map(|metadata| metadata.owner_id).collect::<Vec<_>>()),
+        ("edge_metadata", &edge_ids, document.edge_metadata.iter().map(|metadata| metadata.owner_id).collect()),
+        ("artifacts", &node_ids, document.artifacts.iter().map(|artifact| artifact.node_id).collect()),
+        ("artifact_metadata", &artifact_ids, document.artifact_metadata.iter().map(|metadata| metadata.owner_id).collect()),
+    ];
+    for (section, known, owner_ids) in owners {
+        for (index, owner_id) in owner_ids.iter().enumerate() {
+            if !known.contains(owner_id) {
+                violations.push(format!("{}/{}: owner `{}` is not in the document", section, index, owner_id));
+            }
+        }
+    }
+
+    violations
+}
+
+// Every repeat of a key that the import stores under a primary key, at `{section}/{index}{field}`
+fn repeated_keys(section: &str, field: &str, keys: Vec<Option<String>>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    keys.into_iter()
+        .enumerate()
+        .filter_map(|(index, key)| Some((index, key?)))
+        .filter(|(_, key)| !seen.insert(key.clone()))
+        .map(|(index, key)| format!("{}/{}{}: `{}` is used more than once", section, index, field, key))
+        .collect()
+}
+
+fn duplicate_keys(document: &ProjectExport) -> Vec<String> {
+    let metadata_key = |owner_id: &uuid::Uuid, name: &str| Some(format!("{}:{}", owner_id, name));
+    [
+        repeated_keys("nodes", "/id", document.nodes.iter().map(|node| node.id.map(|id| id.to_string())).collect()),
+        repeated_keys("edges", "/id", document.edges.iter().map(|edge| edge.id.map(|id| id.to_string())).collect()),
+        repeated_keys("artifacts", "/node_id", document.artifacts.iter().map(|artifact| Some(artifact.node_id.to_string())).collect()),
+        repeated_keys("node_metadata", "", document.node_metadata.iter().map(|metadata| metadata_key(&metadata.owner_id, &metadata.name)).collect()),
+        repeated_keys("edge_metadata", "", document.edge_metadata.iter().map(|metadata| metadata_key(&metadata.owner_id, &metadata.name)).collect()),
+        repeated_keys("artifact_metadata", "", document.artifact_metadata.iter().map(|metadata| metadata_key(&metadata.owner_id, &metadata.name)).collect()),
+        repeated_keys("metadata_schemas", "/name", document.metadata_schemas.iter().map(|schema| Some(schema.name.clone())).collect()),
+    ]
+    .concat()
+}
+
+// Embeddings the `vector(1024)` column would refuse
+fn bad_embeddings(document: &ProjectExport) -> Vec<String> {
+    let mut violations = Vec::new();
+    for (index, artifact) in document.artifacts.iter().enumerate() {
+        let embedding = artifact.embedding.as_slice();
+        if embedding.len() != ARTIFACT_DIMENSIONS {
+            violations.push(format!(
+                "artifacts/{}/embedding: node `{}` has {} dimensions, not {}",
+                index, artifact.node_id, embedding.len(), ARTIFACT_DIMENSIONS
+            ));
+        } else if embedding.iter().any(|value| !value.is_finite()) {
+            violations.push(format!("artifacts/{}/embedding: node `{}` has a value that is not a finite number", index, artifact.node_id));
+        }
+    }
+    violations
+}
+
+// Restores the document verbatim: metadata is not re-validated against schemas,
+// since values may predate a later schema change
+async fn store_document(client: &mut Client, document: ProjectExport, params: &ImportParams) -> Result<Project, NapkinError> {
+    if document.format != EXPORT_FORMAT || !(1..=EXPORT_VERSION).contains(&document.version) {
+        return Err(NapkinError {
+            code: "EXPORT_UNSUPPORTED",
+            message: "Document is not a napkin project export of a supported version",
+            root: NapkinErrorRoot::BadRequest,
+        });
+    }
+    let violations = [dangling_references(&document), duplicate_keys(&document), bad_embeddings(&document)].concat();
+    if !violations.is_empty() {
+        return Err(NapkinError {
+            code: "EXPORT_INVALID",
+            message: "Export document is inconsistent, nothing was imported",
+            root: NapkinErrorRoot::Unprocessable(violations),
+        });
+    }
+    let fresh_ids = params.fresh_ids.unwrap_or(false);
+
+    let transaction = client.transaction().await?;
+
+    // Exported ID -> imported ID; the identity unless `fresh_ids` is set
+    let old_node_ids: Vec<uuid::Uuid> = document.nodes.iter().filter_map(|node| node.id).collect();
+    let old_edge_ids: Vec<uuid::Uuid> = document.edges.iter().filter_map(|edge| edge.id).collect();
+    let (project_id, new_node_ids, new_edge_ids) = if fresh_ids {
+        (
+            db::bulk::generate_ids(&transaction, 1).await?[0],
+            db::bulk::generate_ids(&transaction, old_node_ids.len() as i64).await?,
+            db::bulk::generate_ids(&transaction, old_edge_ids.len() as i64).await?,
+        )
+    } else {
+        (document.project.id.unwrap_or_default(), old_node_ids.clone(), old_edge_ids.clone())
+    };
+    let node_map: HashMap<uuid::Uuid, uuid::Uuid> = old_node_ids.into_iter().zip(new_node_ids).collect();
+    let edge_map: HashMap<uuid::Uuid, uuid::Uuid> = old_edge_ids.into_iter().zip(new_edge_ids).collect();
+
+    let project = db::exports::add_project(
+        &transaction,
+        &Project {
+            id: Some(project_id),
+            scope: params.scope.clone().unwrap_or(document.project.scope),
+            name: params.name.clone().unwrap_or(document.project.name),
+            version: 0,
+            deleted_at: None,
+        },
+    )
+    .await?;
+
+    let mut nodes = NodeColumns::default();
+    for node in document.nodes.iter() {
+        nodes.ids.push(node_map[&node.id.unwrap_or_default()]);
+        nodes.labels.push(serde_json::json!(node.labels));
+    }
+    let mut edges = EdgeColumns::default();
+    for edge in document.edges.iter() {
+        edges.ids.push(edge_map[&edge.id.unwrap_or_default()]);
+        edges.sources.push(node_map[&edge.source]);
+        edges.targets.push(node_map[&edge.target]);
+        edges.labels.push(edge.label.clone());
+        edges.directed.push(edge.directed);
+        edges.weights.push(edge.weight);
+    }
+    let mut node_metadata = MetadataColumns::default();
+    for metadata in document.node_metadata {
+        node_metadata.owner_ids.push(node_map[&metadata.owner_id]);
+        node_metadata.names.push(metadata.name);
+        node_metadata.values.push(metadata.value);
+    }
+    let mut edge_metadata = MetadataColumns::default();
+    for metadata in document.edge_metadata {
+        edge_metadata.owner_ids.push(edge_map[&metadata.owner_id]);
+        edge_metadata.names.push(metadata.name);
+        edge_metadata.values.push(metadata.value);
+    }
+    let mut artifact_metadata = MetadataColumns::default();
+    for metadata in document.artifact_metadata {
+        artifact_metadata.owner_ids.push(node_map[&metadata.owner_id]);
+        artifact_metadata.names.push(metadata.name);
+        artifact_metadata.values.push(metadata.value);
+    }
+    let (artifact_ids, embeddings): (Vec<uuid::Uuid>, Vec<pgvector::Vector>) = document
+        .artifacts
+        .into_iter()
+        .map(|artifact| (node_map[&artifact.node_id], artifact.embedding))
+        .unzip();
+
+    // The project's own `created` change is announced; its contents are summed up in one `imported`
+    db::bulk::quiet_changes(&transaction).await?;
+    let counts = serde_json::json!({
+        "nodes": db::bulk::add_nodes(&transaction, &project_id, &nodes).await?,
+        "edges": db::bulk::add_edges(&transaction, &project_id, &edges).await?,
+        "node_metadata": db::bulk::add_node_metadata(&transaction, &node_metadata).await?,
+        "edge_metadata": db::bulk::add_edge_metadata(&transaction, &edge_metadata).await?,
+    });
+    if !artifact_ids.is_empty() {
+        db::exports::add_artifacts(&transaction, &artifact_ids, &embeddings).await?;
+    }
+    db::bulk::add_artifact_metadata(&transaction, &artifact_metadata).await?;
+    db::exports::add_metadata_schemas(&transaction, &project_id, &document.metadata_schemas).await?;
+    db::bulk::notify_import(&transaction, &project_id, &counts).await?;
+
+    transaction.commit().await?;
+
+    Ok(project)
+}
+
+#[post("")]
+pub async fn import_project(params: web::Query<ImportParams>, body: web::Json<ProjectExport>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let mut client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let project = store_document(&mut client, body.into_inner(), &params).await?;
+
+    Ok(web::Json(project))
+}
+
+// Always creates a new project, as GraphML IDs are arbitrary strings
+#[post("/graphml")]
+pub async fn import_project_graphml(params: web::Query<NewProjectParams>, body: String, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let document = formats::graphml::read_graphml(&body)?;
+    let params = params.into_inner();
+
+    let mut client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let project = store_document(
+        &mut client,
+        document,
+        &ImportParams { fresh_ids: Some(true), scope: Some(params.scope), name: Some(params.name) },
+    )
+    .await?;
+
+    Ok(web::Json(project))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NODE: &str = "01a14be3-5525-9ee4-7439-8b7ce87a980c";
+    const OTHER_NODE: &str = "01a14be3-5525-9ee4-7439-8b7ce87a980d";
+    const EDGE: &str = "01a14be3-5525-9ee4-7439-8b7ce87a980e";
+
+    fn document(nodes: &[&str], edges: &[&str], embedding_lengths: &[usize]) -> ProjectExport {
+        serde_json::from_value(serde_json::json!({
+            "format": EXPORT_FORMAT,
+            "version": EXPORT_VERSION,
+            "project": { "id": NODE, "scope": "s", "name": "n" },
+            "nodes": nodes.iter().map(|id| serde_json::json!({ "id": id, "project": NODE })).collect::<Vec<_>>(),
+            "edges": edges.iter().map(|id| serde_json::json!({ "id": id, "project": NODE, "source": NODE, "target": NODE, "directed": true })).collect::<Vec<_>>(),
+            "node_metadata": [],
+            "edge_metadata": [],
+            "artifacts": embedding_lengths.iter().map(|length| serde_json::json!({ "node_id": NODE, "embedding": vec![0.5; *length] })).collect::<Vec<_>>(),
+            "artifact_metadata": [],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn repeated_ids_are_reported_with_the_id() {
+        let violations = duplicate_keys(&document(&[NODE, OTHER_NODE, NODE], &[EDGE, EDGE], &[]));
+        assert_eq!(
+            violations,
+            [
+                format!("nodes/2/id: `{}` is used more than once", NODE),
+                format!("edges/1/id: `{}` is used more than once", EDGE),
+            ]
+        );
+        assert!(duplicate_keys(&document(&[NODE, OTHER_NODE], &[EDGE], &[])).is_empty());
+    }
+
+    #[test]
+    fn embeddings_must_fit_the_vector_column() {
+        let violations = bad_embeddings(&document(&[NODE], &[], &[ARTIFACT_DIMENSIONS, 3]));
+        assert_eq!(violations, [format!("artifacts/1/embedding: node `{}` has 3 dimensions, not 1024", NODE)]);
+    }
+}
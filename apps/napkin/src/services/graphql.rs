@@ -0,0 +1,27 @@
+use actix_web::{ get, post, web, HttpResponse, Responder, Result };
+use actix_web::http::header::ContentType;
+use async_graphql::http::GraphiQLSource;
+use deadpool_postgres::Pool;
+
+use crate::schema::{loaders::NapkinLoader, NapkinSchema};
+
+#[post("")]
+pub async fn post_graphql(
+    body: web::Json<async_graphql::Request>,
+    schema: web::Data<NapkinSchema>,
+    db_pool: web::Data<Pool>,
+) -> Result<impl Responder> {
+    let pool = db_pool.get_ref().clone();
+    // A fresh loader per request, so cached rows never outlive the request
+    let loader = NapkinLoader::new(pool.clone());
+    let request = body.into_inner().data(pool).data(loader);
+
+    Ok(web::Json(schema.execute(request).await))
+}
+
+#[get("")]
+pub async fn get_graphiql() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type(ContentType::html())
+        .body(GraphiQLSource::build().endpoint("/graphql").finish())
+}
@@ -0,0 +1,116 @@
+use actix_web::{ get, post, put, delete, web, HttpRequest, Responder, Result };
+use deadpool_postgres::{Client, Pool};
+use jsonschema::JSONSchema;
+
+use crate::models::pagination::{Listing, PageParams};
+use crate::models::metadata_schemas::{MetadataSchema, MetadataSchemaReqObj, MetadataSchemaUpdate};
+use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
+use crate::services::preconditions::{check_precondition, if_match, versioned, with_etag};
+use crate::db;
+
+pub fn compile_schema(schema: &serde_json::Value) -> Result<JSONSchema, NapkinError> {
+    JSONSchema::compile(schema).map_err(|_| NapkinError {
+        code: "METADATA_SCHEMA_INVALID",
+        message: "`schema` is not a valid JSON Schema",
+        root: NapkinErrorRoot::BadRequest,
+    })
+}
+
+// Every violation of `compiled` by `value`, each prefixed with `path`
+pub fn schema_violations(compiled: &JSONSchema, path: &str, value: &serde_json::Value) -> Vec<String> {
+    match compiled.validate(value) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .map(|error| format!("{}{}: {}", path, error.instance_path, error))
+            .collect(),
+    }
+}
+
+// Rejects `value` with a 422 listing every violation when `schema` is registered
+pub fn validate_metadata(schema: Option<&MetadataSchema>, value: &serde_json::Value) -> Result<(), NapkinError> {
+    let Some(schema) = schema else {
+        return Ok(());
+    };
+    let compiled = compile_schema(&schema.schema)?;
+    let violations = schema_violations(&compiled, &format!("{}/value", schema.name), value);
+
+    if !violations.is_empty() {
+        return Err(NapkinError {
+            code: "METADATA_SCHEMA_VIOLATION",
+            message: "Metadata value does not match the project's schema for this name",
+            root: NapkinErrorRoot::Unprocessable(violations),
+        });
+    }
+
+    Ok(())
+}
+
+#[get("/{id}/schemas")]
+pub async fn get_metadata_schemas(id: web::Path<uuid::Uuid>, page: web::Query<PageParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    db::projects::get_project(&client, &id).await?;
+
+    let schemas = db::metadata_schemas::get_metadata_schemas(&client, &id, &page.after, limit).await?;
+    Ok(web::Json(Listing::new(schemas, limit, |schema| schema.name.clone())))
+}
+
+#[post("/{id}/schemas")]
+pub async fn post_metadata_schema(id: web::Path<uuid::Uuid>, body: web::Json<MetadataSchemaReqObj>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let body = body.into_inner();
+    compile_schema(&body.schema)?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    db::projects::get_project(&client, &id).await?;
+
+    let metadata_schema_info = MetadataSchema {
+        project: *id,
+        name: body.name,
+        schema: body.schema,
+        version: 0,
+    };
+
+    let new_schema = db::metadata_schemas::add_metadata_schema(&client, metadata_schema_info).await?;
+    let version = new_schema.version;
+
+    Ok(with_etag(new_schema, version))
+}
+
+#[get("/{id}/schemas/{name}")]
+pub async fn get_metadata_schema(request: HttpRequest, param: web::Path<(uuid::Uuid, String)>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let (id, name) = param.into_inner();
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let schema = db::metadata_schemas::get_metadata_schema(&client, &id, &name).await?;
+    let version = schema.version;
+
+    Ok(versioned(&request, schema, version))
+}
+
+// Existing metadata is not re-validated; the schema applies to later writes
+#[put("/{id}/schemas/{name}")]
+pub async fn update_metadata_schema(request: HttpRequest, param: web::Path<(uuid::Uuid, String)>, body: web::Json<MetadataSchemaUpdate>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let (id, name) = param.into_inner();
+    compile_schema(&body.schema)?;
+    let if_match = if_match(&request);
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let updated_schema = db::metadata_schemas::update_metadata_schema(&client, &id, &name, &body.schema, &if_match).await;
+    let updated_schema = check_precondition(updated_schema, &if_match, db::metadata_schemas::get_metadata_schema(&client, &id, &name)).await?;
+    let version = updated_schema.version;
+
+    Ok(with_etag(updated_schema, version))
+}
+
+#[delete("/{id}/schemas/{name}")]
+pub async fn delete_metadata_schema(request: HttpRequest, param: web::Path<(uuid::Uuid, String)>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let (id, name) = param.into_inner();
+    let if_match = if_match(&request);
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let deleted_schema = db::metadata_schemas::delete_metadata_schema(&client, &id, &name, &if_match).await;
+    let deleted_schema = check_precondition(deleted_schema, &if_match, db::metadata_schemas::get_metadata_schema(&client, &id, &name)).await?;
+
+    Ok(web::Json(deleted_schema))
+}
@@ -2,4 +2,18 @@ pub mod projects;
 pub mod nodes;
 pub mod edges;
 pub mod node_metadata;
-pub mod edge_metadata;
\ No newline at end of file
+pub mod edge_metadata;
+pub mod artifacts;
+pub mod artifact_metadata;
+pub mod paths;
+pub mod metadata_schemas;
+pub mod bulk;
+pub mod exports;
+pub mod diagrams;
+pub mod csv_imports;
+pub mod rdf;
+pub mod queries;
+pub mod graphql;
+pub mod events;
+pub mod preconditions;
+pub mod trash;
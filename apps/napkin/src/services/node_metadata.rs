@@ -1,15 +1,19 @@
-use actix_web::{ get, post, put, delete, web, Responder, Result };
+use actix_web::{ get, post, put, delete, web, HttpRequest, Responder, Result };
 use deadpool_postgres::{Client, Pool};
 
+use crate::models::pagination::{key_cursor, Listing, PageParams};
 use crate::models::node_metadata::{NodeMetadata, NodeMetadataReqObj};
 use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
+use crate::services::metadata_schemas::validate_metadata;
+use crate::services::preconditions::{check_precondition, if_match, versioned, with_etag};
 use crate::db;
 
 #[get("")]
-pub async fn get_node_metadata(db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn get_node_metadata(page: web::Query<PageParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
-    let node_metadata = db::node_metadata::get_node_metadata(&client).await?;
-    Ok(web::Json(node_metadata))
+    let node_metadata = db::node_metadata::get_node_metadata(&client, &page.after_key()?, limit).await?;
+    Ok(web::Json(Listing::new(node_metadata, limit, |metadata| key_cursor(&metadata.owner_id, &metadata.name))))
 }
 
 #[post("")]
@@ -18,8 +22,6 @@ pub async fn post_node_metadata(body: web::Json<NodeMetadataReqObj>, db_pool: we
 
     let owner_id_uuid = uuid::Uuid::parse_str(&body.owner_id);
 
-    // TODO: Check if owner_id exists
-
     if owner_id_uuid.is_err() {
         return Err(NapkinError {
             code: "NODE_NO_ID",
@@ -31,59 +33,63 @@ pub async fn post_node_metadata(body: web::Json<NodeMetadataReqObj>, db_pool: we
         owner_id: owner_id_uuid.unwrap(),
         name: body.name.clone(),
         value: body.value.clone(),
+        version: 0,
     };
 
+    let schema = db::metadata_schemas::get_node_metadata_schema(&client, &node_metadata_info.owner_id, &node_metadata_info.name).await?;
+    validate_metadata(schema.as_ref(), &node_metadata_info.value)?;
+
     let new_node_metadata = db::node_metadata::add_node_metadata(&client, node_metadata_info).await?;
+    let version = new_node_metadata.version;
 
-    Ok(web::Json(new_node_metadata))
+    Ok(with_etag(new_node_metadata, version))
 }
 
 #[get("/{owner_id}")]
-pub async fn get_node_metadata_singleton(id: web::Path<String>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn get_node_metadata_singleton(owner_id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
-    let owner_id_uuid = uuid::Uuid::parse_str(&id);
-
-    if owner_id_uuid.is_err() {
-        return Err(NapkinError {
-            code: "NODE_NO_ID",
-            message: "ID `{owner_id_uuid}` Invalid or Not Found",
-            root: NapkinErrorRoot::NotFound,
-        });
-    }
-    let node_metadata = db::node_metadata::get_node_metadata_singleton(&client, &id).await?;
+    let node_metadata = db::node_metadata::get_node_metadata_singleton(&client, &owner_id).await?;
 
     Ok(web::Json(node_metadata))
 }
 
 #[get("/{owner_id}/{name}")]
-pub async fn get_node_metadata_singleton_key(param: web::Path<(String, String)>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn get_node_metadata_singleton_key(request: HttpRequest, param: web::Path<(uuid::Uuid, String)>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
     let (owner_id, name) = param.into_inner();
-    println!("{} {}", owner_id, name);
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
     let node_metadata_key = db::node_metadata::get_node_metadata_singleton_key(&client, &owner_id, &name).await?;
+    let version = node_metadata_key.version;
 
-    Ok(web::Json(node_metadata_key))
+    Ok(versioned(&request, node_metadata_key, version))
 }
 
 #[put("/{owner_id}/{name}")]
-pub async fn update_node_metadata(owner_id: web::Path<String>, name: web::Path<String>, body: web::Json<NodeMetadata>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn update_node_metadata(request: HttpRequest, param: web::Path<(uuid::Uuid, String)>, body: web::Json<NodeMetadata>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let (owner_id, name) = param.into_inner();
     let node_info: NodeMetadata = body.into_inner();
+    let if_match = if_match(&request);
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
-    let updated_node = db::node_metadata::update_node_metadata(&client, &owner_id, &name, node_info).await?;
+    let schema = db::metadata_schemas::get_node_metadata_schema(&client, &node_info.owner_id, &node_info.name).await?;
+    validate_metadata(schema.as_ref(), &node_info.value)?;
 
-    Ok(web::Json(updated_node))
+    let updated_node = db::node_metadata::update_node_metadata(&client, &owner_id, &name, node_info, &if_match).await;
+    let updated_node = check_precondition(updated_node, &if_match, db::node_metadata::get_node_metadata_singleton_key(&client, &owner_id, &name)).await?;
+    let version = updated_node.version;
+
+    Ok(with_etag(updated_node, version))
 }
 
 #[delete("/{owner_id}/{name}")]
-pub async fn delete_node_metadata(owner_id: web::Path<String>, name: web::Path<String>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn delete_node_metadata(request: HttpRequest, param: web::Path<(uuid::Uuid, String)>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let (owner_id, name) = param.into_inner();
+    let if_match = if_match(&request);
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
-    let deleted_node = db::node_metadata::delete_node(&client, &owner_id, &name).await?;
+    let deleted_node = db::node_metadata::delete_node_metadata(&client, &owner_id, &name, &if_match).await;
+    let deleted_node = check_precondition(deleted_node, &if_match, db::node_metadata::get_node_metadata_singleton_key(&client, &owner_id, &name)).await?;
 
     Ok(web::Json(deleted_node))
 }
-
-
@@ -1,69 +1,107 @@
-use actix_web::{ get, post, put, delete, web, Responder, Result };
+use actix_web::{ get, post, put, delete, web, HttpRequest, Responder, Result };
 use deadpool_postgres::{Client, Pool};
 
-use crate::models::nodes::{Node, NodeReqObj};
+use crate::models::pagination::{Listing, PageParams};
+use crate::models::nodes::{Node, NodeParams, NodeReqObj};
+use crate::models::traversals::{Neighborhood, NeighborParams};
 use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
+use crate::services::projects::resolve_project;
+use crate::services::preconditions::{check_precondition, if_match, versioned, with_etag};
 use crate::db;
 
+const DEFAULT_NEIGHBOR_DEPTH: i32 = 1;
+const MAX_NEIGHBOR_DEPTH: i32 = 10;
+
 #[get("")]
-pub async fn get_nodes(db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
-    // let nodes: Vec<Node> = Vec::new();
+pub async fn get_nodes(page: web::Query<PageParams>, params: web::Query<NodeParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
-    let nodes = db::nodes::get_nodes(&client).await?;
-    Ok(web::Json(nodes))
+    let nodes = db::nodes::get_nodes(&client, &params.label, &page.after_id()?, limit).await?;
+    Ok(web::Json(Listing::new(nodes, limit, |node| {
+        node.id.map(|id| id.to_string()).unwrap_or_default()
+    })))
+}
+
+// Builds the node to insert; `project` is set when posting under `/project/{id}/nodes`
+pub fn node_from_req_obj(body: &NodeReqObj, project: Option<uuid::Uuid>) -> Result<Node, NapkinError> {
+    Ok(Node {
+        id: None,
+        project: resolve_project(project, &body.project)?,
+        labels: body.labels.clone().unwrap_or_default(),
+        version: 0,
+        deleted_at: None,
+    })
 }
 
 #[post("")]
 pub async fn post_node(body: web::Json<NodeReqObj>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let node_info = node_from_req_obj(&body, None)?;
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
-    // let embedding: pgvector::Vector = pgvector::Vector::from(body.embedding.clone());
-    let project_uuid = uuid::Uuid::parse_str(&body.project);
-
-    // TODO: Check if project exists
-
-    if project_uuid.is_err() {
-        return Err(NapkinError {
-            code: "NODE_NO_ID",
-            message: "Project with ID {id} Not Found",
-            root: NapkinErrorRoot::NotFound,
-        });
-    }
-    let node_info = Node {
-        id: body.id.clone(),
-        project: project_uuid.unwrap(),
-        // embedding: embedding,
-    };
-
     let new_node = db::nodes::add_node(&client, node_info).await?;
+    let version = new_node.version;
 
-    Ok(web::Json(new_node))
+    Ok(with_etag(new_node, version))
 }
 
 #[get("/{id}")]
-pub async fn get_node(id: web::Path<String>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn get_node(request: HttpRequest, id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
     let node = db::nodes::get_node(&client, &id).await?;
+    let version = node.version;
 
-    Ok(web::Json(node))
+    Ok(versioned(&request, node, version))
 }
 
 #[put("/{id}")]
-pub async fn update_node(id: web::Path<String>, body: web::Json<Node>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn update_node(request: HttpRequest, id: web::Path<uuid::Uuid>, body: web::Json<Node>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
     let node_info: Node = body.into_inner();
+    let if_match = if_match(&request);
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
-    let updated_node = db::nodes::update_node(&client, &id, node_info).await?;
+    let updated_node = db::nodes::update_node(&client, &id, node_info, &if_match).await;
+    let updated_node = check_precondition(updated_node, &if_match, db::nodes::get_node(&client, &id)).await?;
+    let version = updated_node.version;
 
-    Ok(web::Json(updated_node))
+    Ok(with_etag(updated_node, version))
 }
 
 #[delete("/{id}")]
-pub async fn delete_node(id: web::Path<String>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn delete_node(request: HttpRequest, id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let if_match = if_match(&request);
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
-    let deleted_node = db::nodes::delete_node(&client, &id).await?;
+    let deleted_node = db::nodes::delete_node(&client, &id, &if_match).await;
+    let deleted_node = check_precondition(deleted_node, &if_match, db::nodes::get_node(&client, &id)).await?;
 
     Ok(web::Json(deleted_node))
-}
\ No newline at end of file
+}
+
+// Validates `params` and loads the subgraph around `id`; shared with the diagram exports
+pub async fn load_neighborhood(client: &Client, id: &uuid::Uuid, params: &NeighborParams) -> Result<Neighborhood, NapkinError> {
+    let depth = params.depth.unwrap_or(DEFAULT_NEIGHBOR_DEPTH);
+    if !(0..=MAX_NEIGHBOR_DEPTH).contains(&depth) {
+        return Err(NapkinError {
+            code: "NODE_BAD_DEPTH",
+            message: "`depth` must be between 0 and 10",
+            root: NapkinErrorRoot::BadRequest,
+        });
+    }
+
+    db::nodes::get_node(client, id).await?;
+
+    let nodes = db::traversals::get_neighbors(client, id, depth, params.direction.unwrap_or_default(), &params.label, &params.node_label).await?;
+    let node_ids: Vec<uuid::Uuid> = nodes.iter().map(|node| node.id).collect();
+    let edges = db::traversals::get_edges_between(client, &node_ids, &params.label).await?;
+
+    Ok(Neighborhood { nodes, edges })
+}
+
+#[get("/{id}/neighbors")]
+pub async fn get_node_neighbors(id: web::Path<uuid::Uuid>, params: web::Query<NeighborParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let neighborhood = load_neighborhood(&client, &id, &params).await?;
+
+    Ok(web::Json(neighborhood))
+}
@@ -0,0 +1,282 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use actix_web::{ get, web, Responder, Result };
+use deadpool_postgres::{Client, Pool};
+
+use crate::models::traversals::{Direction, Path, PathParams, Step};
+use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
+use crate::db;
+
+const DEFAULT_PATH_K: i64 = 1;
+const MAX_PATHS: i64 = 1000;
+// Hop limits for `all`, whose work grows with every hop; the cheapest-path search is
+// only bounded when the caller passes `max_length`
+const DEFAULT_PATH_LENGTH: i32 = 6;
+const MAX_PATH_LENGTH: i32 = 10;
+
+// A path under construction, with the cost of each of its edges
+#[derive(Clone)]
+struct Route {
+    nodes: Vec<uuid::Uuid>,
+    edges: Vec<uuid::Uuid>,
+    costs: Vec<f64>,
+}
+
+impl Route {
+    fn cost(&self) -> f64 {
+        self.costs.iter().sum()
+    }
+
+    // Cheapest first, then fewest edges
+    fn rank(&self, other: &Route) -> Ordering {
+        self.cost().total_cmp(&other.cost()).then(self.edges.len().cmp(&other.edges.len()))
+    }
+
+    fn into_path(self) -> Path {
+        let cost = self.cost();
+        Path { nodes: self.nodes, edges: self.edges, cost }
+    }
+}
+
+// A node reached after `hops` edges at `cost`; ordered so `BinaryHeap` pops the cheapest
+struct Reached {
+    cost: f64,
+    hops: i32,
+    node: uuid::Uuid,
+}
+
+impl Ord for Reached {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost).then(other.hops.cmp(&self.hops))
+    }
+}
+
+impl PartialOrd for Reached {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Reached {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Reached {}
+
+// (node, hops) -> the node, edge and edge cost it was cheapest reached through
+type Previous = HashMap<(uuid::Uuid, i32), (uuid::Uuid, uuid::Uuid, f64)>;
+
+fn route_cost(previous: &Previous, node: uuid::Uuid, hops: i32) -> f64 {
+    let (mut at, mut at_hops, mut cost) = (node, hops, 0.0);
+    while let Some(&(from, _, step_cost)) = previous.get(&(at, at_hops)) {
+        cost += step_cost;
+        (at, at_hops) = (from, at_hops - 1);
+    }
+    cost
+}
+
+// Dijkstra over the nodes reachable from `params.from`, loading the steps out of
+// the whole frontier in one statement whenever an unloaded node is reached
+struct PathSearch<'a> {
+    client: &'a Client,
+    params: &'a PathParams,
+    direction: Direction,
+    steps: HashMap<uuid::Uuid, Vec<Step>>,
+}
+
+impl PathSearch<'_> {
+    async fn load(&mut self, node_ids: Vec<uuid::Uuid>) -> Result<(), NapkinError> {
+        let mut missing = Vec::new();
+        for id in node_ids {
+            if let Entry::Vacant(entry) = self.steps.entry(id) {
+                entry.insert(Vec::new());
+                missing.push(id);
+            }
+        }
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        for step in db::traversals::get_steps(self.client, &missing, self.direction, &self.params.label, &self.params.node_label).await? {
+            // Dijkstra relies on a path never getting cheaper as it grows
+            if !(step.cost.is_finite() && step.cost >= 0.0) {
+                return Err(NapkinError {
+                    code: "PATH_BAD_WEIGHT",
+                    message: "Shortest paths need finite, non-negative edge weights",
+                    root: NapkinErrorRoot::Unprocessable(vec![format!("edge `{}` has weight {}", step.edge, step.cost)]),
+                });
+            }
+            if let Some(steps) = self.steps.get_mut(&step.node) {
+                steps.push(step);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Cheapest route of at most `max_hops` edges from `start` to `params.to` that avoids
+    // `banned_nodes` and `banned_edges`. Any route that repeats a node costs at least as
+    // much as the one without the detour and has more edges, so the result is simple.
+    async fn cheapest(
+        &mut self,
+        start: uuid::Uuid,
+        max_hops: i32,
+        banned_nodes: &HashSet<uuid::Uuid>,
+        banned_edges: &HashSet<uuid::Uuid>,
+    ) -> Result<Option<Route>, NapkinError> {
+        let mut heap = BinaryHeap::from([Reached { cost: 0.0, hops: 0, node: start }]);
+        let mut previous = Previous::new();
+        // Fewest hops a node has been expanded with; later, costlier arrivals need fewer
+        let mut expanded: HashMap<uuid::Uuid, i32> = HashMap::new();
+
+        while let Some(Reached { hops, node, .. }) = heap.pop() {
+            if expanded.get(&node).is_some_and(|&fewest| fewest <= hops) {
+                continue;
+            }
+            expanded.insert(node, hops);
+
+            if node == self.params.to {
+                let mut route = Route { nodes: vec![node], edges: Vec::new(), costs: Vec::new() };
+                let (mut at, mut at_hops) = (node, hops);
+                while let Some(&(from, edge, cost)) = previous.get(&(at, at_hops)) {
+                    route.nodes.push(from);
+                    route.edges.push(edge);
+                    route.costs.push(cost);
+                    (at, at_hops) = (from, at_hops - 1);
+                }
+                route.nodes.reverse();
+                route.edges.reverse();
+                route.costs.reverse();
+                return Ok(Some(route));
+            }
+            if hops == max_hops {
+                continue;
+            }
+
+            if !self.steps.contains_key(&node) {
+                let mut frontier: Vec<uuid::Uuid> = heap.iter().map(|reached| reached.node).collect();
+                frontier.push(node);
+                self.load(frontier).await?;
+            }
+
+            let cost = route_cost(&previous, node, hops);
+            for step in self.steps[&node].iter() {
+                if banned_nodes.contains(&step.next) || banned_edges.contains(&step.edge) || step.next == start {
+                    continue;
+                }
+                let key = (step.next, hops + 1);
+                if previous.contains_key(&key) && route_cost(&previous, step.next, hops + 1) <= cost + step.cost {
+                    continue;
+                }
+                previous.insert(key, (node, step.edge, step.cost));
+                heap.push(Reached { cost: cost + step.cost, hops: hops + 1, node: step.next });
+            }
+        }
+
+        Ok(None)
+    }
+
+    // Yen's algorithm: each next-cheapest route leaves a cheaper one at some node,
+    // so search from every node of the last route with its continuations banned
+    async fn k_cheapest(&mut self, k: usize, max_length: i32) -> Result<Vec<Route>, NapkinError> {
+        let Some(first) = self.cheapest(self.params.from, max_length, &HashSet::new(), &HashSet::new()).await? else {
+            return Ok(Vec::new());
+        };
+        let mut found = vec![first];
+        let mut candidates: Vec<Route> = Vec::new();
+
+        while found.len() < k {
+            let last = found[found.len() - 1].clone();
+            for i in 0..last.edges.len() {
+                let root = &last.nodes[..=i];
+                let banned_edges: HashSet<uuid::Uuid> = found
+                    .iter()
+                    .filter(|route| route.nodes.len() > i + 1 && route.nodes[..=i] == *root)
+                    .map(|route| route.edges[i])
+                    .collect();
+                let banned_nodes: HashSet<uuid::Uuid> = root[..i].iter().copied().collect();
+
+                let Some(spur) = self.cheapest(last.nodes[i], max_length - i as i32, &banned_nodes, &banned_edges).await? else {
+                    continue;
+                };
+                let mut route = Route {
+                    nodes: root[..i].to_vec(),
+                    edges: last.edges[..i].to_vec(),
+                    costs: last.costs[..i].to_vec(),
+                };
+                route.nodes.extend(spur.nodes);
+                route.edges.extend(spur.edges);
+                route.costs.extend(spur.costs);
+                if !candidates.iter().chain(found.iter()).any(|known| known.edges == route.edges) {
+                    candidates.push(route);
+                }
+            }
+
+            let Some((cheapest, _)) = candidates.iter().enumerate().min_by(|(_, a), (_, b)| a.rank(b)) else {
+                break;
+            };
+            found.push(candidates.swap_remove(cheapest));
+        }
+
+        Ok(found)
+    }
+}
+
+#[get("")]
+pub async fn get_paths(params: web::Query<PathParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let all = params.all == Some(true);
+    if all && params.k.is_some() {
+        return Err(NapkinError {
+            code: "PATH_K_WITH_ALL",
+            message: "`k` cannot be combined with `all`, which returns every path",
+            root: NapkinErrorRoot::BadRequest,
+        });
+    }
+    let limit = params.k.unwrap_or(DEFAULT_PATH_K);
+    if !(1..=MAX_PATHS).contains(&limit) {
+        return Err(NapkinError {
+            code: "PATH_BAD_K",
+            message: "`k` must be between 1 and 1000",
+            root: NapkinErrorRoot::BadRequest,
+        });
+    }
+    let max_length = match (all, params.max_length) {
+        (true, max_length) => max_length.unwrap_or(DEFAULT_PATH_LENGTH),
+        (false, max_length) => max_length.unwrap_or(i32::MAX),
+    };
+    if max_length < 1 || (all && max_length > MAX_PATH_LENGTH) {
+        return Err(NapkinError {
+            code: "PATH_BAD_LENGTH",
+            message: "`max_length` must be at least 1, and at most 10 with `all`",
+            root: NapkinErrorRoot::BadRequest,
+        });
+    }
+    let mut client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    db::nodes::get_node(&client, &params.from).await?;
+    db::nodes::get_node(&client, &params.to).await?;
+
+    // Paths follow edge direction unless asked otherwise
+    let direction = params.direction.unwrap_or(Direction::Out);
+    if all {
+        // One path past the cap tells a complete answer from a cut-off one
+        let paths = db::traversals::get_all_paths(&mut client, &params, max_length, direction, MAX_PATHS + 1).await?;
+        if paths.len() as i64 > MAX_PATHS {
+            return Err(NapkinError {
+                code: "PATH_TOO_MANY",
+                message: "More than 1000 paths match, lower `max_length` or filter by `label` or `node_label`",
+                root: NapkinErrorRoot::BadRequest,
+            });
+        }
+        return Ok(web::Json(paths));
+    }
+
+    let mut search = PathSearch { client: &client, params: &params, direction, steps: HashMap::new() };
+    let paths = search.k_cheapest(limit as usize, max_length).await?;
+
+    Ok(web::Json(paths.into_iter().map(Route::into_path).collect::<Vec<_>>()))
+}
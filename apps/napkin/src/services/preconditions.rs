@@ -0,0 +1,75 @@
+use std::future::Future;
+
+use actix_web::http::header::{EntityTag, ETag, Header, IfMatch, IfNoneMatch, IF_MATCH};
+use actix_web::{HttpRequest, HttpResponse};
+use serde::Serialize;
+
+use crate::errors::{NapkinError, NapkinErrorRoot};
+
+pub fn precondition_failed() -> NapkinError {
+    NapkinError {
+        code: "PRECONDITION_FAILED",
+        message: "`If-Match` does not name the current version",
+        root: NapkinErrorRoot::PreconditionFailed,
+    }
+}
+
+// Rows are tagged with their `version` column, e.g. `ETag: "3"`
+pub fn etag(version: i64) -> ETag {
+    ETag(EntityTag::new_strong(version.to_string()))
+}
+
+// Versions named by `If-Match`, or `None` when any version may be written. A tag
+// that is not a version can never match, so it is kept as an empty list.
+pub fn if_match(request: &HttpRequest) -> Option<Vec<i64>> {
+    if !request.headers().contains_key(IF_MATCH) {
+        return None;
+    }
+
+    match IfMatch::parse(request) {
+        Ok(IfMatch::Any) => None,
+        Ok(IfMatch::Items(tags)) => Some(
+            tags.iter()
+                .filter(|tag| !tag.weak)
+                .filter_map(|tag| tag.tag().parse().ok())
+                .collect(),
+        ),
+        Err(_) => Some(Vec::new()),
+    }
+}
+
+pub fn with_etag<T: Serialize>(row: T, version: i64) -> HttpResponse {
+    HttpResponse::Ok().insert_header(etag(version)).json(row)
+}
+
+// Answers `304 Not Modified` when `If-None-Match` already names this version
+pub fn versioned<T: Serialize>(request: &HttpRequest, row: T, version: i64) -> HttpResponse {
+    let current = EntityTag::new_strong(version.to_string());
+    let unchanged = match IfNoneMatch::parse(request) {
+        Ok(IfNoneMatch::Any) => true,
+        Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&current)),
+        Err(_) => false,
+    };
+
+    if unchanged {
+        HttpResponse::NotModified().insert_header(ETag(current)).finish()
+    } else {
+        with_etag(row, version)
+    }
+}
+
+// A conditional write that matched no row either lost to a concurrent writer or
+// targeted a missing row; `current` looks the row up again to tell the two apart
+pub async fn check_precondition<T, U>(
+    written: Result<T, NapkinError>,
+    if_match: &Option<Vec<i64>>,
+    current: impl Future<Output = Result<U, NapkinError>>,
+) -> Result<T, NapkinError> {
+    match written {
+        Err(NapkinError { root: NapkinErrorRoot::NotFound, .. }) if if_match.is_some() => match current.await {
+            Ok(_) => Err(precondition_failed()),
+            Err(err) => Err(err),
+        },
+        written => written,
+    }
+}
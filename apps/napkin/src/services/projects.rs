@@ -1,16 +1,50 @@
-use actix_web::{ get, post, put, delete, web, Responder, Result };
+use actix_web::{ get, post, put, delete, web, HttpRequest, Responder, Result };
 use deadpool_postgres::{Client, Pool};
 
+use crate::models::pagination::{key_cursor, Listing, PageParams};
 use crate::models::projects::Project;
-use crate::errors::{ NapkinError, handle_pool_error };
+use crate::models::nodes::{NodeParams, NodeReqObj};
+use crate::models::edges::{EdgeParams, EdgeReqObj};
+use crate::services::{nodes::node_from_req_obj, edges::edge_from_req_obj};
+use crate::services::preconditions::{check_precondition, if_match, versioned, with_etag};
+use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
 use crate::db;
 
+// Resolves the project of a new node or edge: the path of a project-scoped route
+// takes precedence, and a body that names a different project is rejected
+pub fn resolve_project(path_project: Option<uuid::Uuid>, body_project: &Option<String>) -> Result<uuid::Uuid, NapkinError> {
+    let body_project = match body_project {
+        Some(project) => Some(uuid::Uuid::parse_str(project).map_err(|_| NapkinError {
+            code: "PROJECT_NO_ID",
+            message: "Project with ID {id} Not Found",
+            root: NapkinErrorRoot::NotFound,
+        })?),
+        None => None,
+    };
+
+    match (path_project, body_project) {
+        (Some(path_project), Some(body_project)) if path_project != body_project => Err(NapkinError {
+            code: "PROJECT_MISMATCH",
+            message: "Body `project` does not match the project in the path",
+            root: NapkinErrorRoot::BadRequest,
+        }),
+        (Some(project), _) | (None, Some(project)) => Ok(project),
+        (None, None) => Err(NapkinError {
+            code: "PROJECT_REQUIRED",
+            message: "`project` is required",
+            root: NapkinErrorRoot::BadRequest,
+        }),
+    }
+}
+
 #[get("")]
-pub async fn get_projects(db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
-    // let projects: Vec<Project> = Vec::new();
+pub async fn get_projects(page: web::Query<PageParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
-    let projects = db::projects::get_projects(&client).await?;
-    Ok(web::Json(projects))
+    let projects = db::projects::get_projects(&client, &page.after_id()?, limit).await?;
+    Ok(web::Json(Listing::new(projects, limit, |project| {
+        project.id.map(|id| id.to_string()).unwrap_or_default()
+    })))
 }
 
 #[post("")]
@@ -19,34 +53,115 @@ pub async fn post_project(body: web::Json<Project>, db_pool: web::Data<Pool>) ->
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
     let new_project = db::projects::add_project(&client, project_info).await?;
+    let version = new_project.version;
 
-    Ok(web::Json(new_project))
+    Ok(with_etag(new_project, version))
 }
 
 #[get("/{id}")]
-pub async fn get_project(id: web::Path<String>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn get_project(request: HttpRequest, id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
     let project = db::projects::get_project(&client, &id).await?;
+    let version = project.version;
 
-    Ok(web::Json(project))
+    Ok(versioned(&request, project, version))
 }
 
 #[put("/{id}")]
-pub async fn update_project(id: web::Path<String>, body: web::Json<Project>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn update_project(request: HttpRequest, id: web::Path<uuid::Uuid>, body: web::Json<Project>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
     let project_info: Project = body.into_inner();
+    let if_match = if_match(&request);
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
-    let updated_project = db::projects::update_project(&client, &id, project_info).await?;
+    let updated_project = db::projects::update_project(&client, &id, project_info, &if_match).await;
+    let updated_project = check_precondition(updated_project, &if_match, db::projects::get_project(&client, &id)).await?;
+    let version = updated_project.version;
 
-    Ok(web::Json(updated_project))
+    Ok(with_etag(updated_project, version))
 }
 
 #[delete("/{id}")]
-pub async fn delete_project(id: web::Path<String>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+pub async fn delete_project(request: HttpRequest, id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let if_match = if_match(&request);
     let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
 
-    let deleted_project = db::projects::delete_project(&client, &id).await?;
+    let deleted_project = db::projects::delete_project(&client, &id, &if_match).await;
+    let deleted_project = check_precondition(deleted_project, &if_match, db::projects::get_project(&client, &id)).await?;
 
     Ok(web::Json(deleted_project))
-}
\ No newline at end of file
+}
+
+#[get("/{id}/nodes")]
+pub async fn get_project_nodes(id: web::Path<uuid::Uuid>, page: web::Query<PageParams>, params: web::Query<NodeParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    db::projects::get_project(&client, &id).await?;
+
+    let nodes = db::nodes::get_project_nodes(&client, &id, &params.label, &page.after_id()?, limit).await?;
+    Ok(web::Json(Listing::new(nodes, limit, |node| {
+        node.id.map(|id| id.to_string()).unwrap_or_default()
+    })))
+}
+
+#[post("/{id}/nodes")]
+pub async fn post_project_node(id: web::Path<uuid::Uuid>, body: web::Json<NodeReqObj>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let node_info = node_from_req_obj(&body, Some(*id))?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    db::projects::get_project(&client, &id).await?;
+
+    let new_node = db::nodes::add_node(&client, node_info).await?;
+    let version = new_node.version;
+
+    Ok(with_etag(new_node, version))
+}
+
+#[get("/{id}/nodes/metadata")]
+pub async fn get_project_node_metadata(id: web::Path<uuid::Uuid>, page: web::Query<PageParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    db::projects::get_project(&client, &id).await?;
+
+    let node_metadata = db::node_metadata::get_project_node_metadata(&client, &id, &page.after_key()?, limit).await?;
+    Ok(web::Json(Listing::new(node_metadata, limit, |metadata| key_cursor(&metadata.owner_id, &metadata.name))))
+}
+
+#[get("/{id}/edges")]
+pub async fn get_project_edges(id: web::Path<uuid::Uuid>, page: web::Query<PageParams>, params: web::Query<EdgeParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    db::projects::get_project(&client, &id).await?;
+
+    let edges = db::edges::get_project_edges(&client, &id, &params.label, &page.after_id()?, limit).await?;
+    Ok(web::Json(Listing::new(edges, limit, |edge| {
+        edge.id.map(|id| id.to_string()).unwrap_or_default()
+    })))
+}
+
+#[post("/{id}/edges")]
+pub async fn post_project_edge(id: web::Path<uuid::Uuid>, body: web::Json<EdgeReqObj>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let edge_info = edge_from_req_obj(&body, Some(*id))?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    db::projects::get_project(&client, &id).await?;
+
+    let new_edge = db::edges::add_edge(&client, edge_info).await?;
+    let version = new_edge.version;
+
+    Ok(with_etag(new_edge, version))
+}
+
+#[get("/{id}/edges/metadata")]
+pub async fn get_project_edge_metadata(id: web::Path<uuid::Uuid>, page: web::Query<PageParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    db::projects::get_project(&client, &id).await?;
+
+    let edge_metadata = db::edge_metadata::get_project_edge_metadata(&client, &id, &page.after_key()?, limit).await?;
+    Ok(web::Json(Listing::new(edge_metadata, limit, |metadata| key_cursor(&metadata.owner_id, &metadata.name))))
+}
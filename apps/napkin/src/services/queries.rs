@@ -0,0 +1,25 @@
+use actix_web::{ post, web, Responder, Result };
+use deadpool_postgres::{Client, Pool};
+
+use crate::models::queries::{QueryReqObj, QueryResult};
+use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
+use crate::query::parse_query;
+use crate::db;
+
+#[post("")]
+pub async fn post_query(body: web::Json<QueryReqObj>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let query = parse_query(&body.query).map_err(|error| NapkinError {
+        code: "QUERY_SYNTAX",
+        message: "Query could not be parsed",
+        root: NapkinErrorRoot::Unprocessable(vec![format!("column {}: {}", error.column, error.message)]),
+    })?;
+    let (stmt, params) = db::queries::compile_query(&query, body.project, &body.params)?;
+
+    let mut client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let rows = db::queries::run_query(&mut client, &stmt, &params).await?;
+
+    Ok(web::Json(QueryResult {
+        columns: query.returns.into_iter().map(|item| item.name).collect(),
+        rows: rows.into_iter().map(|row| row.row).collect(),
+    }))
+}
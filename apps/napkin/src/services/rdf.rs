@@ -0,0 +1,62 @@
+use actix_web::{ get, web, HttpResponse, Responder, Result };
+use deadpool_postgres::{Client, Pool};
+use tokio_postgres::IsolationLevel;
+
+use crate::models::rdf::RdfParams;
+use crate::formats::rdf::{project_triples, valid_vocab, Triple, DEFAULT_VOCAB};
+use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
+use crate::{db, formats};
+
+// The project's triples and the vocabulary they were built with
+async fn load_triples(client: &mut Client, id: &uuid::Uuid, params: &RdfParams) -> Result<(Vec<Triple>, String), NapkinError> {
+    let vocab = params.vocab.clone().unwrap_or_else(|| DEFAULT_VOCAB.to_string());
+    if !valid_vocab(&vocab) {
+        return Err(NapkinError {
+            code: "RDF_BAD_VOCAB",
+            message: "`vocab` must be an absolute IRI",
+            root: NapkinErrorRoot::BadRequest,
+        });
+    }
+
+    // One snapshot, so edges and metadata never point at nodes missing from the output
+    let transaction = client
+        .build_transaction()
+        .isolation_level(IsolationLevel::RepeatableRead)
+        .read_only(true)
+        .start()
+        .await?;
+
+    db::projects::get_project(&transaction, id).await?;
+    let nodes = db::exports::get_nodes(&transaction, id).await?;
+    let edges = db::exports::get_edges(&transaction, id).await?;
+    let node_metadata = db::exports::get_node_metadata(&transaction, id).await?;
+    let edge_metadata = db::exports::get_edge_metadata(&transaction, id).await?;
+
+    transaction.commit().await?;
+
+    Ok((project_triples(&vocab, &nodes, &edges, &node_metadata, &edge_metadata), vocab))
+}
+
+#[get("/{id}/export/turtle")]
+pub async fn export_project_turtle(id: web::Path<uuid::Uuid>, params: web::Query<RdfParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let mut client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let (triples, vocab) = load_triples(&mut client, &id, &params).await?;
+
+    Ok(HttpResponse::Ok().content_type("text/turtle; charset=utf-8").body(formats::rdf::write_turtle(&triples, &vocab)))
+}
+
+#[get("/{id}/export/ntriples")]
+pub async fn export_project_ntriples(id: web::Path<uuid::Uuid>, params: web::Query<RdfParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let mut client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let (triples, _) = load_triples(&mut client, &id, &params).await?;
+
+    Ok(HttpResponse::Ok().content_type("application/n-triples").body(formats::rdf::write_ntriples(&triples)))
+}
+
+#[get("/{id}/export/jsonld")]
+pub async fn export_project_jsonld(id: web::Path<uuid::Uuid>, params: web::Query<RdfParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let mut client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let (triples, vocab) = load_triples(&mut client, &id, &params).await?;
+
+    Ok(HttpResponse::Ok().content_type("application/ld+json").json(formats::rdf::write_jsonld(&triples, &vocab)))
+}
@@ -0,0 +1,125 @@
+use actix_web::{ get, post, delete, web, Responder, Result };
+use deadpool_postgres::{Client, Pool};
+
+use crate::models::pagination::{Listing, PageParams};
+use crate::models::trash::TrashParams;
+use crate::errors::{ NapkinError, NapkinErrorRoot, handle_pool_error };
+use crate::services::preconditions::with_etag;
+use crate::db;
+
+// A restore that would leave a row under a project or endpoint that is still in the trash
+fn still_trashed(code: &'static str, message: &'static str) -> impl FnOnce(NapkinError) -> NapkinError {
+    move |err| match err.root {
+        NapkinErrorRoot::NotFound => NapkinError {
+            code,
+            message,
+            root: NapkinErrorRoot::Conflict,
+        },
+        _ => err,
+    }
+}
+
+#[get("/projects")]
+pub async fn get_trashed_projects(page: web::Query<PageParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let projects = db::projects::get_trashed_projects(&client, &page.after_id()?, limit).await?;
+    Ok(web::Json(Listing::new(projects, limit, |project| {
+        project.id.map(|id| id.to_string()).unwrap_or_default()
+    })))
+}
+
+#[get("/nodes")]
+pub async fn get_trashed_nodes(page: web::Query<PageParams>, params: web::Query<TrashParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let nodes = db::nodes::get_trashed_nodes(&client, &params.project, &page.after_id()?, limit).await?;
+    Ok(web::Json(Listing::new(nodes, limit, |node| {
+        node.id.map(|id| id.to_string()).unwrap_or_default()
+    })))
+}
+
+#[get("/edges")]
+pub async fn get_trashed_edges(page: web::Query<PageParams>, params: web::Query<TrashParams>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let limit = page.page_limit()?;
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+    let edges = db::edges::get_trashed_edges(&client, &params.project, &page.after_id()?, limit).await?;
+    Ok(web::Json(Listing::new(edges, limit, |edge| {
+        edge.id.map(|id| id.to_string()).unwrap_or_default()
+    })))
+}
+
+#[post("/projects/{id}/restore")]
+pub async fn restore_project(id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let restored_project = db::projects::restore_project(&client, &id).await?;
+    let version = restored_project.version;
+
+    Ok(with_etag(restored_project, version))
+}
+
+#[post("/nodes/{id}/restore")]
+pub async fn restore_node(id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let node = db::nodes::get_trashed_node(&client, &id).await?;
+    db::projects::get_project(&client, &node.project).await.map_err(still_trashed(
+        "NODE_PROJECT_TRASHED",
+        "The node's project is in the trash, restore the project instead",
+    ))?;
+
+    let restored_node = db::nodes::restore_node(&client, &id).await?;
+    let version = restored_node.version;
+
+    Ok(with_etag(restored_node, version))
+}
+
+#[post("/edges/{id}/restore")]
+pub async fn restore_edge(id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let edge = db::edges::get_trashed_edge(&client, &id).await?;
+    db::projects::get_project(&client, &edge.project).await.map_err(still_trashed(
+        "EDGE_PROJECT_TRASHED",
+        "The edge's project is in the trash, restore the project instead",
+    ))?;
+    for endpoint in [&edge.source, &edge.target] {
+        db::nodes::get_node(&client, endpoint).await.map_err(still_trashed(
+            "EDGE_NODE_TRASHED",
+            "An endpoint of the edge is in the trash, restore it first",
+        ))?;
+    }
+
+    let restored_edge = db::edges::restore_edge(&client, &id).await?;
+    let version = restored_edge.version;
+
+    Ok(with_etag(restored_edge, version))
+}
+
+#[delete("/projects/{id}")]
+pub async fn purge_project(id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let purged_project = db::projects::purge_project(&client, &id).await?;
+
+    Ok(web::Json(purged_project))
+}
+
+#[delete("/nodes/{id}")]
+pub async fn purge_node(id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let purged_node = db::nodes::purge_node(&client, &id).await?;
+
+    Ok(web::Json(purged_node))
+}
+
+#[delete("/edges/{id}")]
+pub async fn purge_edge(id: web::Path<uuid::Uuid>, db_pool: web::Data<Pool>) -> Result<impl Responder, NapkinError> {
+    let client: Client = db_pool.get().await.map_err(handle_pool_error)?;
+
+    let purged_edge = db::edges::purge_edge(&client, &id).await?;
+
+    Ok(web::Json(purged_edge))
+}
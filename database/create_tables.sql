@@ -2,6 +2,9 @@ CREATE EXTENSION IF NOT EXISTS "vector";
 CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
 CREATE EXTENSION IF NOT EXISTS "pgcrypto";
 
+-- Safe to re-run on an existing database: `CREATE TABLE IF NOT EXISTS` leaves older
+-- tables as they are, so each table is followed by the columns added to it since
+
 CREATE OR REPLACE FUNCTION generate_ulid() RETURNS uuid
 	AS $$
 		SELECT (lpad(to_hex(floor(extract(epoch FROM clock_timestamp()) * 1000)::bigint), 12, '0') || encode(gen_random_bytes(10), 'hex'))::uuid;
@@ -11,26 +14,46 @@ CREATE TABLE IF NOT EXISTS projects (
 	id uuid DEFAULT generate_ulid (),
 	scope TEXT NOT NULL,
 	name TEXT NOT NULL,
+	version BIGINT NOT NULL DEFAULT 1,
+	-- Set while the project is in the trash; it keeps its name until purged
+	deleted_at TIMESTAMPTZ,
 	PRIMARY KEY (id),
 	UNIQUE (scope, name)
 );
 
+ALTER TABLE projects ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+ALTER TABLE projects ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
 ALTER TABLE projects OWNER TO postgres;
 
 CREATE TABLE IF NOT EXISTS nodes (
 	id uuid DEFAULT generate_ulid (),
 	project UUID,
+	labels TEXT[] NOT NULL DEFAULT '{}',
+	version BIGINT NOT NULL DEFAULT 1,
+	deleted_at TIMESTAMPTZ,
 	PRIMARY KEY (id),
 	CONSTRAINT n_project
 		FOREIGN KEY(project)
 			REFERENCES projects(id)
 );
 
+ALTER TABLE nodes ADD COLUMN IF NOT EXISTS labels TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE nodes ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+ALTER TABLE nodes ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS node_labels_idx ON nodes USING gin (labels);
+
 CREATE TABLE IF NOT EXISTS edges (
 	id UUID DEFAULT generate_ulid (),
 	project UUID,
 	source UUID,
 	target UUID,
+	label TEXT,
+	directed BOOLEAN NOT NULL DEFAULT TRUE,
+	weight DOUBLE PRECISION,
+	version BIGINT NOT NULL DEFAULT 1,
+	deleted_at TIMESTAMPTZ,
 	PRIMARY KEY (id),
 	CONSTRAINT e_project
 		FOREIGN KEY(project)
@@ -43,37 +66,179 @@ CREATE TABLE IF NOT EXISTS edges (
 			REFERENCES nodes(id)
 );
 
+ALTER TABLE edges ADD COLUMN IF NOT EXISTS label TEXT;
+ALTER TABLE edges ADD COLUMN IF NOT EXISTS directed BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE edges ADD COLUMN IF NOT EXISTS weight DOUBLE PRECISION;
+ALTER TABLE edges ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+ALTER TABLE edges ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS edge_label_idx ON edges (project, label);
+
 CREATE TABLE IF NOT EXISTS artifacts (
 	node_id UUID NOT NULL,
 	embedding vector(1024) NOT NULL,
+	version BIGINT NOT NULL DEFAULT 1,
 	CONSTRAINT artifact_pkey PRIMARY KEY (node_id),
 	CONSTRAINT artifact_node_id_fkey FOREIGN KEY (node_id)
 		REFERENCES nodes (id) ON DELETE CASCADE
 );
 
+ALTER TABLE artifacts ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+
+-- One HNSW index per distance operator used by `POST /artifact/search`
+CREATE INDEX IF NOT EXISTS artifact_embedding_l2_idx ON artifacts USING hnsw (embedding vector_l2_ops);
+CREATE INDEX IF NOT EXISTS artifact_embedding_cosine_idx ON artifacts USING hnsw (embedding vector_cosine_ops);
+CREATE INDEX IF NOT EXISTS artifact_embedding_ip_idx ON artifacts USING hnsw (embedding vector_ip_ops);
+
 CREATE TABLE IF NOT EXISTS node_metadata (
 	owner_id UUID NOT NULL,
 	name TEXT NOT NULL,
 	value JSONB NOT NULL,
+	version BIGINT NOT NULL DEFAULT 1,
 	CONSTRAINT node_tag_pkey PRIMARY KEY (owner_id, name),
 	CONSTRAINT node_tag_owner_id_fkey FOREIGN KEY (owner_id)
 		REFERENCES nodes (id) ON DELETE CASCADE
 );
 
+ALTER TABLE node_metadata ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+
 CREATE TABLE IF NOT EXISTS edge_metadata (
 	owner_id UUID NOT NULL,
 	name TEXT NOT NULL,
 	value JSONB NOT NULL,
+	version BIGINT NOT NULL DEFAULT 1,
 	CONSTRAINT edge_tag_pkey PRIMARY KEY (owner_id, name),
 	CONSTRAINT edge_tag_owner_id_fkey FOREIGN KEY (owner_id)
 		REFERENCES edges (id) ON DELETE CASCADE
 );
 
+ALTER TABLE edge_metadata ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+
 CREATE TABLE IF NOT EXISTS artifact_metadata (
 	owner_id UUID NOT NULL,
 	name TEXT NOT NULL,
 	value JSONB NOT NULL,
+	version BIGINT NOT NULL DEFAULT 1,
 	CONSTRAINT artifact_tag_pkey PRIMARY KEY (owner_id, name),
 	CONSTRAINT artifact_tag_owner_id_fkey FOREIGN KEY (owner_id)
 		REFERENCES artifacts (node_id) ON DELETE CASCADE
-);
\ No newline at end of file
+);
+
+ALTER TABLE artifact_metadata ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+
+-- JSON Schemas that `node_metadata` and `edge_metadata` values with a matching
+-- `name` must satisfy within a project
+CREATE TABLE IF NOT EXISTS metadata_schemas (
+	project UUID NOT NULL,
+	name TEXT NOT NULL,
+	schema JSONB NOT NULL,
+	version BIGINT NOT NULL DEFAULT 1,
+	CONSTRAINT metadata_schema_pkey PRIMARY KEY (project, name),
+	CONSTRAINT metadata_schema_project_fkey FOREIGN KEY (project)
+		REFERENCES projects (id) ON DELETE CASCADE
+);
+
+ALTER TABLE metadata_schemas ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+
+-- Counts every update of a row, whichever process made it; served as the `ETag`
+CREATE OR REPLACE FUNCTION napkin_bump_version() RETURNS trigger
+	AS $$
+	BEGIN
+		NEW.version := OLD.version + 1;
+		RETURN NEW;
+	END;
+	$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER projects_bump_version BEFORE UPDATE ON projects
+	FOR EACH ROW EXECUTE FUNCTION napkin_bump_version();
+CREATE OR REPLACE TRIGGER nodes_bump_version BEFORE UPDATE ON nodes
+	FOR EACH ROW EXECUTE FUNCTION napkin_bump_version();
+CREATE OR REPLACE TRIGGER edges_bump_version BEFORE UPDATE ON edges
+	FOR EACH ROW EXECUTE FUNCTION napkin_bump_version();
+CREATE OR REPLACE TRIGGER artifacts_bump_version BEFORE UPDATE ON artifacts
+	FOR EACH ROW EXECUTE FUNCTION napkin_bump_version();
+CREATE OR REPLACE TRIGGER node_metadata_bump_version BEFORE UPDATE ON node_metadata
+	FOR EACH ROW EXECUTE FUNCTION napkin_bump_version();
+CREATE OR REPLACE TRIGGER edge_metadata_bump_version BEFORE UPDATE ON edge_metadata
+	FOR EACH ROW EXECUTE FUNCTION napkin_bump_version();
+CREATE OR REPLACE TRIGGER artifact_metadata_bump_version BEFORE UPDATE ON artifact_metadata
+	FOR EACH ROW EXECUTE FUNCTION napkin_bump_version();
+CREATE OR REPLACE TRIGGER metadata_schemas_bump_version BEFORE UPDATE ON metadata_schemas
+	FOR EACH ROW EXECUTE FUNCTION napkin_bump_version();
+
+-- Announces every committed row change on the `napkin_changes` channel, whichever
+-- process wrote it; the server relays these as `/events`
+CREATE OR REPLACE FUNCTION napkin_notify_change() RETURNS trigger
+	AS $$
+	DECLARE
+		row_data jsonb;
+		row_project uuid;
+		row_action text;
+		payload text;
+	BEGIN
+		-- Bulk writes set this for their transaction and announce one `imported` change instead
+		IF current_setting('napkin.bulk_changes', true) = 'on' THEN
+			RETURN NULL;
+		END IF;
+
+		IF TG_OP = 'DELETE' THEN
+			row_data := to_jsonb(OLD);
+		ELSE
+			row_data := to_jsonb(NEW);
+		END IF;
+
+		row_action := CASE TG_OP WHEN 'INSERT' THEN 'created' WHEN 'UPDATE' THEN 'updated' ELSE 'deleted' END;
+		-- Moving a row to or from the trash reads as deleting or re-creating it
+		IF TG_OP = 'UPDATE' AND (to_jsonb(OLD)->>'deleted_at') IS DISTINCT FROM (row_data->>'deleted_at') THEN
+			row_action := CASE WHEN row_data->>'deleted_at' IS NULL THEN 'created' ELSE 'deleted' END;
+		END IF;
+
+		IF TG_TABLE_NAME = 'projects' THEN
+			row_project := (row_data->>'id')::uuid;
+		ELSIF TG_TABLE_NAME IN ('nodes', 'edges') THEN
+			row_project := (row_data->>'project')::uuid;
+		ELSIF TG_TABLE_NAME = 'node_metadata' THEN
+			SELECT project INTO row_project FROM nodes WHERE id = (row_data->>'owner_id')::uuid;
+		ELSE
+			SELECT project INTO row_project FROM edges WHERE id = (row_data->>'owner_id')::uuid;
+		END IF;
+		-- Metadata removed along with its owner is implied by the owner's deletion
+		IF row_project IS NULL THEN
+			RETURN NULL;
+		END IF;
+
+		payload := jsonb_build_object(
+			'entity', TG_ARGV[0],
+			'action', row_action,
+			'project', row_project,
+			'data', row_data
+		)::text;
+		-- Notification payloads are capped at 8000 bytes; send only the row's key beyond that
+		IF octet_length(payload) >= 8000 THEN
+			payload := jsonb_build_object(
+				'entity', TG_ARGV[0],
+				'action', row_action,
+				'project', row_project,
+				'data', CASE WHEN row_data ? 'id' THEN jsonb_build_object('id', row_data->'id')
+					ELSE jsonb_build_object('owner_id', row_data->'owner_id', 'name', row_data->'name') END,
+				'partial', true
+			)::text;
+		END IF;
+		IF octet_length(payload) < 8000 THEN
+			PERFORM pg_notify('napkin_changes', payload);
+		END IF;
+
+		RETURN NULL;
+	END;
+	$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER projects_notify_change AFTER INSERT OR UPDATE OR DELETE ON projects
+	FOR EACH ROW EXECUTE FUNCTION napkin_notify_change('project');
+CREATE OR REPLACE TRIGGER nodes_notify_change AFTER INSERT OR UPDATE OR DELETE ON nodes
+	FOR EACH ROW EXECUTE FUNCTION napkin_notify_change('node');
+CREATE OR REPLACE TRIGGER edges_notify_change AFTER INSERT OR UPDATE OR DELETE ON edges
+	FOR EACH ROW EXECUTE FUNCTION napkin_notify_change('edge');
+CREATE OR REPLACE TRIGGER node_metadata_notify_change AFTER INSERT OR UPDATE OR DELETE ON node_metadata
+	FOR EACH ROW EXECUTE FUNCTION napkin_notify_change('node_metadata');
+CREATE OR REPLACE TRIGGER edge_metadata_notify_change AFTER INSERT OR UPDATE OR DELETE ON edge_metadata
+	FOR EACH ROW EXECUTE FUNCTION napkin_notify_change('edge_metadata');